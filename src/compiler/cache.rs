@@ -0,0 +1,300 @@
+//! Remembers the classes of the last build, so that unchanged classes are not compiled again.
+//!
+//! The cache is a text file with one line per Jack file. A class is taken from the cache if
+//! the hash of its Jack file and the hash of the [`CompileOptions`] did not change, its output
+//! files still exist, and the classes it uses (e.g. `Point` in `do Point.new()`) still
+//! declare the same subroutines. The cache also remembers the warnings of each class.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::options::CompileOptions;
+use super::output::Diagnostic;
+use super::CompiledClass;
+use crate::parser::parse_tree::class::{ClassNode, ClassVarNode};
+use crate::parser::parse_tree::expression::SubroutineCall;
+use crate::parser::parse_tree::subroutine::{ParameterNode, SubroutineNode};
+use crate::parser::parse_tree::var::{VarNode, VarType};
+use crate::parser::parse_tree::visit::{self, Visitor};
+use crate::tokenizer::tokens::Position;
+
+/// The first line of a cache file. Changes whenever the format changes.
+const HEADER: &str = "jack-cache 2";
+
+/// The classes of the last build, read from the file [`CompileOptions::cache`],
+/// and the classes of the current build, which are written to the same file by
+/// [`CompileOutput::write`](super::output::CompileOutput::write).
+#[derive(Debug)]
+pub struct Cache {
+    path: PathBuf,
+    options: u64,
+    previous: HashMap<String, CacheEntry>,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+/// What the cache remembers about the class in a Jack file.
+#[derive(Clone, Debug)]
+pub(super) struct CacheEntry {
+    /// The hash of the Jack file.
+    pub(super) hash: u64,
+    pub(super) class: CompiledClass,
+    /// The signatures of the [`dependencies`](CompiledClass::dependencies) when the class
+    /// was compiled, or `None` if a dependency was not defined by the program.
+    pub(super) signatures: Vec<Option<u64>>,
+}
+
+impl Cache {
+    /// Reads the cache file at `path`. If it does not exist, cannot be read, or was written
+    /// with different options, the cache starts out empty.
+    pub fn load(path: &Path, options: &CompileOptions) -> Cache {
+        let options = hash_options(options);
+        let previous = match fs::read_to_string(path) {
+            Ok(text) => parse(&text, options).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        };
+        Cache {
+            path: path.to_path_buf(),
+            options,
+            previous,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Writes the classes of the current build to the cache file.
+    pub fn save(&self) -> io::Result<()> {
+        let mut text = format!("{} {:016x}\n", HEADER, self.options);
+        for (source, entry) in self.entries.iter() {
+            let class = &entry.class;
+            let dependencies: Vec<String> = class
+                .dependencies
+                .iter()
+                .zip(entry.signatures.iter())
+                .map(|(name, signature)| match signature {
+                    Some(signature) => format!("{}={:016x}", name, signature),
+                    None => format!("{}=-", name),
+                })
+                .collect();
+            text.push_str(&format!(
+                "{}\t{:016x}\t{}\t{}\t{}\t{}\t{:016x}\t{}",
+                source,
+                entry.hash,
+                class.name,
+                class.position.line,
+                class.position.column,
+                class.has_main as u8,
+                class.signature,
+                dependencies.join(",")
+            ));
+            // the warnings come last, because their messages may contain commas
+            for diagnostic in class.diagnostics.iter() {
+                text.push_str(&format!(
+                    "\t{},{},{}",
+                    diagnostic.position.line, diagnostic.position.column, diagnostic.message
+                ));
+            }
+            text.push('\n');
+        }
+        if let Some(dir) = self.path.parent() {
+            if !dir.as_os_str().is_empty() {
+                fs::create_dir_all(dir)?;
+            }
+        }
+        fs::write(&self.path, text)
+    }
+
+    /// Returns the entry of the last build for the Jack file `source`, if its content did not change.
+    pub(super) fn lookup(&self, source: &str, text: &str) -> Option<&CacheEntry> {
+        self.previous
+            .get(source)
+            .filter(|entry| entry.hash == hash(text.as_bytes()))
+    }
+
+    /// Remembers a class of the current build.
+    pub(super) fn insert(&mut self, source: &str, text: &str, class: CompiledClass, signatures: Vec<Option<u64>>) {
+        let entry = CacheEntry {
+            hash: hash(text.as_bytes()),
+            class,
+            signatures,
+        };
+        self.entries.insert(source.to_string(), entry);
+    }
+}
+
+/// Parses the lines of a cache file, see [`Cache::save`].
+fn parse(text: &str, options: u64) -> Option<HashMap<String, CacheEntry>> {
+    let mut lines = text.lines();
+    if lines.next()? != format!("{} {:016x}", HEADER, options) {
+        return None;
+    }
+    let mut entries = HashMap::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 8 {
+            return None;
+        }
+        let mut dependencies = Vec::new();
+        let mut signatures = Vec::new();
+        for dependency in fields[7].split(',').filter(|d| !d.is_empty()) {
+            let (name, signature) = dependency.split_once('=')?;
+            dependencies.push(name.to_string());
+            signatures.push(match signature {
+                "-" => None,
+                signature => Some(u64::from_str_radix(signature, 16).ok()?),
+            });
+        }
+        let mut diagnostics = Vec::new();
+        for diagnostic in fields[8..].iter() {
+            let mut parts = diagnostic.splitn(3, ',');
+            let position = Position {
+                line: parts.next()?.parse().ok()?,
+                column: parts.next()?.parse().ok()?,
+            };
+            diagnostics.push(Diagnostic {
+                position,
+                message: parts.next()?.to_string(),
+            });
+        }
+        let class = CompiledClass {
+            name: fields[2].to_string(),
+            source: fields[0].to_string(),
+            position: Position {
+                line: fields[3].parse().ok()?,
+                column: fields[4].parse().ok()?,
+            },
+            has_main: fields[5] == "1",
+            signature: u64::from_str_radix(fields[6], 16).ok()?,
+            dependencies,
+            diagnostics,
+        };
+        let entry = CacheEntry {
+            hash: u64::from_str_radix(fields[1], 16).ok()?,
+            class,
+            signatures,
+        };
+        entries.insert(fields[0].to_string(), entry);
+    }
+    Some(entries)
+}
+
+//------------------------------
+// HASHES
+//------------------------------
+
+/// The 64-bit FNV-1a hash. Unlike the hasher of the standard library, it is the same
+/// for every build of the compiler, so that it can be stored.
+fn hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Hashes the options that change the outputs, and the version of the compiler.
+fn hash_options(options: &CompileOptions) -> u64 {
+    let options = format!(
+        "{} {:?} {:?} {} {} {} {} {:?} {:?}",
+        env!("CARGO_PKG_VERSION"),
+        options.emit,
+        options.out_dir,
+        options.annotate,
+        options.debug_info,
+        options.allow_file_name_mismatch,
+        options.warnings,
+        options.optimization,
+        options.extensions
+    );
+    hash(options.as_bytes())
+}
+
+/// Hashes the declarations of the subroutines of the class, which is all that other
+/// classes see of it.
+pub(super) fn signature(class: &ClassNode) -> u64 {
+    let mut declarations = class.name.clone();
+    for subroutine in class.subroutines.iter() {
+        let return_type = match &subroutine.return_type {
+            Some(return_type) => return_type.to_string(),
+            None => "void".to_string(),
+        };
+        let parameters: Vec<String> = subroutine
+            .parameter_list
+            .parameters
+            .iter()
+            .map(|parameter| parameter.var_type.to_string())
+            .collect();
+        declarations.push_str(&format!(
+            "\n{} {} {}({})",
+            subroutine.subroutine_type,
+            return_type,
+            subroutine.name,
+            parameters.join(",")
+        ));
+    }
+    hash(declarations.as_bytes())
+}
+
+//------------------------------
+// DEPENDENCIES
+//------------------------------
+
+/// Returns the names of the classes the class uses as a type or calls a subroutine of,
+/// in sorted order.
+pub(super) fn dependencies(class: &ClassNode) -> Vec<String> {
+    let mut dependencies = Dependencies::default();
+    dependencies.visit_class(class);
+    dependencies.classes.remove(&class.name);
+    dependencies.classes.into_iter().collect()
+}
+
+#[derive(Default)]
+struct Dependencies {
+    class_vars: Vec<String>,
+    /// The parameters and local variables of the current subroutine.
+    locals: Vec<String>,
+    classes: BTreeSet<String>,
+}
+
+impl Dependencies {
+    fn add_type(&mut self, var_type: &VarType) {
+        if let VarType::ClassName(name) = var_type {
+            self.classes.insert(name.clone());
+        }
+    }
+}
+
+impl Visitor for Dependencies {
+    fn visit_class_var(&mut self, class_var: &ClassVarNode) {
+        self.class_vars.extend(class_var.names.iter().cloned());
+        self.add_type(&class_var.var_type);
+    }
+
+    fn visit_subroutine(&mut self, subroutine: &SubroutineNode) {
+        self.locals.clear();
+        if let Some(return_type) = &subroutine.return_type {
+            self.add_type(return_type);
+        }
+        visit::walk_subroutine(self, subroutine);
+    }
+
+    fn visit_parameter(&mut self, parameter: &ParameterNode) {
+        self.locals.push(parameter.name.clone());
+        self.add_type(&parameter.var_type);
+    }
+
+    fn visit_var(&mut self, var: &VarNode) {
+        self.locals.extend(var.var_names.iter().cloned());
+        self.add_type(&var.var_type);
+    }
+
+    fn visit_subroutine_call(&mut self, subroutine_call: &SubroutineCall) {
+        // a caller that is not a variable is a class name
+        if let Some(caller) = &subroutine_call.caller {
+            if !self.locals.contains(caller) && !self.class_vars.contains(caller) {
+                self.classes.insert(caller.clone());
+            }
+        }
+        visit::walk_subroutine_call(self, subroutine_call);
+    }
+}
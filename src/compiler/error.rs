@@ -0,0 +1,86 @@
+//! Defines compiler errors.
+use std::error;
+use std::fmt;
+use std::io;
+
+use crate::parser::error::ParseError;
+use crate::tokenizer::error::LexError;
+use crate::tokenizer::tokens::Position;
+use crate::vm_writer::error::WriteError;
+
+/// This error occurs when a Jack file cannot be read, tokenized, parsed or written, when two Jack
+/// files define the same class, or when the entry class of a program is missing.
+#[derive(Debug)]
+pub enum CompileError {
+    /// The Jack files could not be listed or read.
+    Io { name: String, error: io::Error },
+    /// A Jack file contains an invalid token.
+    Lex { name: String, error: LexError },
+    /// A Jack file contains a syntax error.
+    Parse { name: String, error: ParseError },
+    /// A Jack file uses an undeclared variable or another construct that has no VM code.
+    Write { name: String, error: WriteError },
+    /// The class at `position` in the Jack file `name` is already defined in the Jack file `first`.
+    DuplicateClass {
+        name: String,
+        class: String,
+        position: Position,
+        first: String,
+    },
+    /// No Jack file defines the entry class of the program.
+    MissingEntry { class: String },
+    /// The entry class at `position` in the Jack file `name` has no function `main`.
+    MissingMain {
+        name: String,
+        class: String,
+        position: Position,
+    },
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::Io { name, error } if name.is_empty() => write!(f, "Error: {}", error),
+            CompileError::Io { name, error } => write!(f, "{}: {}", name, error),
+            CompileError::Lex { name, error } => write!(f, "{}: {}", name, error),
+            CompileError::Parse { name, error } => write!(f, "{}: {}", name, error),
+            CompileError::Write { name, error } => write!(f, "{}: {}", name, error),
+            CompileError::DuplicateClass {
+                name,
+                class,
+                position,
+                first,
+            } => write!(
+                f,
+                "{}: Error in line {}, column {}: Class \"{}\" is already defined in '{}'",
+                name, position.line, position.column, class, first
+            ),
+            CompileError::MissingEntry { class } => {
+                write!(f, "Error: the entry class \"{}\" is not defined", class)
+            }
+            CompileError::MissingMain {
+                name,
+                class,
+                position,
+            } => write!(
+                f,
+                "{}: Error in line {}, column {}: Class \"{}\" has no function \"main\"",
+                name, position.line, position.column, class
+            ),
+        }
+    }
+}
+
+impl error::Error for CompileError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CompileError::Io { error, .. } => Some(error),
+            CompileError::Lex { error, .. } => Some(error),
+            CompileError::Parse { error, .. } => Some(error),
+            CompileError::Write { error, .. } => Some(error),
+            CompileError::DuplicateClass { .. }
+            | CompileError::MissingEntry { .. }
+            | CompileError::MissingMain { .. } => None,
+        }
+    }
+}
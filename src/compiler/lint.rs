@@ -0,0 +1,80 @@
+//! Finds code that compiles, but is probably a mistake.
+use super::output::Diagnostic;
+use crate::parser::parse_tree::class::ClassNode;
+use crate::parser::parse_tree::expression::{Expr, SubroutineCall};
+use crate::parser::parse_tree::statement::{LetStatementNode, LetTarget};
+use crate::parser::parse_tree::subroutine::SubroutineNode;
+use crate::parser::parse_tree::var::VarNode;
+use crate::parser::parse_tree::visit::{self, Visitor};
+use crate::tokenizer::tokens::Position;
+
+/// Returns the warnings about the class, in source order.
+pub(super) fn warnings(class: &ClassNode) -> Vec<Diagnostic> {
+    let mut unused = UnusedLocals::default();
+    unused.visit_class(class);
+    unused.diagnostics
+}
+
+/// Reports the local variables that are declared, but never used in their subroutine.
+#[derive(Default)]
+struct UnusedLocals {
+    /// The local variables of the current subroutine and the position of their declaration.
+    locals: Vec<(String, Position)>,
+    used: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl UnusedLocals {
+    fn use_name(&mut self, name: &str) {
+        self.used.push(name.to_string());
+    }
+}
+
+impl Visitor for UnusedLocals {
+    fn visit_subroutine(&mut self, subroutine: &SubroutineNode) {
+        self.locals.clear();
+        self.used.clear();
+        visit::walk_subroutine(self, subroutine);
+        for (name, position) in self.locals.iter() {
+            if !self.used.contains(name) {
+                self.diagnostics.push(Diagnostic {
+                    position: *position,
+                    message: format!(
+                        "Local variable \"{}\" of subroutine \"{}\" is never used",
+                        name, subroutine.name
+                    ),
+                });
+            }
+        }
+    }
+
+    fn visit_var(&mut self, var: &VarNode) {
+        for name in var.var_names.iter() {
+            self.locals.push((name.clone(), var.position));
+        }
+    }
+
+    fn visit_let_statement(&mut self, statement: &LetStatementNode) {
+        match &statement.target {
+            LetTarget::Var(name) => self.use_name(name),
+            LetTarget::Index { name, .. } => self.use_name(name),
+        }
+        visit::walk_let_statement(self, statement);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Var(name) => self.use_name(name),
+            Expr::Index { name, .. } => self.use_name(name),
+            _ => (),
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_subroutine_call(&mut self, subroutine_call: &SubroutineCall) {
+        if let Some(caller) = &subroutine_call.caller {
+            self.use_name(caller);
+        }
+        visit::walk_subroutine_call(self, subroutine_call);
+    }
+}
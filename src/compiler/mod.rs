@@ -0,0 +1,459 @@
+//! Compiles Jack code into VM code, the single entry point for tools that embed the compiler.
+pub mod cache;
+use cache::{Cache, CacheEntry};
+
+pub mod error;
+use error::CompileError;
+
+mod lint;
+
+pub mod options;
+use options::{CompileOptions, Extension, OptLevel};
+
+pub mod output;
+use output::{Artifact, ArtifactKind, ClassOutput, CompileOutput, Diagnostic};
+
+mod pool;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::io::source::{FileProvider, Source};
+use crate::parser::parse_tree::subroutine::SubroutineType;
+use crate::parser::parse_tree::ParseTree;
+use crate::tokenizer::tokens::Position;
+use crate::parser::Parser;
+use crate::tokenizer::tokens::TokenWrapper;
+use crate::tokenizer::Tokenizer;
+use crate::vm_writer::VMWriter;
+use crate::xml_writer::XmlWriter;
+
+/// Runs the tokenizer, the parser and the writers on Jack code, as configured
+/// by the [`CompileOptions`].
+pub struct Compiler {
+    options: CompileOptions,
+    tokenizer: Tokenizer,
+    parser: Parser,
+    vm_writer: VMWriter,
+    xml_writer: XmlWriter,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    /// Returns a compiler with the default [`CompileOptions`].
+    pub fn new() -> Compiler {
+        Compiler::with_options(CompileOptions::default())
+    }
+
+    pub fn with_options(options: CompileOptions) -> Compiler {
+        Compiler {
+            tokenizer: Tokenizer::new(),
+            parser: Parser::new()
+                .allow_file_name_mismatch(options.allow_file_name_mismatch)
+                .operator_precedence(options.extensions.contains(&Extension::OperatorPrecedence)),
+            vm_writer: VMWriter::new()
+                .annotate(options.annotate)
+                .fold_constants(options.optimization >= OptLevel::O1),
+            xml_writer: XmlWriter::new(),
+            options,
+        }
+    }
+
+    pub fn options(&self) -> &CompileOptions {
+        &self.options
+    }
+
+    /// Compiles the Jack code `text` and returns the VM code.
+    /// `name` is the name of the Jack file, e.g. `Main.jack`, and must match the class name.
+    pub fn compile_str(&self, name: &str, text: &str) -> Result<String, CompileError> {
+        let (_, parse_tree) = self.parse(name, text)?;
+        self.vm_writer.write(&parse_tree).map_err(|error| CompileError::Write {
+            name: name.to_string(),
+            error,
+        })
+    }
+
+    /// Compiles the given [`Source`] and returns the outputs selected by
+    /// [`emit`](CompileOptions::emit). In the output directory, the outputs are not
+    /// put in subdirectories.
+    pub fn compile(&self, source: &Source) -> Result<ClassOutput, CompileError> {
+        let (tokens, parse_tree) = self.parse(&source.name, &source.text)?;
+        let diagnostics = self.lint(&parse_tree);
+        self.write(source, Path::new(""), tokens, &parse_tree, diagnostics)
+    }
+
+    /// Compiles every Jack file of the given [`FileProvider`], in the order of
+    /// [`jack_files`](FileProvider::jack_files). In the output directory, the outputs
+    /// are put in the same subdirectories as the Jack files.
+    /// Returns an error if two files define the same class.
+    ///
+    /// If a [`cache`](CompileOptions::cache) is given, the classes that did not change
+    /// since the last build are read back from their output files instead.
+    pub fn compile_files(&self, files: &dyn FileProvider) -> Result<CompileOutput, CompileError> {
+        let mut output = CompileOutput::default();
+        let mut classes = HashMap::new();
+        let mut cache = self.load_cache();
+        self.compile_into(files, &mut classes, Some(&mut output), cache.as_mut())?;
+        output.cache = cache;
+        Ok(output)
+    }
+
+    /// Compiles a program like [`compile_files`](Compiler::compile_files).
+    /// The classes of `libraries` are read as well, so that they cannot be defined twice,
+    /// but produce no outputs. Returns an error if the `entry` class is not defined
+    /// or has no function `main`.
+    pub fn compile_program(
+        &self,
+        files: &dyn FileProvider,
+        libraries: &dyn FileProvider,
+        entry: &str,
+    ) -> Result<CompileOutput, CompileError> {
+        let mut output = CompileOutput::default();
+        let mut classes = HashMap::new();
+        let mut cache = self.load_cache();
+        self.compile_into(libraries, &mut classes, None, cache.as_mut())?;
+        output.libraries = classes.keys().cloned().collect();
+        output.libraries.sort();
+        self.compile_into(files, &mut classes, Some(&mut output), cache.as_mut())?;
+        output.cache = cache;
+        match classes.get(entry) {
+            Some(class) if !class.has_main => Err(CompileError::MissingMain {
+                name: class.source.clone(),
+                class: entry.to_string(),
+                position: class.position,
+            }),
+            Some(_) => Ok(output),
+            None => Err(CompileError::MissingEntry {
+                class: entry.to_string(),
+            }),
+        }
+    }
+
+    /// Returns the cache of the last build, if a cache file is configured.
+    fn load_cache(&self) -> Option<Cache> {
+        self.options
+            .cache
+            .as_ref()
+            .map(|path| Cache::load(path, &self.options))
+    }
+
+    /// Compiles the Jack files and adds the outputs to `output`, if it is given.
+    /// `classes` holds the classes that were compiled before.
+    ///
+    /// The files are read one after the other, then tokenized, parsed and written on
+    /// [`worker_threads`](CompileOptions::worker_threads) threads. The results are
+    /// checked in the order of the files, so that the first error is always the same.
+    fn compile_into(
+        &self,
+        files: &dyn FileProvider,
+        classes: &mut HashMap<String, CompiledClass>,
+        mut output: Option<&mut CompileOutput>,
+        mut cache: Option<&mut Cache>,
+    ) -> Result<(), CompileError> {
+        let names = files.jack_files().map_err(|error| CompileError::Io {
+            name: String::new(),
+            error,
+        })?;
+        let mut sources = Vec::new();
+        for name in names {
+            let text = files.read(&name).map_err(|error| CompileError::Io {
+                name: name.clone(),
+                error,
+            });
+            let relative_path = files.relative_path(&name);
+            let dir = relative_path.parent().unwrap_or(Path::new("")).to_path_buf();
+            sources.push(text.map(|text| (Source { name, text }, dir)));
+        }
+        let write = output.is_some();
+
+        // the classes that did not change since the last build
+        let mut results: Vec<Option<Result<Compiled, CompileError>>> = sources
+            .iter()
+            .map(|source| {
+                let (source, dir) = source.as_ref().ok()?;
+                let entry = cache.as_deref()?.lookup(&source.name, &source.text)?;
+                self.reuse(source, dir, entry, write).map(Ok)
+            })
+            .collect();
+        self.compile_missing(&sources, &mut results, write);
+
+        // a class is compiled again if a class it uses declares different subroutines now
+        let mut signatures: HashMap<String, u64> = classes
+            .iter()
+            .map(|(name, class)| (name.clone(), class.signature))
+            .collect();
+        for compiled in results.iter().flatten().flatten() {
+            signatures
+                .entry(compiled.class.name.clone())
+                .or_insert(compiled.class.signature);
+        }
+        let current = |dependencies: &[String]| -> Vec<Option<u64>> {
+            dependencies
+                .iter()
+                .map(|name| signatures.get(name).copied())
+                .collect()
+        };
+        for result in results.iter_mut() {
+            if let Some(Ok(Compiled {
+                class,
+                signatures: Some(previous),
+                ..
+            })) = result
+            {
+                if *previous != current(&class.dependencies) {
+                    *result = None;
+                }
+            }
+        }
+        self.compile_missing(&sources, &mut results, write);
+
+        for (source, result) in sources.into_iter().zip(results) {
+            let (source, _) = source?;
+            let compiled = result.expect("every Jack file that was read is compiled")?;
+            let class = compiled.class;
+            if let Some(first) = classes.get(&class.name) {
+                return Err(CompileError::DuplicateClass {
+                    name: source.name,
+                    class: class.name,
+                    position: class.position,
+                    first: first.source.clone(),
+                });
+            }
+            if let Some(cache) = cache.as_mut() {
+                let signatures = current(&class.dependencies);
+                cache.insert(&source.name, &source.text, class.clone(), signatures);
+            }
+            if let (Some(output), Some(class_output)) = (output.as_mut(), compiled.output) {
+                output.classes.push(class_output);
+            }
+            classes.insert(class.name.clone(), class);
+        }
+        Ok(())
+    }
+
+    /// Compiles the Jack files that have no result yet on the worker threads.
+    fn compile_missing(
+        &self,
+        sources: &[Result<(Source, PathBuf), CompileError>],
+        results: &mut [Option<Result<Compiled, CompileError>>],
+        write: bool,
+    ) {
+        let missing: Vec<(usize, &Source, &PathBuf)> = sources
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| results[*idx].is_none())
+            .filter_map(|(idx, source)| source.as_ref().ok().map(|(source, dir)| (idx, source, dir)))
+            .collect();
+        let compiled = pool::map(self.options.worker_threads(), &missing, |(_, source, dir)| {
+            self.compile_class(source, dir, write)
+        });
+        for ((idx, _, _), result) in missing.into_iter().zip(compiled) {
+            results[idx] = Some(result);
+        }
+    }
+
+    /// Compiles a single class, and writes its outputs if `write` is true.
+    fn compile_class(&self, source: &Source, dir: &Path, write: bool) -> Result<Compiled, CompileError> {
+        let (tokens, parse_tree) = self.parse(&source.name, &source.text)?;
+        let class = &parse_tree.class_node;
+        let has_main = class.subroutines.iter().any(|subroutine| {
+            subroutine.name == "main" && subroutine.subroutine_type == SubroutineType::Function
+        });
+        let compiled_class = CompiledClass {
+            name: class.name.clone(),
+            source: source.name.clone(),
+            position: class.position,
+            has_main,
+            signature: cache::signature(class),
+            dependencies: cache::dependencies(class),
+            diagnostics: self.lint(&parse_tree),
+        };
+        let output = if write {
+            Some(self.write(source, dir, tokens, &parse_tree, compiled_class.diagnostics.clone())?)
+        } else {
+            None
+        };
+        Ok(Compiled {
+            class: compiled_class,
+            output,
+            signatures: None,
+        })
+    }
+
+    /// Takes a class from the cache. If `write` is true, its outputs are read back from
+    /// the output files. Returns `None` if one of them is missing.
+    fn reuse(&self, source: &Source, dir: &Path, entry: &CacheEntry, write: bool) -> Option<Compiled> {
+        let output = if write {
+            let mut artifacts = Vec::new();
+            for kind in self.artifact_kinds() {
+                let path = self.artifact_path(source, dir, &entry.class.name, kind);
+                let content = fs::read_to_string(&path).ok()?;
+                artifacts.push(Artifact { kind, path, content });
+            }
+            Some(ClassOutput {
+                name: entry.class.name.clone(),
+                source: source.name.clone(),
+                artifacts,
+                diagnostics: entry.class.diagnostics.clone(),
+                up_to_date: true,
+            })
+        } else {
+            None
+        };
+        Some(Compiled {
+            class: entry.class.clone(),
+            output,
+            signatures: Some(entry.signatures.clone()),
+        })
+    }
+
+    /// Returns the parse tree, and the tokens if they are emitted.
+    fn parse(
+        &self,
+        name: &str,
+        text: &str,
+    ) -> Result<(Option<Vec<TokenWrapper>>, ParseTree), CompileError> {
+        let tokens = self
+            .tokenizer
+            .tokenize(text)
+            .map_err(|error| CompileError::Lex {
+                name: name.to_string(),
+                error,
+            })?;
+        let emitted_tokens = if self.options.emit.tokens {
+            Some(tokens.clone())
+        } else {
+            None
+        };
+        let parse_tree = self
+            .parser
+            .parse(tokens, name)
+            .map_err(|error| CompileError::Parse {
+                name: name.to_string(),
+                error,
+            })?;
+        Ok((emitted_tokens, parse_tree))
+    }
+
+    /// Returns the warnings about the class, if they are enabled.
+    fn lint(&self, parse_tree: &ParseTree) -> Vec<Diagnostic> {
+        if self.options.warnings {
+            lint::warnings(&parse_tree.class_node)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// `dir` is the directory of the outputs relative to the output directory.
+    fn write(
+        &self,
+        source: &Source,
+        dir: &Path,
+        tokens: Option<Vec<TokenWrapper>>,
+        parse_tree: &ParseTree,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Result<ClassOutput, CompileError> {
+        let emit = &self.options.emit;
+        let mut artifacts = Vec::new();
+        let mut add = |kind: ArtifactKind, content: String| {
+            artifacts.push(Artifact {
+                kind,
+                path: self.artifact_path(source, dir, &parse_tree.class_node.name, kind),
+                content,
+            });
+        };
+        if let Some(tokens) = tokens {
+            add(ArtifactKind::Tokens, self.tokenizer.write_xml(tokens));
+        }
+        if emit.xml {
+            add(ArtifactKind::Xml, self.xml_writer.write(parse_tree));
+        }
+        let write_error = |error| CompileError::Write {
+            name: source.name.clone(),
+            error,
+        };
+        if emit.vm {
+            if self.options.debug_info {
+                let file = match source.name.rfind('/') {
+                    Some(idx) => &source.name[idx + 1..],
+                    None => &source.name,
+                };
+                let (vm_code, debug_info) = self.vm_writer
+                    .write_with_debug_info(parse_tree, file)
+                    .map_err(write_error)?;
+                add(ArtifactKind::Vm, vm_code);
+                add(ArtifactKind::DebugInfo, debug_info.to_json());
+            } else {
+                add(ArtifactKind::Vm, self.vm_writer.write(parse_tree).map_err(write_error)?);
+            }
+        }
+        Ok(ClassOutput {
+            name: parse_tree.class_node.name.clone(),
+            source: source.name.clone(),
+            artifacts,
+            diagnostics,
+            up_to_date: false,
+        })
+    }
+
+    /// Returns the kinds of the artifacts that are produced for each class, in the order
+    /// of [`write`](Compiler::write).
+    fn artifact_kinds(&self) -> Vec<ArtifactKind> {
+        let emit = &self.options.emit;
+        let mut kinds = Vec::new();
+        if emit.tokens {
+            kinds.push(ArtifactKind::Tokens);
+        }
+        if emit.xml {
+            kinds.push(ArtifactKind::Xml);
+        }
+        if emit.vm {
+            kinds.push(ArtifactKind::Vm);
+            if self.options.debug_info {
+                kinds.push(ArtifactKind::DebugInfo);
+            }
+        }
+        kinds
+    }
+
+    /// Returns the path of the artifact of the given class: next to the Jack file, or in
+    /// `dir` inside the output directory.
+    fn artifact_path(&self, source: &Source, dir: &Path, class: &str, kind: ArtifactKind) -> PathBuf {
+        let file_name = format!("{}{}", class, kind.suffix());
+        match &self.options.out_dir {
+            Some(out_dir) => out_dir.join(dir).join(file_name),
+            None => match Path::new(&source.name).parent() {
+                Some(source_dir) => source_dir.join(file_name),
+                None => PathBuf::from(file_name),
+            },
+        }
+    }
+}
+
+/// What a build remembers about a class that was compiled before.
+#[derive(Clone, Debug)]
+struct CompiledClass {
+    name: String,
+    source: String,
+    position: Position,
+    has_main: bool,
+    /// The hash of the subroutine declarations, see [`cache::signature`].
+    signature: u64,
+    /// The classes the class uses, see [`cache::dependencies`].
+    dependencies: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A class compiled on a worker thread, or taken from the cache.
+struct Compiled {
+    class: CompiledClass,
+    output: Option<ClassOutput>,
+    /// If the class was taken from the cache, the signatures of its dependencies when it was compiled.
+    signatures: Option<Vec<Option<u64>>>,
+}
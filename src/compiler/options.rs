@@ -0,0 +1,112 @@
+//! Configures the [`Compiler`](super::Compiler).
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::thread;
+
+/// Configures the [`Compiler`](super::Compiler).
+#[derive(Clone, Debug, Default)]
+pub struct CompileOptions {
+    /// The outputs to produce for each class.
+    pub emit: Emit,
+    /// The directory the outputs are written to, in the same subdirectories as the Jack files.
+    /// If `None`, each output is written next to its Jack file.
+    pub out_dir: Option<PathBuf>,
+    /// Interleave the VM code with comments showing the Jack source,
+    /// see [`VMWriter::annotate`](crate::vm_writer::VMWriter::annotate).
+    pub annotate: bool,
+    /// Produce a `<NAME>.vmdbg` file next to each `<NAME>.vm` file.
+    pub debug_info: bool,
+    /// Do not require the class name to match the file name.
+    pub allow_file_name_mismatch: bool,
+    /// Report warnings in the [`diagnostics`](super::output::ClassOutput::diagnostics)
+    /// of each class, e.g. for local variables that are never used.
+    pub warnings: bool,
+    /// The number of classes compiled at the same time. If `0`, one class is compiled
+    /// per available CPU core. The outputs and errors do not depend on this number.
+    pub jobs: usize,
+    /// The file that remembers the classes of the last build, see [`Cache`](super::cache::Cache).
+    /// If `None`, every class is compiled.
+    pub cache: Option<PathBuf>,
+    /// How much the VM code is optimized.
+    pub optimization: OptLevel,
+    /// The extensions of the Jack language the classes may use.
+    pub extensions: BTreeSet<Extension>,
+}
+
+impl CompileOptions {
+    /// Returns the number of worker threads, see [`jobs`](CompileOptions::jobs).
+    pub fn worker_threads(&self) -> usize {
+        match self.jobs {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            jobs => jobs,
+        }
+    }
+}
+
+/// The outputs produced for each class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Emit {
+    /// `<NAME>T.xml`, the tokens as XML.
+    pub tokens: bool,
+    /// `<NAME>.xml`, the parse tree as XML.
+    pub xml: bool,
+    /// `<NAME>.vm`, the VM code.
+    pub vm: bool,
+}
+
+impl Default for Emit {
+    /// Only the VM code.
+    fn default() -> Self {
+        Emit {
+            tokens: false,
+            xml: false,
+            vm: true,
+        }
+    }
+}
+
+/// How much the VM code is optimized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptLevel {
+    /// The VM code of the reference compiler.
+    #[default]
+    O0,
+    /// Also folds expressions of constants, e.g. `60 * 60` is pushed as `3600`,
+    /// see [`VMWriter::fold_constants`](crate::vm_writer::VMWriter::fold_constants).
+    O1,
+}
+
+impl OptLevel {
+    /// Returns the level with the given number, e.g. `1` for [`O1`](OptLevel::O1).
+    pub fn get(level: &str) -> Option<OptLevel> {
+        match level {
+            "0" => Some(OptLevel::O0),
+            "1" => Some(OptLevel::O1),
+            _ => None,
+        }
+    }
+}
+
+/// An extension of the Jack language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Extension {
+    /// The binary operators have the usual precedence instead of being evaluated
+    /// from left to right, see [`BinaryOp::precedence`](crate::parser::parse_tree::expression::BinaryOp::precedence).
+    OperatorPrecedence,
+}
+
+impl Extension {
+    /// Returns the extension with the given name, e.g. `precedence`.
+    pub fn get(name: &str) -> Option<Extension> {
+        match name {
+            "precedence" => Some(Extension::OperatorPrecedence),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Extension::OperatorPrecedence => "precedence",
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! The outputs of the [`Compiler`](super::Compiler).
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::cache::Cache;
+use crate::tokenizer::tokens::Position;
+
+/// The outputs of a build, one entry per class in the order the Jack files were compiled.
+#[derive(Debug, Default)]
+pub struct CompileOutput {
+    pub classes: Vec<ClassOutput>,
+    /// The names of the library classes of a [`compile_program`](super::Compiler::compile_program)
+    /// build. They are read, but have no outputs.
+    pub libraries: Vec<String>,
+    /// The classes of this build, if a [`cache`](super::options::CompileOptions::cache) is used.
+    pub cache: Option<Cache>,
+}
+
+impl CompileOutput {
+    /// Returns the outputs of the class with the given name.
+    pub fn class(&self, name: &str) -> Option<&ClassOutput> {
+        self.classes.iter().find(|class| class.name == name)
+    }
+
+    /// Writes every artifact to its path, except for the classes that are
+    /// [`up_to_date`](ClassOutput::up_to_date). Missing directories are created.
+    /// Afterwards, the cache is saved.
+    pub fn write(&self) -> io::Result<()> {
+        for class in self.classes.iter().filter(|class| !class.up_to_date) {
+            for artifact in class.artifacts.iter() {
+                if let Some(dir) = artifact.path.parent() {
+                    if !dir.as_os_str().is_empty() {
+                        fs::create_dir_all(dir)?;
+                    }
+                }
+                fs::write(&artifact.path, &artifact.content)?;
+            }
+        }
+        if let Some(cache) = &self.cache {
+            cache.save()?;
+        }
+        Ok(())
+    }
+
+    /// Returns how many classes were rebuilt, e.g. `Rebuilt 2 of 15 classes (13 up to date)`.
+    pub fn summary(&self) -> String {
+        let up_to_date = self.classes.iter().filter(|class| class.up_to_date).count();
+        format!(
+            "Rebuilt {} of {} classes ({} up to date)",
+            self.classes.len() - up_to_date,
+            self.classes.len(),
+            up_to_date
+        )
+    }
+
+    /// Writes the content of every artifact to `out`, e.g. standard output.
+    /// If there is more than one artifact, each one is preceded by a line `==> <PATH> <==`.
+    pub fn print(&self, out: &mut dyn Write) -> io::Result<()> {
+        let artifacts: Vec<&Artifact> = self
+            .classes
+            .iter()
+            .flat_map(|class| class.artifacts.iter())
+            .collect();
+        for (i, artifact) in artifacts.iter().enumerate() {
+            if artifacts.len() > 1 {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                writeln!(out, "==> {} <==", artifact.path.display())?;
+            }
+            out.write_all(artifact.content.as_bytes())?;
+            if artifacts.len() > 1 && !artifact.content.is_empty() && !artifact.content.ends_with('\n') {
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The outputs of a single class.
+#[derive(Debug)]
+pub struct ClassOutput {
+    /// The class name.
+    pub name: String,
+    /// The name of the Jack file the class was read from.
+    pub source: String,
+    pub artifacts: Vec<Artifact>,
+    /// The warnings about the class, if [`warnings`](super::options::CompileOptions::warnings)
+    /// are enabled. They are remembered by the cache, so up-to-date classes report them again.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The artifacts were read back from their files, because the class did not change
+    /// since the last build. They are not written again.
+    pub up_to_date: bool,
+}
+
+impl ClassOutput {
+    /// Returns the artifact of the given kind, if it was produced.
+    pub fn artifact(&self, kind: ArtifactKind) -> Option<&Artifact> {
+        self.artifacts.iter().find(|artifact| artifact.kind == kind)
+    }
+}
+
+/// A warning about a class. Unlike a [`CompileError`](super::error::CompileError),
+/// it does not stop the class from being compiled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub position: Position,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Warning in line {}, column {}: {}",
+            self.position.line, self.position.column, self.message
+        )
+    }
+}
+
+/// A file produced by the [`Compiler`](super::Compiler). It is only written to disk
+/// by [`CompileOutput::write`].
+#[derive(Debug)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// The kinds of [`Artifact`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// `<NAME>T.xml`, where `<NAME>` is the class name
+    Tokens,
+    /// `<NAME>.xml`
+    Xml,
+    /// `<NAME>.vm`
+    Vm,
+    /// `<NAME>.vmdbg`
+    DebugInfo,
+}
+
+impl ArtifactKind {
+    /// Returns the suffix that is appended to the class name to get the file name.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            ArtifactKind::Tokens => "T.xml",
+            ArtifactKind::Xml => ".xml",
+            ArtifactKind::Vm => ".vm",
+            ArtifactKind::DebugInfo => ".vmdbg",
+        }
+    }
+}
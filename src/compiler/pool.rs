@@ -0,0 +1,48 @@
+//! Runs the compilation of several classes on a pool of worker threads.
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Applies `f` to every item on `jobs` threads and returns the results in the order
+/// of the items, no matter which thread finished first. The threads take the next
+/// item as soon as they are done with the previous one.
+pub(super) fn map<T, R, F>(jobs: usize, items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let jobs = jobs.min(items.len());
+    if jobs <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(idx) {
+                            Some(item) => done.push((idx, f(item))),
+                            None => return done,
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            match worker.join() {
+                Ok(done) => {
+                    for (idx, result) in done {
+                        results[idx] = Some(result);
+                    }
+                }
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
+    });
+    results.into_iter().map(|result| result.unwrap()).collect()
+}
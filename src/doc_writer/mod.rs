@@ -0,0 +1,269 @@
+//! Takes in the classes of a program and writes an API reference from their `/** ... */` comments.
+use std::collections::BTreeSet;
+
+use crate::parser::parse_tree::class::{ClassNode, ClassVarNode};
+use crate::parser::parse_tree::subroutine::SubroutineNode;
+use crate::parser::parse_tree::var::VarType;
+
+/// The format of the pages written by the [`DocWriter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocFormat {
+    Html,
+    Markdown,
+}
+
+impl DocFormat {
+    /// Returns the format with the given name, `html` or `markdown` (or `md`).
+    pub fn get(name: &str) -> Option<DocFormat> {
+        match name {
+            "html" => Some(DocFormat::Html),
+            "markdown" | "md" => Some(DocFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Returns the file extension of the pages, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Html => "html",
+            DocFormat::Markdown => "md",
+        }
+    }
+}
+
+/// A page of the API reference.
+#[derive(Debug)]
+pub struct DocPage {
+    /// The name of the file, e.g. `Square.html`.
+    pub file_name: String,
+    pub content: String,
+}
+
+/// Writes an index page and one page per class. A page lists the doc comment of the class,
+/// its variables and the signatures of its subroutines with their doc comments.
+/// Class names used as types link to the page of the class.
+pub struct DocWriter {
+    format: DocFormat,
+}
+
+impl Default for DocWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocWriter {
+    /// Returns a writer for HTML pages.
+    pub fn new() -> DocWriter {
+        DocWriter {
+            format: DocFormat::Html,
+        }
+    }
+
+    /// Sets the format of the pages.
+    pub fn format(mut self, format: DocFormat) -> DocWriter {
+        self.format = format;
+        self
+    }
+
+    /// Returns the index page, followed by the pages of the classes in the order of their names.
+    pub fn write(&self, classes: &[&ClassNode]) -> Vec<DocPage> {
+        let mut classes = classes.to_vec();
+        classes.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: BTreeSet<&str> = classes.iter().map(|class| class.name.as_str()).collect();
+        let mut pages = vec![DocPage {
+            file_name: format!("index.{}", self.format.extension()),
+            content: self.write_index(&classes),
+        }];
+        for class in classes.iter() {
+            pages.push(DocPage {
+                file_name: self.file_name(&class.name),
+                content: self.write_class(class, &names),
+            });
+        }
+        pages
+    }
+
+    fn file_name(&self, class_name: &str) -> String {
+        format!("{}.{}", class_name, self.format.extension())
+    }
+
+    //------------------------------
+    // PAGES
+    //------------------------------
+
+    fn write_index(&self, classes: &[&ClassNode]) -> String {
+        let mut items = String::new();
+        for class in classes.iter() {
+            let summary = class.doc.as_deref().map(summary).unwrap_or_default();
+            items.push_str(&match self.format {
+                DocFormat::Html => format!(
+                    "<li><a href=\"{}\">{}</a>{}</li>\n",
+                    self.file_name(&class.name),
+                    class.name,
+                    if summary.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" &ndash; {}", escape(summary))
+                    }
+                ),
+                DocFormat::Markdown => format!(
+                    "- [{}]({}){}\n",
+                    class.name,
+                    self.file_name(&class.name),
+                    if summary.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" - {}", summary)
+                    }
+                ),
+            });
+        }
+        match self.format {
+            DocFormat::Html => html_page("Classes", &format!("<h1>Classes</h1>\n<ul>\n{}</ul>\n", items)),
+            DocFormat::Markdown => format!("# Classes\n\n{}", items),
+        }
+    }
+
+    fn write_class(&self, class: &ClassNode, names: &BTreeSet<&str>) -> String {
+        let mut content = match self.format {
+            DocFormat::Html => format!("<h1>class {}</h1>\n", class.name),
+            DocFormat::Markdown => format!("# class {}\n\n", class.name),
+        };
+        content.push_str(&self.write_doc(&class.doc));
+        if !class.variables.is_empty() {
+            content.push_str(&self.heading("Variables"));
+            for class_var in class.variables.iter() {
+                content.push_str(&self.write_class_var(class_var, names));
+            }
+        }
+        if !class.subroutines.is_empty() {
+            content.push_str(&self.heading("Subroutines"));
+            for subroutine in class.subroutines.iter() {
+                content.push_str(&self.write_subroutine(subroutine, names));
+            }
+        }
+        match self.format {
+            DocFormat::Html => html_page(&class.name, &format!("<p><a href=\"index.html\">Classes</a></p>\n{}", content)),
+            DocFormat::Markdown => format!("[Classes](index.md)\n\n{}", content),
+        }
+    }
+
+    fn write_class_var(&self, class_var: &ClassVarNode, names: &BTreeSet<&str>) -> String {
+        let signature = format!(
+            "{} {} {}",
+            class_var.var_kind,
+            self.write_var_type(&class_var.var_type, names),
+            class_var.names.join(", ")
+        );
+        self.write_item(&signature, &class_var.doc)
+    }
+
+    fn write_subroutine(&self, subroutine: &SubroutineNode, names: &BTreeSet<&str>) -> String {
+        let return_type = match &subroutine.return_type {
+            Some(return_type) => self.write_var_type(return_type, names),
+            None => "void".to_string(),
+        };
+        let parameters: Vec<String> = subroutine
+            .parameter_list
+            .parameters
+            .iter()
+            .map(|parameter| format!("{} {}", self.write_var_type(&parameter.var_type, names), parameter.name))
+            .collect();
+        let signature = format!(
+            "{} {} {}({})",
+            subroutine.subroutine_type,
+            return_type,
+            subroutine.name,
+            parameters.join(", ")
+        );
+        self.write_item(&signature, &subroutine.doc)
+    }
+
+    //------------------------------
+    // ELEMENTS
+    //------------------------------
+
+    fn heading(&self, title: &str) -> String {
+        match self.format {
+            DocFormat::Html => format!("<h2>{}</h2>\n", title),
+            DocFormat::Markdown => format!("## {}\n\n", title),
+        }
+    }
+
+    /// Writes a signature and the doc comment below it.
+    fn write_item(&self, signature: &str, doc: &Option<String>) -> String {
+        let signature = match self.format {
+            DocFormat::Html => format!("<h3><code>{}</code></h3>\n", signature),
+            DocFormat::Markdown => format!("### {}\n\n", signature),
+        };
+        format!("{}{}", signature, self.write_doc(doc))
+    }
+
+    /// Writes a doc comment, with a paragraph for every block of lines.
+    fn write_doc(&self, doc: &Option<String>) -> String {
+        let mut content = String::new();
+        for paragraph in doc.iter().flat_map(|doc| paragraphs(doc)) {
+            content.push_str(&match self.format {
+                DocFormat::Html => format!("<p>{}</p>\n", escape(&paragraph)),
+                DocFormat::Markdown => format!("{}\n\n", paragraph),
+            });
+        }
+        content
+    }
+
+    /// Writes a type, with a link if it is a class of the program.
+    fn write_var_type(&self, var_type: &VarType, names: &BTreeSet<&str>) -> String {
+        match var_type {
+            VarType::ClassName(name) if names.contains(name.as_str()) => match self.format {
+                DocFormat::Html => format!("<a href=\"{}\">{}</a>", self.file_name(name), name),
+                DocFormat::Markdown => format!("[{}]({})", name, self.file_name(name)),
+            },
+            var_type => var_type.to_string(),
+        }
+    }
+}
+
+/// Wraps the body into a complete HTML document.
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n\
+        <title>{}</title>\n\
+        </head>\n\
+        <body>\n\
+        {}\
+        </body>\n\
+        </html>\n",
+        title, body
+    )
+}
+
+/// Splits a doc comment at blank lines and joins the lines of each paragraph.
+fn paragraphs(doc: &str) -> Vec<String> {
+    doc.split("\n\n")
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+/// Returns the first line of a doc comment.
+fn summary(doc: &str) -> &str {
+    doc.lines().next().unwrap_or_default().trim()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
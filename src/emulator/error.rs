@@ -0,0 +1,33 @@
+//! Defines emulator errors.
+use std::error;
+use std::fmt;
+
+/// This error occurs when VM code cannot be loaded, or when the program does something
+/// the VM does not allow.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EmulatorError {
+    /// The line `line` of the VM file `file` is not a valid VM command.
+    Syntax {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    /// The program failed in the function `function`, e.g. because it called a function
+    /// that is not defined.
+    Runtime { function: String, message: String },
+}
+
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmulatorError::Syntax { file, line, message } => {
+                write!(f, "{}: Error in line {}: {}", file, line, message)
+            }
+            EmulatorError::Runtime { function, message } => {
+                write!(f, "Runtime Error in {}: {}", function, message)
+            }
+        }
+    }
+}
+
+impl error::Error for EmulatorError {}
@@ -0,0 +1,499 @@
+//! Runs VM code the way the VM emulator of the book does, so that programs can be
+//! measured without leaving the command line, see [`profiler`](crate::profiler).
+//!
+//! The memory map is the one of the Hack platform: the stack starts at 256, the static
+//! variables of all classes share the addresses 16 to 255, and the screen and the keyboard
+//! are mapped to 16384 and 24576. Nothing is displayed and no key is ever pressed.
+use std::collections::HashMap;
+
+pub mod error;
+use error::EmulatorError;
+
+/// The addresses of the registers and segments.
+const SP: usize = 0;
+const LCL: usize = 1;
+const ARG: usize = 2;
+const THIS: usize = 3;
+const THAT: usize = 4;
+const TEMP: usize = 5;
+const STATIC: usize = 16;
+const STACK: i16 = 256;
+const RAM_SIZE: usize = 32768;
+
+/// The segments of `push` and `pop`. The address of `constant`, `static`, `pointer`
+/// and `temp` is known when the VM code is loaded.
+#[derive(Clone, Copy, Debug)]
+enum Segment {
+    Constant(i16),
+    Fixed(usize),
+    Local(i16),
+    Argument(i16),
+    This(i16),
+    That(i16),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Instruction {
+    Push(Segment),
+    Pop(Segment),
+    Add,
+    Sub,
+    Neg,
+    Eq,
+    Gt,
+    Lt,
+    And,
+    Or,
+    Not,
+    Goto(usize),
+    IfGoto(usize),
+    Function { locals: u16 },
+    Call { function: usize, arguments: u16 },
+    Return,
+}
+
+/// A function that is defined or called by the VM code of a [`Program`].
+#[derive(Clone, Debug)]
+pub struct Function {
+    /// The name, e.g. `Main.main`.
+    pub name: String,
+    /// The index of the `function` instruction, or `None` if the function is only called.
+    start: Option<usize>,
+}
+
+impl Function {
+    pub fn is_defined(&self) -> bool {
+        self.start.is_some()
+    }
+}
+
+/// The VM code of all the classes of a program, loaded with [`add_file`](Program::add_file).
+/// Labels are removed and the targets of jumps and calls are resolved while loading.
+#[derive(Debug, Default)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+    functions: Vec<Function>,
+    function_indices: HashMap<String, usize>,
+    /// The address of the next static variable.
+    next_static: usize,
+}
+
+/// The labels of the function that is being loaded, and the jumps to them.
+struct Labels {
+    function: String,
+    targets: HashMap<String, usize>,
+    jumps: Vec<(usize, String, usize)>,
+}
+
+impl Program {
+    pub fn new() -> Program {
+        Program {
+            next_static: STATIC,
+            ..Program::default()
+        }
+    }
+
+    /// Returns the functions that are defined or called, in the order they were first seen.
+    /// The [`Observer`] identifies functions by their index in this list.
+    pub fn functions(&self) -> &[Function] {
+        &self.functions
+    }
+
+    /// Returns the index of the function with the given name.
+    pub fn function(&self, name: &str) -> Option<usize> {
+        self.function_indices.get(name).copied()
+    }
+
+    /// Loads the VM code of a file, e.g. `Main.vm`. The static variables of the file
+    /// follow the ones of the files loaded before.
+    pub fn add_file(&mut self, file: &str, vm_code: &str) -> Result<(), EmulatorError> {
+        let error = |line: usize, message: &str| EmulatorError::Syntax {
+            file: file.to_string(),
+            line,
+            message: message.to_string(),
+        };
+        let mut labels: Option<Labels> = None;
+        let mut statics = 0;
+        for (idx, line) in vm_code.lines().enumerate() {
+            let number = idx + 1;
+            let line = line.split("//").next().unwrap_or_default();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            if words[0] == "function" {
+                if let Some(labels) = labels.take() {
+                    self.resolve(labels, file)?;
+                }
+                let (name, locals) = match words[1..] {
+                    [name, locals] => (name, number_argument(locals).ok_or_else(|| error(number, "Expected the number of local variables"))?),
+                    _ => return Err(error(number, "Expected 'function <NAME> <LOCALS>'")),
+                };
+                let function = self.function_index(name);
+                if self.functions[function].start.is_some() {
+                    return Err(error(number, &format!("Function '{}' is already defined", name)));
+                }
+                self.functions[function].start = Some(self.instructions.len());
+                self.instructions.push(Instruction::Function { locals });
+                labels = Some(Labels {
+                    function: name.to_string(),
+                    targets: HashMap::new(),
+                    jumps: Vec::new(),
+                });
+                continue;
+            }
+            let labels = match labels.as_mut() {
+                Some(labels) => labels,
+                None => return Err(error(number, "VM commands must be inside a function")),
+            };
+            let instruction = match words[..] {
+                ["push", segment, index] => {
+                    let segment = self.segment(segment, index, file, &mut statics).map_err(|message| error(number, &message))?;
+                    Instruction::Push(segment)
+                }
+                ["pop", "constant", _] => return Err(error(number, "Cannot pop into the constant segment")),
+                ["pop", segment, index] => {
+                    let segment = self.segment(segment, index, file, &mut statics).map_err(|message| error(number, &message))?;
+                    Instruction::Pop(segment)
+                }
+                ["add"] => Instruction::Add,
+                ["sub"] => Instruction::Sub,
+                ["neg"] => Instruction::Neg,
+                ["eq"] => Instruction::Eq,
+                ["gt"] => Instruction::Gt,
+                ["lt"] => Instruction::Lt,
+                ["and"] => Instruction::And,
+                ["or"] => Instruction::Or,
+                ["not"] => Instruction::Not,
+                ["label", label] => {
+                    if labels.targets.insert(label.to_string(), self.instructions.len()).is_some() {
+                        return Err(error(number, &format!("Label '{}' is already defined", label)));
+                    }
+                    continue;
+                }
+                ["goto", label] | ["if-goto", label] => {
+                    labels.jumps.push((self.instructions.len(), label.to_string(), number));
+                    match words[0] {
+                        "goto" => Instruction::Goto(0),
+                        _ => Instruction::IfGoto(0),
+                    }
+                }
+                ["call", name, arguments] => Instruction::Call {
+                    function: self.function_index(name),
+                    arguments: number_argument(arguments).ok_or_else(|| error(number, "Expected the number of arguments"))?,
+                },
+                ["return"] => Instruction::Return,
+                _ => return Err(error(number, &format!("Unknown command '{}'", line.trim()))),
+            };
+            self.instructions.push(instruction);
+        }
+        if let Some(labels) = labels {
+            self.resolve(labels, file)?;
+        }
+        self.next_static += statics;
+        Ok(())
+    }
+
+    /// Sets the targets of the jumps of a function.
+    fn resolve(&mut self, labels: Labels, file: &str) -> Result<(), EmulatorError> {
+        for (instruction, label, line) in labels.jumps {
+            let target = match labels.targets.get(&label) {
+                Some(target) => *target,
+                None => {
+                    return Err(EmulatorError::Syntax {
+                        file: file.to_string(),
+                        line,
+                        message: format!("Unknown label '{}' in function '{}'", label, labels.function),
+                    })
+                }
+            };
+            self.instructions[instruction] = match self.instructions[instruction] {
+                Instruction::Goto(_) => Instruction::Goto(target),
+                _ => Instruction::IfGoto(target),
+            };
+        }
+        Ok(())
+    }
+
+    fn function_index(&mut self, name: &str) -> usize {
+        if let Some(idx) = self.function_indices.get(name) {
+            return *idx;
+        }
+        self.functions.push(Function {
+            name: name.to_string(),
+            start: None,
+        });
+        self.function_indices.insert(name.to_string(), self.functions.len() - 1);
+        self.functions.len() - 1
+    }
+
+    /// Parses the segment and index of `push` and `pop`. `statics` is the number of
+    /// static variables the file uses so far.
+    fn segment(&self, segment: &str, index: &str, file: &str, statics: &mut usize) -> Result<Segment, String> {
+        let index: i16 = match index.parse() {
+            Ok(index) if index >= 0 => index,
+            _ => return Err(format!("Invalid index '{}'", index)),
+        };
+        let segment = match segment {
+            "constant" => Segment::Constant(index),
+            "local" => Segment::Local(index),
+            "argument" => Segment::Argument(index),
+            "this" => Segment::This(index),
+            "that" => Segment::That(index),
+            "pointer" if index <= 1 => Segment::Fixed(THIS + index as usize),
+            "temp" if index <= 7 => Segment::Fixed(TEMP + index as usize),
+            "static" if self.next_static + (index as usize) < STACK as usize => {
+                *statics = (*statics).max(index as usize + 1);
+                Segment::Fixed(self.next_static + index as usize)
+            }
+            "pointer" | "temp" => return Err(format!("Index {} is out of range for segment '{}'", index, segment)),
+            "static" => return Err(format!("Too many static variables, '{}' does not fit below address {}", file, STACK)),
+            _ => return Err(format!("Unknown segment '{}'", segment)),
+        };
+        Ok(segment)
+    }
+}
+
+/// Parses the number of local variables or arguments.
+fn number_argument(number: &str) -> Option<u16> {
+    number.parse().ok()
+}
+
+/// Is told what the [`Emulator`] does, e.g. to count the instructions of each function.
+pub trait Observer {
+    /// Called before an instruction is executed.
+    fn instruction(&mut self) {}
+
+    /// Called when a function is called, with the index of the function in
+    /// [`Program::functions`].
+    fn call(&mut self, _function: usize) {}
+
+    /// Called when a function returns.
+    fn ret(&mut self) {}
+}
+
+/// An [`Observer`] that is told nothing.
+impl Observer for () {}
+
+/// Why the emulator stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// The program called `Sys.halt`.
+    Halted,
+    /// `Sys.init` returned.
+    Returned,
+    /// The program executed the maximum number of instructions, e.g. because it
+    /// waits for a key to be pressed.
+    Limit,
+}
+
+struct Frame {
+    function: usize,
+    return_address: usize,
+}
+
+/// Runs a [`Program`], starting with `Sys.init` like the bootstrap code of the book.
+pub struct Emulator<'a> {
+    program: &'a Program,
+    ram: Vec<i16>,
+    pc: usize,
+    frames: Vec<Frame>,
+    executed: u64,
+}
+
+impl<'a> Emulator<'a> {
+    pub fn new(program: &'a Program) -> Emulator<'a> {
+        Emulator {
+            program,
+            ram: vec![0; RAM_SIZE],
+            pc: 0,
+            frames: Vec::new(),
+            executed: 0,
+        }
+    }
+
+    /// Returns the memory, e.g. to look at the screen or the static variables.
+    pub fn ram(&self) -> &[i16] {
+        &self.ram
+    }
+
+    /// Returns the number of instructions executed so far.
+    pub fn executed(&self) -> u64 {
+        self.executed
+    }
+
+    /// Runs the program until it halts, `Sys.init` returns, or `max_instructions` instructions
+    /// have been executed in total. The program can be run further with a higher limit.
+    pub fn run(&mut self, observer: &mut dyn Observer, max_instructions: u64) -> Result<Stop, EmulatorError> {
+        if self.executed == 0 && self.frames.is_empty() {
+            let init = match self.program.function("Sys.init") {
+                Some(init) if self.program.functions[init].is_defined() => init,
+                _ => {
+                    return Err(EmulatorError::Runtime {
+                        function: "the bootstrap code".to_string(),
+                        message: "The program has no function 'Sys.init', it needs an operating system".to_string(),
+                    })
+                }
+            };
+            self.ram[SP] = STACK;
+            self.call(init, 0, observer)?;
+        }
+        let halt = self.program.function("Sys.halt");
+        while self.executed < max_instructions {
+            let instruction = match self.program.instructions.get(self.pc) {
+                Some(instruction) => *instruction,
+                None => return Err(self.error("The last function does not return")),
+            };
+            observer.instruction();
+            self.executed += 1;
+            self.pc += 1;
+            match instruction {
+                Instruction::Push(segment) => {
+                    let value = match segment {
+                        Segment::Constant(value) => value,
+                        _ => self.ram[self.address(segment)?],
+                    };
+                    self.push(value)?;
+                }
+                Instruction::Pop(segment) => {
+                    let value = self.pop()?;
+                    let address = self.address(segment)?;
+                    self.ram[address] = value;
+                }
+                Instruction::Add => self.binary(|x, y| x.wrapping_add(y))?,
+                Instruction::Sub => self.binary(|x, y| x.wrapping_sub(y))?,
+                Instruction::Eq => self.binary(|x, y| -((x == y) as i16))?,
+                Instruction::Gt => self.binary(|x, y| -((x > y) as i16))?,
+                Instruction::Lt => self.binary(|x, y| -((x < y) as i16))?,
+                Instruction::And => self.binary(|x, y| x & y)?,
+                Instruction::Or => self.binary(|x, y| x | y)?,
+                Instruction::Neg => {
+                    let value = self.pop()?;
+                    self.push(value.wrapping_neg())?;
+                }
+                Instruction::Not => {
+                    let value = self.pop()?;
+                    self.push(!value)?;
+                }
+                Instruction::Goto(target) => self.pc = target,
+                Instruction::IfGoto(target) => {
+                    if self.pop()? != 0 {
+                        self.pc = target;
+                    }
+                }
+                Instruction::Function { locals } => {
+                    for _ in 0..locals {
+                        self.push(0)?;
+                    }
+                }
+                Instruction::Call { function, arguments } => {
+                    if Some(function) == halt {
+                        return Ok(Stop::Halted);
+                    }
+                    self.call(function, arguments, observer)?;
+                }
+                Instruction::Return => {
+                    if self.ret(observer)? {
+                        return Ok(Stop::Returned);
+                    }
+                }
+            }
+        }
+        Ok(Stop::Limit)
+    }
+
+    /// Saves the frame of the caller and jumps to the function, as `call` does.
+    fn call(&mut self, function: usize, arguments: u16, observer: &mut dyn Observer) -> Result<(), EmulatorError> {
+        let start = match self.program.functions[function].start {
+            Some(start) => start,
+            None => {
+                let name = &self.program.functions[function].name;
+                return Err(self.error(&format!("Unknown function '{}'", name)));
+            }
+        };
+        // the return address is kept in `frames`, the VM code cannot see it anyway
+        self.push(0)?;
+        for register in [LCL, ARG, THIS, THAT] {
+            self.push(self.ram[register])?;
+        }
+        self.ram[ARG] = self.ram[SP].wrapping_sub(5 + arguments as i16);
+        self.ram[LCL] = self.ram[SP];
+        self.frames.push(Frame {
+            function,
+            return_address: self.pc,
+        });
+        self.pc = start;
+        observer.call(function);
+        Ok(())
+    }
+
+    /// Restores the frame of the caller, as `return` does.
+    /// Returns true if the outermost function, `Sys.init`, returned.
+    fn ret(&mut self, observer: &mut dyn Observer) -> Result<bool, EmulatorError> {
+        let frame = self.ram[LCL];
+        let value = self.pop()?;
+        let address = self.address(Segment::Argument(0))?;
+        self.ram[address] = value;
+        self.ram[SP] = self.ram[ARG].wrapping_add(1);
+        for (offset, register) in [(1, THAT), (2, THIS), (3, ARG), (4, LCL)] {
+            let address = self.checked(frame.wrapping_sub(offset))?;
+            self.ram[register] = self.ram[address];
+        }
+        observer.ret();
+        let caller = self.frames.pop().map(|frame| frame.return_address);
+        self.pc = caller.unwrap_or_default();
+        Ok(self.frames.is_empty())
+    }
+
+    fn binary(&mut self, op: fn(i16, i16) -> i16) -> Result<(), EmulatorError> {
+        let y = self.pop()?;
+        let x = self.pop()?;
+        self.push(op(x, y))
+    }
+
+    fn push(&mut self, value: i16) -> Result<(), EmulatorError> {
+        let address = self.checked(self.ram[SP])?;
+        self.ram[address] = value;
+        self.ram[SP] = self.ram[SP].wrapping_add(1);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<i16, EmulatorError> {
+        self.ram[SP] = self.ram[SP].wrapping_sub(1);
+        let address = self.checked(self.ram[SP])?;
+        Ok(self.ram[address])
+    }
+
+    /// Returns the address of a segment entry.
+    fn address(&self, segment: Segment) -> Result<usize, EmulatorError> {
+        let (base, index) = match segment {
+            Segment::Fixed(address) => return Ok(address),
+            Segment::Constant(_) => return Err(self.error("The constant segment has no address")),
+            Segment::Local(index) => (self.ram[LCL], index),
+            Segment::Argument(index) => (self.ram[ARG], index),
+            Segment::This(index) => (self.ram[THIS], index),
+            Segment::That(index) => (self.ram[THAT], index),
+        };
+        self.checked(base.wrapping_add(index))
+    }
+
+    /// Returns the address as an index into the memory, which must not be negative.
+    fn checked(&self, address: i16) -> Result<usize, EmulatorError> {
+        match usize::try_from(address) {
+            Ok(address) => Ok(address),
+            Err(_) => Err(self.error(&format!("Address {} is out of range", address))),
+        }
+    }
+
+    /// Returns an error in the function that is executed.
+    fn error(&self, message: &str) -> EmulatorError {
+        let function = match self.frames.last() {
+            Some(frame) => self.program.functions[frame.function].name.clone(),
+            None => "the bootstrap code".to_string(),
+        };
+        EmulatorError::Runtime {
+            function,
+            message: message.to_string(),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Defines formatter errors.
+use std::error;
+use std::fmt;
+
+use crate::parser::error::ParseError;
+use crate::tokenizer::error::LexError;
+
+/// This error occurs when the Jack code to be formatted is not a valid class.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    Lex(LexError),
+    Parse(ParseError),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Lex(error) => write!(f, "{}", error),
+            FormatError::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            FormatError::Lex(error) => Some(error),
+            FormatError::Parse(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Formats Jack code in a canonical style, e.g. for `jack_compiler fmt`.
+//!
+//! The formatter prints the [concrete syntax tree](crate::parser::cst) of a Jack file:
+//! - every declaration and statement starts on a new line, indented by its nesting depth,
+//! - opening braces are on the line of the declaration or statement, closing braces on their own line,
+//! - binary operators are surrounded by spaces, unary operators and parentheses are not,
+//!   and commas are followed by a space,
+//! - subroutines are separated by a blank line, other blank lines are kept (at most one),
+//! - comments are kept, either on their own line or at the end of the line of a token.
+//!
+//! Names declared together (`field int x, y;`) stay together. Only whitespace is changed,
+//! so the formatted file compiles to the same VM code.
+pub mod error;
+use error::FormatError;
+
+use crate::parser::cst::{NodeKind, SyntaxElement, SyntaxNode};
+use crate::parser::Parser;
+use crate::tokenizer::tokens::{Constant, Symbol, SyntaxToken, Token, Trivia, TriviaKind};
+use crate::tokenizer::Tokenizer;
+
+/// Formats Jack code, see the [module documentation](self).
+pub struct Formatter {
+    indent_width: usize,
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter {
+    /// Returns a formatter that indents by 4 spaces.
+    pub fn new() -> Formatter {
+        Formatter { indent_width: 4 }
+    }
+
+    /// Sets the number of spaces per level of indentation.
+    pub fn indent_width(mut self, indent_width: usize) -> Self {
+        self.indent_width = indent_width;
+        self
+    }
+
+    /// Takes in the Jack code of a class and returns it formatted.
+    pub fn format(&self, source: &str) -> Result<String, FormatError> {
+        let tokens = Tokenizer::new().tokenize_lossless(source).map_err(FormatError::Lex)?;
+        let file = Parser::new().parse_lossless(tokens).map_err(FormatError::Parse)?;
+
+        let mut printer = Printer {
+            out: String::new(),
+            indent_width: self.indent_width,
+            depth: 0,
+            line_start: true,
+            space: false,
+            item: false,
+            previous: None,
+            unary: false,
+            source_line_ended: true,
+        };
+        printer.node(&file);
+        let mut out = printer.out.trim_end().to_string();
+        out.push('\n');
+        Ok(out)
+    }
+}
+
+/// Writes the formatted code.
+struct Printer {
+    out: String,
+    indent_width: usize,
+    /// The nesting depth of the current line.
+    depth: usize,
+    /// Nothing was written on the current line yet.
+    line_start: bool,
+    /// The next token must be preceded by a space, e.g. after an inline comment.
+    space: bool,
+    /// The next token starts a declaration or statement.
+    item: bool,
+    /// The last token written.
+    previous: Option<Token>,
+    /// The last token written is a unary operator.
+    unary: bool,
+    /// The last token written is followed by a line break in the Jack file.
+    source_line_ended: bool,
+}
+
+impl Printer {
+    fn node(&mut self, node: &SyntaxNode) {
+        match node.kind {
+            NodeKind::SubroutineDec => {
+                self.blank_line();
+                self.item = true;
+            }
+            NodeKind::ClassVarDec
+            | NodeKind::VarDec
+            | NodeKind::LetStatement
+            | NodeKind::IfStatement
+            | NodeKind::WhileStatement
+            | NodeKind::DoStatement
+            | NodeKind::ReturnStatement => {
+                self.newline();
+                self.item = true;
+            }
+            _ => (),
+        }
+        for child in node.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => self.node(node),
+                SyntaxElement::Token(token) if token.text == "}" => self.closing_brace(token),
+                SyntaxElement::Token(token) => {
+                    self.token(token);
+                    if token.text == "{" {
+                        self.depth += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    //------------------------------
+    // TOKENS
+    //------------------------------
+
+    fn token(&mut self, token: &SyntaxToken) {
+        self.leading_trivia(&token.leading_trivia);
+        if let Some(wrapper) = &token.token {
+            let space = self.space_before(&wrapper.token);
+            self.write(&token.text, space);
+            self.unary = self.is_unary(&wrapper.token);
+            self.previous = Some(wrapper.token.clone());
+        }
+        self.trailing_trivia(&token.trailing_trivia);
+    }
+
+    /// The comments before a closing brace belong inside the block.
+    fn closing_brace(&mut self, token: &SyntaxToken) {
+        self.leading_trivia(&token.leading_trivia);
+        self.depth -= 1;
+        self.newline();
+        self.write(&token.text, false);
+        self.previous = token.token.as_ref().map(|wrapper| wrapper.token.clone());
+        self.unary = false;
+        self.trailing_trivia(&token.trailing_trivia);
+    }
+
+    /// Returns true if a space is written between the last token and `next` on the same line.
+    fn space_before(&self, next: &Token) -> bool {
+        let previous = match &self.previous {
+            Some(previous) => previous,
+            None => return false,
+        };
+        if self.unary {
+            return false;
+        }
+        match (previous, next) {
+            (
+                _,
+                Token::Symbol(
+                    Symbol::Comma | Symbol::Semicolon | Symbol::RightParen | Symbol::RightBracket | Symbol::Dot,
+                ),
+            ) => false,
+            (Token::Symbol(Symbol::LeftParen | Symbol::LeftBracket | Symbol::Dot), _) => false,
+            // calls and array accesses
+            (Token::Identifier(_), Token::Symbol(Symbol::LeftParen | Symbol::LeftBracket)) => false,
+            _ => true,
+        }
+    }
+
+    /// Returns true if `token` is a unary operator, i.e. `~`, or a `-` that does not
+    /// follow an operand.
+    fn is_unary(&self, token: &Token) -> bool {
+        match token {
+            Token::Symbol(Symbol::Tilde) => true,
+            Token::Symbol(Symbol::Minus) => !matches!(
+                &self.previous,
+                Some(
+                    Token::Identifier(_)
+                        | Token::Constant(Constant::IntegerConstant(_) | Constant::StringConstant(_))
+                        | Token::Symbol(Symbol::RightParen | Symbol::RightBracket)
+                )
+            ) && !matches!(&self.previous, Some(Token::Keyword(keyword)) if keyword.is_constant()),
+            _ => false,
+        }
+    }
+
+    //------------------------------
+    // TRIVIA
+    //------------------------------
+
+    /// Writes the comments before a token. A comment that is on its own line in the Jack
+    /// file stays on its own line, and a blank line before it is kept. A blank line before
+    /// the token is kept as well if the token starts a declaration or statement.
+    fn leading_trivia(&mut self, trivia: &[Trivia]) {
+        let mut line_breaks = if self.source_line_ended { 1 } else { 0 };
+        for trivia in trivia.iter() {
+            if trivia.kind == TriviaKind::Whitespace {
+                line_breaks += trivia.text.matches('\n').count();
+                continue;
+            }
+            if line_breaks >= 2 {
+                self.blank_line();
+            } else if line_breaks == 1 {
+                self.newline();
+            }
+            self.comment(trivia);
+            line_breaks = 0;
+        }
+        if self.item && line_breaks >= 2 {
+            self.blank_line();
+        } else if line_breaks >= 1 && self.space {
+            // the token was on the line after a block comment
+            self.newline();
+        }
+        self.item = false;
+    }
+
+    /// Writes the comments at the end of the line of a token.
+    fn trailing_trivia(&mut self, trivia: &[Trivia]) {
+        self.source_line_ended = false;
+        for trivia in trivia.iter() {
+            if trivia.kind == TriviaKind::Whitespace {
+                self.source_line_ended |= trivia.text.contains('\n');
+            } else {
+                self.comment(trivia);
+            }
+        }
+    }
+
+    /// Writes a comment. The lines of a block comment that start with `*` are indented
+    /// like the first line.
+    fn comment(&mut self, comment: &Trivia) {
+        let lines: Vec<&str> = comment.text.lines().map(str::trim_end).collect();
+        let align = lines[1..].iter().all(|line| line.trim_start().starts_with('*'));
+        self.write(lines[0], true);
+        for line in lines[1..].iter() {
+            self.out.push('\n');
+            if align {
+                self.indent();
+                self.out.push(' ');
+                self.out.push_str(line.trim_start());
+            } else {
+                self.out.push_str(line);
+            }
+        }
+        if comment.kind == TriviaKind::LineComment {
+            self.newline();
+        } else {
+            self.space = true;
+        }
+    }
+
+    //------------------------------
+    // OUTPUT
+    //------------------------------
+
+    fn write(&mut self, text: &str, space: bool) {
+        if self.line_start {
+            self.indent();
+        } else if space || self.space {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+        self.line_start = false;
+        self.space = false;
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth * self.indent_width {
+            self.out.push(' ');
+        }
+    }
+
+    /// Ends the current line, unless nothing was written on it.
+    fn newline(&mut self) {
+        if !self.line_start {
+            self.out.push('\n');
+            self.line_start = true;
+            self.space = false;
+        }
+    }
+
+    /// Ends the current line and adds an empty line, except at the start of the file
+    /// or of a block.
+    fn blank_line(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") && !self.out.ends_with("{\n") {
+            self.out.push('\n');
+        }
+    }
+}
+
@@ -0,0 +1,51 @@
+//! Matches file paths against glob patterns, e.g. `*Test.jack` or `src/**/*.jack`.
+
+/// A glob pattern. `*` matches any characters except `/`, `**` matches any
+/// characters including `/`, and `?` matches a single character except `/`.
+///
+/// A pattern without a `/` is matched against the file name only, so that `*Test.jack`
+/// matches `Test.jack` in every directory. Otherwise it is matched against the whole path.
+#[derive(Clone, Debug)]
+pub struct Glob {
+    pattern: Vec<char>,
+    file_name_only: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Glob {
+        Glob {
+            pattern: pattern.chars().collect(),
+            file_name_only: !pattern.contains('/'),
+        }
+    }
+
+    /// Returns true if the given path (with `/` as separator) matches the pattern.
+    pub fn matches(&self, path: &str) -> bool {
+        let path = if self.file_name_only {
+            match path.rfind('/') {
+                Some(idx) => &path[idx + 1..],
+                None => path,
+            }
+        } else {
+            path
+        };
+        let path: Vec<char> = path.chars().collect();
+        matches(&self.pattern, &path)
+    }
+}
+
+fn matches(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            // `**/` also matches no directory at all
+            matches(rest, path) || (0..path.len()).any(|i| path[i] == '/' && matches(rest, &path[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|i| matches(rest, &path[i..])),
+        ['*', rest @ ..] => (0..=path.len())
+            .take_while(|&i| i == 0 || path[i - 1] != '/')
+            .any(|i| matches(rest, &path[i..])),
+        ['?', rest @ ..] => !path.is_empty() && path[0] != '/' && matches(rest, &path[1..]),
+        [c, rest @ ..] => !path.is_empty() && path[0] == *c && matches(rest, &path[1..]),
+    }
+}
@@ -12,9 +12,4 @@ impl Line {
             number,
         }
     }
-
-    pub fn replace_content(mut self, from: &str, to: &str) -> Self {
-        self.content = self.content.replace(from, to);
-        self
-    }
 }
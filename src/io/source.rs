@@ -0,0 +1,265 @@
+//! Jack code that does not have to come from the file system.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::glob::Glob;
+use super::tar;
+
+/// Holds the name and the contents of a Jack file.
+/// The name is used to derive the class name, e.g. `Main.jack` or `src/Main.jack` for `Main`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Source {
+    pub name: String,
+    pub text: String,
+}
+
+impl Source {
+    pub fn new(name: &str, text: &str) -> Source {
+        Source {
+            name: name.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    /// Returns the name without the directories and the `.jack` ending, e.g. `Main` for `src/Main.jack`.
+    pub fn stem(&self) -> &str {
+        let name = match self.name.rfind('/') {
+            Some(idx) => &self.name[idx + 1..],
+            None => &self.name,
+        };
+        name.strip_suffix(".jack").unwrap_or(name)
+    }
+}
+
+/// Provides the Jack files of a program, e.g. from a directory, from memory or from an archive.
+pub trait FileProvider {
+    /// Returns the names of all the Jack files, in ascending order.
+    fn jack_files(&self) -> io::Result<Vec<String>>;
+
+    /// Returns the contents of the file with the given name.
+    fn read(&self, name: &str) -> io::Result<String>;
+
+    /// Returns the path of the file with the given name relative to the root of the provider.
+    /// The layout of the output directory mirrors these paths.
+    fn relative_path(&self, name: &str) -> PathBuf {
+        PathBuf::from(name)
+    }
+
+    /// Reads all the Jack files, in the order of [`jack_files`](FileProvider::jack_files).
+    fn sources(&self) -> io::Result<Vec<Source>> {
+        let mut sources = Vec::new();
+        for name in self.jack_files()? {
+            let text = self.read(&name)?;
+            sources.push(Source { name, text });
+        }
+        Ok(sources)
+    }
+}
+
+/// Provides the Jack files of directories and single Jack files on disk.
+/// The names are the paths of the files.
+pub struct DiskProvider {
+    paths: Vec<PathBuf>,
+    recursive: bool,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl DiskProvider {
+    /// Provides the Jack files of a directory, or a single Jack file.
+    pub fn new<P: AsRef<Path>>(path: P) -> DiskProvider {
+        DiskProvider::with_paths(&[path])
+    }
+
+    /// Provides the Jack files of several directories and single Jack files.
+    pub fn with_paths<P: AsRef<Path>>(paths: &[P]) -> DiskProvider {
+        DiskProvider {
+            paths: paths.iter().map(|path| path.as_ref().to_path_buf()).collect(),
+            recursive: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// If set to true, the Jack files in the subdirectories are provided as well.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Only provides the Jack files that match the given [`Glob`] pattern, or any of the
+    /// other include patterns. The patterns are matched against the relative paths,
+    /// see [`relative_path`](FileProvider::relative_path).
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(Glob::new(pattern));
+        self
+    }
+
+    /// Does not provide the Jack files that match the given [`Glob`] pattern.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(Glob::new(pattern));
+        self
+    }
+
+    fn read_dir(&self, dir: &Path, names: &mut Vec<String>) -> io::Result<()> {
+        let entries = fs::read_dir(dir).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("could not read directory '{}': {}", dir.display(), e),
+            )
+        })?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                if self.recursive {
+                    self.read_dir(&path, names)?;
+                }
+            } else if is_jack_file(&path) {
+                names.push(path.to_string_lossy().into_owned());
+            }
+        }
+        Ok(())
+    }
+
+    fn is_selected(&self, name: &str) -> bool {
+        let relative_path = self.relative_path(name);
+        let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.matches(&relative_path)))
+            && !self.exclude.iter().any(|glob| glob.matches(&relative_path))
+    }
+}
+
+impl FileProvider for DiskProvider {
+    /// Returns the paths of the Jack files, sorted so that the order does not depend
+    /// on the file system.
+    fn jack_files(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for path in self.paths.iter() {
+            if path.is_dir() {
+                self.read_dir(path, &mut names)?;
+            } else if is_jack_file(path) {
+                names.push(path.to_string_lossy().into_owned());
+            } else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("not a Jack file: '{}'", path.display()),
+                ));
+            }
+        }
+        names.retain(|name| self.is_selected(name));
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    fn read(&self, name: &str) -> io::Result<String> {
+        fs::read_to_string(name)
+    }
+
+    /// Returns the path relative to the directory it was found in,
+    /// or the file name for a single Jack file.
+    fn relative_path(&self, name: &str) -> PathBuf {
+        let path = Path::new(name);
+        for dir in self.paths.iter().filter(|dir| dir.is_dir()) {
+            if let Ok(relative_path) = path.strip_prefix(dir) {
+                return relative_path.to_path_buf();
+            }
+        }
+        PathBuf::from(path.file_name().unwrap_or(path.as_os_str()))
+    }
+}
+
+fn is_jack_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ending| ending == "jack")
+}
+
+/// Provides Jack files that are held in memory, e.g. for tests or an editor.
+#[derive(Default)]
+pub struct MemoryProvider {
+    files: BTreeMap<String, String>,
+}
+
+impl MemoryProvider {
+    pub fn new() -> MemoryProvider {
+        MemoryProvider {
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a file, or replaces the contents of the file with the same name.
+    pub fn insert(&mut self, name: &str, text: &str) {
+        self.files.insert(name.to_string(), text.to_string());
+    }
+}
+
+impl FileProvider for MemoryProvider {
+    fn jack_files(&self) -> io::Result<Vec<String>> {
+        Ok(self
+            .files
+            .keys()
+            .filter(|name| name.ends_with(".jack"))
+            .cloned()
+            .collect())
+    }
+
+    fn read(&self, name: &str) -> io::Result<String> {
+        match self.files.get(name) {
+            Some(text) => Ok(text.clone()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no such file: '{}'", name),
+            )),
+        }
+    }
+}
+
+/// Provides the Jack files of a tar archive, e.g. a submission that was uploaded to a
+/// web playground. The names are the paths inside the archive, e.g. `Square/Main.jack`.
+/// The archive must not be compressed, and zip archives are not supported.
+pub struct TarProvider {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl TarProvider {
+    /// Reads the tar archive at the given path.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<TarProvider> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("could not read archive '{}': {}", path.display(), e))
+        })?;
+        TarProvider::from_bytes(&bytes)
+    }
+
+    /// Reads a tar archive that is held in memory.
+    /// Returns an error of kind [`InvalidData`](io::ErrorKind::InvalidData) if it is not a tar archive.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<TarProvider> {
+        Ok(TarProvider {
+            files: tar::read_archive(bytes)?.into_iter().collect(),
+        })
+    }
+}
+
+impl FileProvider for TarProvider {
+    fn jack_files(&self) -> io::Result<Vec<String>> {
+        Ok(self
+            .files
+            .keys()
+            .filter(|name| name.ends_with(".jack"))
+            .cloned()
+            .collect())
+    }
+
+    fn read(&self, name: &str) -> io::Result<String> {
+        match self.files.get(name) {
+            Some(bytes) => String::from_utf8(bytes.clone()).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("'{}' is not UTF-8", name))
+            }),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no such file: '{}'", name),
+            )),
+        }
+    }
+}
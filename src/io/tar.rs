@@ -0,0 +1,101 @@
+//! Reads the files of an uncompressed tar archive, in the ustar format that `tar`
+//! writes by default, including the long names of GNU tar and pax.
+use std::io;
+
+const BLOCK: usize = 512;
+
+/// Returns the path and the contents of every regular file in the archive, in the
+/// order of the archive. Directories, links and other entries are left out.
+pub(crate) fn read_archive(bytes: &[u8]) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut offset = 0;
+    // the name of the next entry, given by a GNU long name or a pax header before it
+    let mut long_name: Option<String> = None;
+    while offset + BLOCK <= bytes.len() {
+        let header = &bytes[offset..offset + BLOCK];
+        if header.iter().all(|&byte| byte == 0) {
+            // the archive ends with two empty blocks
+            break;
+        }
+        if checksum(header) != octal(&header[148..156])? {
+            return Err(invalid(&format!("invalid header checksum at byte {}", offset)));
+        }
+        let size = octal(&header[124..136])? as usize;
+        let start = offset + BLOCK;
+        let end = start + size;
+        if end > bytes.len() {
+            return Err(invalid("unexpected end of the archive"));
+        }
+        let data = &bytes[start..end];
+        match header[156] {
+            b'0' | b'\0' | b'7' => {
+                let name = match long_name.take() {
+                    Some(name) => name,
+                    None => header_name(header),
+                };
+                files.push((normalize(&name), data.to_vec()));
+            }
+            b'L' => long_name = Some(text(data)),
+            b'x' => long_name = pax_path(data).or(long_name),
+            _ => long_name = None,
+        }
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+    }
+    Ok(files)
+}
+
+/// Returns the name of a ustar header, which is split into a prefix and a name.
+fn header_name(header: &[u8]) -> String {
+    let name = text(&header[0..100]);
+    let prefix = match &header[257..262] {
+        b"ustar" => text(&header[345..500]),
+        _ => String::new(),
+    };
+    match prefix.as_str() {
+        "" => name,
+        _ => format!("{}/{}", prefix, name),
+    }
+}
+
+/// Returns the `path` record of a pax header, e.g. `30 path=src/a/long/name/Main.jack\n`.
+fn pax_path(data: &[u8]) -> Option<String> {
+    let records = String::from_utf8_lossy(data);
+    records.lines().find_map(|record| {
+        let (_, record) = record.split_once(' ')?;
+        record.strip_prefix("path=").map(String::from)
+    })
+}
+
+/// Removes a leading `./`, as in the archives written by `tar -cf x.tar .`
+fn normalize(name: &str) -> String {
+    name.trim_start_matches("./").to_string()
+}
+
+/// Reads a field that is terminated by a NUL byte or fills the field.
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|&byte| byte == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Reads an octal number that is padded with spaces or NUL bytes.
+fn octal(field: &[u8]) -> io::Result<u64> {
+    let digits = text(field);
+    let digits = digits.trim_matches(' ');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 8).map_err(|_| invalid(&format!("invalid number '{}' in a header", digits)))
+}
+
+/// The sum of the bytes of the header, counting the checksum field as spaces.
+fn checksum(header: &[u8]) -> u64 {
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, &byte)| if (148..156).contains(&i) { b' ' as u64 } else { byte as u64 })
+        .sum()
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("not a valid tar archive: {}", message))
+}
@@ -0,0 +1,42 @@
+//! Detects changes to files by polling their modification times, which works
+//! the same on every platform.
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::SystemTime;
+
+/// Remembers the modification times of a set of files.
+#[derive(Debug, Default)]
+pub struct Watcher {
+    modified: BTreeMap<String, Option<SystemTime>>,
+}
+
+impl Watcher {
+    pub fn new() -> Watcher {
+        Watcher::default()
+    }
+
+    /// Takes in the files to watch and returns the ones that were added, modified or
+    /// removed since the previous call, in sorted order. On the first call, every file
+    /// is returned. A file that cannot be read has no modification time.
+    pub fn changes(&mut self, files: &[String]) -> Vec<String> {
+        let mut modified = BTreeMap::new();
+        for file in files.iter() {
+            let time = fs::metadata(file).and_then(|metadata| metadata.modified()).ok();
+            modified.insert(file.clone(), time);
+        }
+        let mut changes: Vec<String> = modified
+            .iter()
+            .filter(|(file, time)| self.modified.get(*file) != Some(*time))
+            .map(|(file, _)| file.clone())
+            .collect();
+        changes.extend(
+            self.modified
+                .keys()
+                .filter(|file| !modified.contains_key(*file))
+                .cloned(),
+        );
+        changes.sort();
+        self.modified = modified;
+        changes
+    }
+}
@@ -1,6 +1,14 @@
 //! A compiler front-end for the Jack programming language,
 //! as described in the book [The Elements of Computing Systems](https://www.nand2tetris.org/book).
+pub mod compiler;
+pub mod doc_writer;
+pub mod emulator;
+pub mod formatter;
 pub mod io;
+pub mod manifest;
+pub mod os;
 pub mod tokenizer;
 pub mod parser;
-pub mod vm_writer;
\ No newline at end of file
+pub mod profiler;
+pub mod vm_writer;
+pub mod xml_writer;
\ No newline at end of file
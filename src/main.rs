@@ -1,40 +1,891 @@
 use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
 
-use jack_compiler::io;
-use jack_compiler::tokenizer::Tokenizer;
+use jack_compiler::compiler::options::{CompileOptions, Emit, Extension, OptLevel};
+use jack_compiler::compiler::output::{ArtifactKind, CompileOutput};
+use jack_compiler::compiler::Compiler;
+use jack_compiler::doc_writer::{DocFormat, DocWriter};
+use jack_compiler::emulator::{Emulator, Program, Stop};
+use jack_compiler::formatter::Formatter;
+use jack_compiler::io::source::{DiskProvider, FileProvider, Source};
+use jack_compiler::io::watch::Watcher;
+use jack_compiler::manifest::{Manifest, Os, MANIFEST_FILE};
+use jack_compiler::parser::parse_tree::ParseTree;
 use jack_compiler::parser::Parser;
-use jack_compiler::vm_writer::VMWriter;
+use jack_compiler::profiler::Profiler;
+use jack_compiler::tokenizer::Tokenizer;
+
+/// The name of the cache file of `watch`, if no other one is given.
+const WATCH_CACHE: &str = ".jack-cache";
+
+/// The output directory of `doc`, if no other one is given.
+const DOC_DIR: &str = "doc";
+
+/// The number of VM instructions after which `profile` stops a program, e.g. one
+/// that waits for a key to be pressed.
+const MAX_INSTRUCTIONS: u64 = 10_000_000;
+
+#[derive(PartialEq, Eq)]
+enum Subcommand {
+    Build,
+    Watch,
+    Fmt,
+    Doc,
+    Profile,
+}
+
+impl Subcommand {
+    fn name(&self) -> &'static str {
+        match self {
+            Subcommand::Build => "build",
+            Subcommand::Watch => "watch",
+            Subcommand::Fmt => "fmt",
+            Subcommand::Doc => "doc",
+            Subcommand::Profile => "profile",
+        }
+    }
+}
+
+struct Args {
+    paths: Vec<String>, // ["-"] for standard input
+    subcommand: Subcommand,
+    check: bool,
+    doc_format: DocFormat,
+    interval: Duration,
+    exec: Option<String>, // run by the shell
+    os: Option<Os>,
+    folded: Option<PathBuf>,
+    max_instructions: u64,
+    manifest: Option<PathBuf>,
+    stdout: bool,
+    // the options below are added to the options of the manifest
+    emit: Option<Emit>,
+    out_dir: Option<PathBuf>,
+    recursive: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    debug_info: bool,
+    annotate: bool,
+    allow_file_name_mismatch: bool,
+    warnings: bool,
+    optimization: Option<OptLevel>,
+    extensions: Vec<Extension>,
+    jobs: Option<usize>,
+    cache: Option<PathBuf>,
+}
+
+impl Args {
+    /// Applies the command line options to the given default options.
+    fn apply(&self, options: &mut CompileOptions) {
+        if let Some(emit) = self.emit {
+            options.emit = emit;
+        }
+        if let Some(out_dir) = &self.out_dir {
+            options.out_dir = Some(out_dir.clone());
+        }
+        options.debug_info |= self.debug_info;
+        options.annotate |= self.annotate;
+        options.allow_file_name_mismatch |= self.allow_file_name_mismatch;
+        options.warnings |= self.warnings;
+        if let Some(optimization) = self.optimization {
+            options.optimization = optimization;
+        }
+        options.extensions.extend(self.extensions.iter().copied());
+        if let Some(jobs) = self.jobs {
+            options.jobs = jobs;
+        }
+        if let Some(cache) = &self.cache {
+            options.cache = Some(cache.clone());
+        }
+        if self.subcommand == Subcommand::Watch && options.cache.is_none() {
+            options.cache = self.watch_cache(options.out_dir.as_deref());
+        }
+    }
 
-fn parse_args() -> String {
-    let args: Vec<String> = env::args().skip(1).collect();
-    if args.len() != 1 {
-        eprintln!(
-            "Error: wrong number of arguments: expected 1, got {}",
-            args.len()
-        );
-        println!("Usage: jack_compiler <INPUT_FILE>.jack OR jack_compiler <INPUT_DIR>");
+    /// Returns the cache file of `watch`: `.jack-cache` in the output directory, or else in
+    /// the directory of the manifest or of the first input. Returns `None` if the file
+    /// cannot be written there, e.g. in a read-only checkout, so that nothing is cached.
+    fn watch_cache(&self, out_dir: Option<&Path>) -> Option<PathBuf> {
+        let dir = match (out_dir, &self.manifest, self.paths.first()) {
+            (Some(out_dir), _, _) => out_dir.to_path_buf(),
+            (None, Some(manifest), _) => manifest.parent().unwrap_or(Path::new("")).to_path_buf(),
+            (None, None, Some(path)) if Path::new(path).is_dir() => PathBuf::from(path),
+            (None, None, Some(path)) => Path::new(path).parent().unwrap_or(Path::new("")).to_path_buf(),
+            (None, None, None) => PathBuf::new(),
+        };
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(&dir).ok()?;
+        }
+        let path = dir.join(WATCH_CACHE);
+        fs::OpenOptions::new().append(true).create(true).open(&path).ok()?;
+        Some(path)
+    }
+}
+
+fn print_usage() {
+    println!("Usage: jack_compiler [OPTIONS] <INPUT>...");
+    println!("       jack_compiler [OPTIONS] -    (reads a single class from standard input)");
+    println!("       jack_compiler [OPTIONS]      (builds the program described by ./{})", MANIFEST_FILE);
+    println!("       jack_compiler watch [OPTIONS] [<INPUT>...]");
+    println!("       jack_compiler fmt [--check] [<INPUT>...]");
+    println!("       jack_compiler doc [--format <FORMAT>] [-o <DIR>] [<INPUT>...]");
+    println!("       jack_compiler profile [--os <OS>] [--folded <FILE>] [<INPUT>...]");
+    println!();
+    println!("Each input is a Jack file (<NAME>.jack) or a directory of Jack files.");
+    println!("'watch' compiles the inputs again whenever a Jack file is added, modified or removed,");
+    println!("and only compiles the classes that changed (the cache defaults to {} in the output", WATCH_CACHE);
+    println!("directory, or else next to the manifest or the first input, if it can be written there).");
+    println!("'fmt' formats the Jack files in place, or standard input ('-') to standard output.");
+    println!("'doc' writes an API reference from the /** ... */ comments of the classes");
+    println!("to ./{} (or the directory given with -o).", DOC_DIR);
+    println!("'profile' runs the program in a VM emulator and prints the number of VM instructions");
+    println!("executed in each subroutine, including the ones of the operating system.");
+    println!("The subcommand must come first. To compile an input named like a subcommand,");
+    println!("write it as a path, e.g. './doc', or after '--', which ends the options.");
+    println!("Of the options below, 'fmt' only takes --manifest, -r, --include and --exclude,");
+    println!("'doc' also takes -o and --allow-file-name-mismatch, and 'profile' takes all of them");
+    println!("except -o, --stdout, --emit, --debug-info, --annotate and --cache.");
+    println!();
+    println!("Options:");
+    println!("  --manifest <FILE>");
+    println!("                  build the program described by the manifest FILE");
+    println!("  -r, --recursive also compile the Jack files in the subdirectories of each directory");
+    println!("  --include <GLOB>");
+    println!("                  only compile the Jack files that match GLOB, e.g. 'src/**/*.jack'");
+    println!("  --exclude <GLOB>");
+    println!("                  do not compile the Jack files that match GLOB, e.g. '*Test.jack'");
+    println!("  -o, --out-dir <DIR>");
+    println!("                  write the output files to DIR, in the same subdirectories as the inputs");
+    println!("  --stdout        write the output to standard output instead of files");
+    println!("  -j, --jobs <N>  compile N classes at the same time (default: one per CPU core)");
+    println!("  --cache <FILE>  only compile the classes that changed since the last build,");
+    println!("                  which is remembered in FILE");
+    println!("  --emit <KINDS>  comma-separated outputs to write for each file (default: vm):");
+    println!("                    tokens  <NAME>T.xml, the tokens as XML");
+    println!("                    xml     <NAME>.xml, the parse tree as XML");
+    println!("                    vm      <NAME>.vm, the VM code");
+    println!("  --debug-info    write a <NAME>.vmdbg file next to each <NAME>.vm file");
+    println!("  --annotate      interleave the VM code with comments showing the Jack source");
+    println!("  --allow-file-name-mismatch");
+    println!("                  do not require the class name to match the file name");
+    println!("  --warnings      report code that compiles but is probably a mistake,");
+    println!("                  e.g. local variables that are never used");
+    println!("  -O, --opt-level <LEVEL>");
+    println!("                  0: the VM code of the reference compiler (default)");
+    println!("                  1: also compute expressions of constants, e.g. '60 * 60', when compiling");
+    println!("  --extension <NAME>");
+    println!("                  allow an extension of the Jack language, can be given more than once:");
+    println!("                    precedence  '*' and '/' bind more strongly than '+' and '-', which bind");
+    println!("                                more strongly than '<', '>' and '=', then '&', then '|'");
+    println!();
+    println!("Options of 'watch':");
+    println!("  --interval <MS> check the Jack files for changes every MS milliseconds (default: 500)");
+    println!("  --exec <COMMAND>");
+    println!("                  run COMMAND with the shell after each successful build, e.g. an emulator");
+    println!();
+    println!("Options of 'fmt':");
+    println!("  --check         do not change any file, list the files that are not formatted");
+    println!();
+    println!("Options of 'doc':");
+    println!("  --format <FORMAT>");
+    println!("                  write the pages as html (default) or markdown");
+    println!();
+    println!("Options of 'profile':");
+    println!("  --os <OS>       link the program with the operating system OS: 'bundled', or a directory");
+    println!("                  of .vm files (default: the 'os' of the manifest, or 'bundled')");
+    println!("  --folded <FILE> write the folded stacks of the calls to FILE, for flame graph tools");
+    println!("  --max-instructions <N>");
+    println!("                  stop the program after N VM instructions (default: {})", MAX_INSTRUCTIONS);
+}
+
+/// Parses a comma-separated list of outputs, e.g. `tokens,vm`.
+fn parse_emit(emit: &mut Emit, kinds: &str) {
+    for kind in kinds.split(',') {
+        match kind {
+            "tokens" => emit.tokens = true,
+            "xml" => emit.xml = true,
+            "vm" => emit.vm = true,
+            _ => {
+                eprintln!(
+                    "Error: unknown output for --emit: '{}' (expected tokens, xml or vm)",
+                    kind
+                );
+                print_usage();
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Parses the number of jobs, e.g. `-j 4`.
+fn parse_jobs(jobs: &str) -> usize {
+    match jobs.parse() {
+        Ok(jobs) if jobs > 0 => jobs,
+        _ => {
+            eprintln!("Error: invalid number of jobs: '{}' (expected a number greater than 0)", jobs);
+            print_usage();
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parses the optimization level, e.g. `-O 1`.
+fn parse_opt_level(level: &str) -> OptLevel {
+    match OptLevel::get(level) {
+        Some(level) => level,
+        None => {
+            eprintln!("Error: invalid optimization level: '{}' (expected 0 or 1)", level);
+            print_usage();
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parses the name of a language extension, e.g. `--extension precedence`.
+fn parse_extension(name: &str) -> Extension {
+    match Extension::get(name) {
+        Some(extension) => extension,
+        None => {
+            eprintln!("Error: unknown extension: '{}' (expected precedence)", name);
+            print_usage();
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Exits with an error message if an option is only allowed in the given subcommand.
+fn only_in(option: &str, subcommand: &Subcommand, allowed: Subcommand) {
+    if *subcommand != allowed {
+        eprintln!("Error: option '{}' can only be used with '{}'", option, allowed.name());
+        print_usage();
         std::process::exit(1);
     }
-    args[0].clone()
 }
 
-fn main() {
-    let path = parse_args();
-    let file_paths = io::get_file_paths(&path);
+/// Exits with an error message if an option cannot be used in the given subcommand.
+fn not_in(option: &str, subcommand: &Subcommand, denied: &[Subcommand]) {
+    if denied.contains(subcommand) {
+        eprintln!("Error: option '{}' cannot be used with '{}'", option, subcommand.name());
+        print_usage();
+        std::process::exit(1);
+    }
+}
 
-    let tokenizer = Tokenizer::new();
-    let parser = Parser::new();
-    let vm_writer = VMWriter::new();
+/// Returns the value of an option that takes one, e.g. `--emit vm`.
+fn option_value(args: &mut impl Iterator<Item = String>, option: &str) -> String {
+    match args.next() {
+        Some(value) => value,
+        None => {
+            eprintln!("Error: missing value for option: '{}'", option);
+            print_usage();
+            std::process::exit(1);
+        }
+    }
+}
+
+fn parse_args() -> Args {
+    let mut paths = Vec::new();
+    let mut manifest = None;
+    let mut out_dir = None;
+    let mut debug_info = false;
+    let mut annotate = false;
+    let mut allow_file_name_mismatch = false;
+    let mut warnings = false;
+    let mut optimization = None;
+    let mut extensions = Vec::new();
+    let mut emit = Emit {
+        tokens: false,
+        xml: false,
+        vm: false,
+    };
+    let mut stdout = false;
+    let mut recursive = false;
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+    let mut jobs = None;
+    let mut cache = None;
+    let mut interval = Duration::from_millis(500);
+    let mut exec = None;
+    let mut os = None;
+    let mut folded = None;
+    let mut max_instructions = MAX_INSTRUCTIONS;
+    let mut args = env::args().skip(1).peekable();
+    let mut check = false;
+    let mut doc_format = DocFormat::Html;
+    // the subcommand must be the first argument, so `jack_compiler -- doc` or
+    // `jack_compiler ./doc` compiles a directory named `doc`
+    let subcommand = match args
+        .next_if(|arg| arg == "watch" || arg == "fmt" || arg == "doc" || arg == "profile")
+        .as_deref()
+    {
+        Some("watch") => Subcommand::Watch,
+        Some("fmt") => Subcommand::Fmt,
+        Some("doc") => Subcommand::Doc,
+        Some(_) => Subcommand::Profile,
+        None => Subcommand::Build,
+    };
+    let mut only_inputs = false;
+    while let Some(arg) = args.next() {
+        if only_inputs {
+            paths.push(arg);
+            continue;
+        }
+        let name = arg.split('=').next().unwrap_or_default();
+        match name {
+            "--emit" | "--stdout" | "--debug-info" | "--annotate" | "--cache" => {
+                not_in(name, &subcommand, &[Subcommand::Fmt, Subcommand::Doc, Subcommand::Profile]);
+            }
+            "--warnings" | "--jobs" | "--opt-level" | "--extension" => {
+                not_in(name, &subcommand, &[Subcommand::Fmt, Subcommand::Doc]);
+            }
+            _ if arg.starts_with("-j") => not_in("-j", &subcommand, &[Subcommand::Fmt, Subcommand::Doc]),
+            _ if arg.starts_with("-O") => not_in("-O", &subcommand, &[Subcommand::Fmt, Subcommand::Doc]),
+            "-o" | "--out-dir" => not_in(name, &subcommand, &[Subcommand::Fmt, Subcommand::Profile]),
+            "--allow-file-name-mismatch" => not_in(name, &subcommand, &[Subcommand::Fmt]),
+            _ => (),
+        }
+        match arg.as_str() {
+            "--" => only_inputs = true,
+            "--emit" => parse_emit(&mut emit, &option_value(&mut args, &arg)),
+            _ if arg.starts_with("--emit=") => parse_emit(&mut emit, &arg["--emit=".len()..]),
+            "-o" | "--out-dir" => out_dir = Some(PathBuf::from(option_value(&mut args, &arg))),
+            _ if arg.starts_with("--out-dir=") => out_dir = Some(PathBuf::from(&arg["--out-dir=".len()..])),
+            "--manifest" => manifest = Some(PathBuf::from(option_value(&mut args, &arg))),
+            "--stdout" => stdout = true,
+            "-r" | "--recursive" => recursive = true,
+            "--include" => include.push(option_value(&mut args, &arg)),
+            "--exclude" => exclude.push(option_value(&mut args, &arg)),
+            "--debug-info" => debug_info = true,
+            "--annotate" => annotate = true,
+            "--allow-file-name-mismatch" => allow_file_name_mismatch = true,
+            "--warnings" => warnings = true,
+            "--interval" => {
+                only_in(&arg, &subcommand, Subcommand::Watch);
+                let value = option_value(&mut args, &arg);
+                match value.parse() {
+                    Ok(ms) if ms > 0 => interval = Duration::from_millis(ms),
+                    _ => {
+                        eprintln!("Error: invalid interval: '{}' (expected milliseconds greater than 0)", value);
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--exec" => {
+                only_in(&arg, &subcommand, Subcommand::Watch);
+                let command = option_value(&mut args, &arg);
+                if command.trim().is_empty() {
+                    eprintln!("Error: missing command for '--exec'");
+                    print_usage();
+                    std::process::exit(1);
+                }
+                exec = Some(command);
+            }
+            "--os" => {
+                only_in(&arg, &subcommand, Subcommand::Profile);
+                os = Some(match option_value(&mut args, &arg).as_str() {
+                    "bundled" => Os::Bundled,
+                    dir => Os::Dir(PathBuf::from(dir)),
+                });
+            }
+            "--folded" => {
+                only_in(&arg, &subcommand, Subcommand::Profile);
+                folded = Some(PathBuf::from(option_value(&mut args, &arg)));
+            }
+            "--max-instructions" => {
+                only_in(&arg, &subcommand, Subcommand::Profile);
+                let value = option_value(&mut args, &arg);
+                match value.parse() {
+                    Ok(n) if n > 0 => max_instructions = n,
+                    _ => {
+                        eprintln!("Error: invalid number of instructions: '{}' (expected a number greater than 0)", value);
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--check" => {
+                only_in(&arg, &subcommand, Subcommand::Fmt);
+                check = true;
+            }
+            "--format" => {
+                only_in(&arg, &subcommand, Subcommand::Doc);
+                let value = option_value(&mut args, &arg);
+                match DocFormat::get(&value) {
+                    Some(format) => doc_format = format,
+                    None => {
+                        eprintln!("Error: unknown format for --format: '{}' (expected html or markdown)", value);
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--cache" => cache = Some(PathBuf::from(option_value(&mut args, &arg))),
+            _ if arg.starts_with("--cache=") => cache = Some(PathBuf::from(&arg["--cache=".len()..])),
+            "-O" | "--opt-level" => optimization = Some(parse_opt_level(&option_value(&mut args, &arg))),
+            _ if arg.starts_with("--opt-level=") => {
+                optimization = Some(parse_opt_level(&arg["--opt-level=".len()..]));
+            }
+            _ if arg.starts_with("-O") => optimization = Some(parse_opt_level(&arg["-O".len()..])),
+            "--extension" => extensions.push(parse_extension(&option_value(&mut args, &arg))),
+            _ if arg.starts_with("--extension=") => extensions.push(parse_extension(&arg["--extension=".len()..])),
+            "-j" | "--jobs" => jobs = Some(parse_jobs(&option_value(&mut args, &arg))),
+            _ if arg.starts_with("--jobs=") => jobs = Some(parse_jobs(&arg["--jobs=".len()..])),
+            _ if arg.starts_with("-j") => jobs = Some(parse_jobs(&arg["-j".len()..])),
+            _ if arg.starts_with('-') && arg != "-" => {
+                eprintln!("Error: unknown option: '{}'", arg);
+                print_usage();
+                std::process::exit(1);
+            }
+            _ => paths.push(arg),
+        }
+    }
+    if paths.is_empty() && manifest.is_none() {
+        if !Path::new(MANIFEST_FILE).is_file() {
+            eprintln!("Error: no input given, and there is no {} in the current directory", MANIFEST_FILE);
+            print_usage();
+            std::process::exit(1);
+        }
+        manifest = Some(PathBuf::from(MANIFEST_FILE));
+    }
+    if !paths.is_empty() && manifest.is_some() {
+        eprintln!("Error: inputs cannot be combined with --manifest");
+        print_usage();
+        std::process::exit(1);
+    }
+    if subcommand == Subcommand::Doc && paths.iter().any(|path| path == "-") {
+        eprintln!("Error: '-' (standard input) cannot be documented");
+        print_usage();
+        std::process::exit(1);
+    }
+    if subcommand == Subcommand::Watch && paths.iter().any(|path| path == "-") {
+        eprintln!("Error: '-' (standard input) cannot be watched");
+        print_usage();
+        std::process::exit(1);
+    }
+    if subcommand == Subcommand::Profile && paths.iter().any(|path| path == "-") {
+        eprintln!("Error: '-' (standard input) cannot be profiled");
+        print_usage();
+        std::process::exit(1);
+    }
+    if paths.len() > 1 && paths.iter().any(|path| path == "-") {
+        eprintln!("Error: '-' (standard input) cannot be combined with other inputs");
+        print_usage();
+        std::process::exit(1);
+    }
+    Args {
+        paths,
+        subcommand,
+        check,
+        doc_format,
+        interval,
+        exec,
+        os,
+        folded,
+        max_instructions,
+        manifest,
+        stdout,
+        emit: if emit.tokens || emit.xml || emit.vm {
+            Some(emit)
+        } else {
+            None
+        },
+        out_dir,
+        recursive,
+        include,
+        exclude,
+        debug_info,
+        annotate,
+        allow_file_name_mismatch,
+        warnings,
+        optimization,
+        extensions,
+        jobs,
+        cache,
+    }
+}
+
+/// Compiles the single class read from standard input.
+/// The class name does not have to match a file name.
+fn compile_stdin(mut options: CompileOptions) -> Result<CompileOutput, String> {
+    let mut text = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut text) {
+        return Err(format!("Error: failed to read from standard input: {}", e));
+    }
+    options.allow_file_name_mismatch = true;
+    let compiler = Compiler::with_options(options);
+    let class = compiler
+        .compile(&Source::new("<stdin>", &text))
+        .map_err(|e| e.to_string())?;
+    Ok(CompileOutput {
+        classes: vec![class],
+        ..CompileOutput::default()
+    })
+}
+
+/// Reads the manifest at the given path and adds the command line options to it.
+fn read_manifest(path: &Path, args: &Args) -> Result<Manifest, String> {
+    let mut manifest = Manifest::read(path).map_err(|e| e.to_string())?;
+    args.apply(&mut manifest.options);
+    manifest.recursive |= args.recursive;
+    manifest.include.extend(args.include.iter().cloned());
+    manifest.exclude.extend(args.exclude.iter().cloned());
+    Ok(manifest)
+}
+
+/// Returns the Jack files given on the command line.
+fn input_files(args: &Args) -> DiskProvider {
+    let mut files = DiskProvider::with_paths(&args.paths).recursive(args.recursive);
+    for pattern in args.include.iter() {
+        files = files.include(pattern);
+    }
+    for pattern in args.exclude.iter() {
+        files = files.exclude(pattern);
+    }
+    files
+}
 
-    for file_path in file_paths.iter() {
-        let filepath_wo_ending = match file_path.rfind(".jack") {
-            Some(idx) => &file_path[..idx],
-            None => &file_path,
+/// Builds the program described by the manifest at the given path, and links it
+/// with the operating system.
+fn compile_manifest(path: &Path, args: &Args) -> Result<CompileOutput, String> {
+    let manifest = read_manifest(path, args)?;
+    let compiler = Compiler::with_options(manifest.options.clone());
+    let mut output = compiler
+        .compile_program(&manifest.sources(), &manifest.libraries(), &manifest.entry)
+        .map_err(|e| e.to_string())?;
+    // the operating system is put next to the VM code of the entry class
+    let vm_dir = output
+        .class(&manifest.entry)
+        .and_then(|class| class.artifact(ArtifactKind::Vm))
+        .map(|vm| vm.path.parent().unwrap_or(Path::new("")).to_path_buf());
+    if let Some(vm_dir) = vm_dir {
+        manifest.os.link(&mut output, &vm_dir).map_err(|e| e.to_string())?;
+    }
+    Ok(output)
+}
+
+/// Compiles the inputs, or the program described by the manifest.
+fn compile(args: &Args) -> Result<CompileOutput, String> {
+    if let Some(manifest) = &args.manifest {
+        compile_manifest(manifest, args)
+    } else if args.paths == ["-"] {
+        let mut options = CompileOptions::default();
+        args.apply(&mut options);
+        compile_stdin(options)
+    } else {
+        let mut options = CompileOptions::default();
+        args.apply(&mut options);
+        let compiler = Compiler::with_options(options);
+        compiler.compile_files(&input_files(args)).map_err(|e| e.to_string())
+    }
+}
+
+/// Writes the output to its files, or to standard output.
+fn write_output(output: &CompileOutput, args: &Args) -> Result<(), String> {
+    let result = if args.stdout {
+        output.print(&mut std::io::stdout().lock())
+    } else {
+        output.write()
+    };
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        Err(e) => Err(format!("Error: failed to write the output: {}", e)),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Prints the warnings of every class.
+fn print_diagnostics(output: &CompileOutput) {
+    for class in output.classes.iter() {
+        for diagnostic in class.diagnostics.iter() {
+            eprintln!("{}: {}", class.source, diagnostic);
+        }
+    }
+}
+
+/// Returns the files `watch` checks for changes: the Jack files, and the manifest.
+fn watched_files(args: &Args) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Some(path) = &args.manifest {
+        files.push(path.to_string_lossy().into_owned());
+        if let Ok(manifest) = read_manifest(path, args) {
+            files.extend(manifest.sources().jack_files().unwrap_or_default());
+            files.extend(manifest.libraries().jack_files().unwrap_or_default());
+        }
+    } else {
+        files.extend(input_files(args).jack_files().unwrap_or_default());
+    }
+    files
+}
+
+/// Compiles the inputs again whenever a watched file changes, until the process is stopped.
+fn watch(args: &Args) -> ! {
+    let mut watcher = Watcher::new();
+    let mut first = true;
+    loop {
+        let changes = watcher.changes(&watched_files(args));
+        if first || !changes.is_empty() {
+            if !first {
+                eprintln!();
+                eprintln!("Changed: {}", changes.join(", "));
+            }
+            first = false;
+            match compile(args).and_then(|output| write_output(&output, args).map(|_| output)) {
+                Ok(output) => {
+                    print_diagnostics(&output);
+                    eprintln!("{}", output.summary());
+                    if let Some(exec) = &args.exec {
+                        match shell(exec).status() {
+                            Ok(status) if !status.success() => eprintln!("'{}' failed: {}", exec, status),
+                            Ok(_) => (),
+                            Err(e) => eprintln!("Error: failed to run '{}': {}", exec, e),
+                        }
+                    }
+                }
+                Err(e) => eprintln!("{}", e),
+            }
+            eprintln!("Watching for changes (press Ctrl-C to stop)");
+        }
+        thread::sleep(args.interval);
+    }
+}
+
+/// Returns a command that runs the command line through the shell, so that
+/// arguments can be quoted.
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+/// Formats the inputs, or the source directories of the manifest, in place.
+/// Returns false if a file could not be formatted, or if `--check` finds a file
+/// that is not formatted.
+fn format(args: &Args) -> bool {
+    let formatter = Formatter::new();
+    if args.paths == ["-"] {
+        let mut text = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut text) {
+            eprintln!("Error: failed to read from standard input: {}", e);
+            return false;
+        }
+        return match formatter.format(&text) {
+            Ok(formatted) if args.check => formatted == text,
+            Ok(formatted) => {
+                print!("{}", formatted);
+                true
+            }
+            Err(e) => {
+                eprintln!("<stdin>: {}", e);
+                false
+            }
         };
-        let lines = io::read_file(file_path);
-        let tokens = tokenizer.tokenize(lines);
-        let parse_tree = parser.parse(tokens, filepath_wo_ending);
-        let vm_code = vm_writer.write(&parse_tree);
-        io::write_file(&format!("{}.vm", filepath_wo_ending), &vm_code);
     }
+    let files = match &args.manifest {
+        Some(path) => read_manifest(path, args).map(|manifest| manifest.sources()),
+        None => Ok(input_files(args)),
+    };
+    let files = match files.and_then(|files| files.jack_files().map_err(|e| format!("Error: {}", e))) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let mut ok = true;
+    for file in files {
+        let text = match std::fs::read_to_string(&file) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                ok = false;
+                continue;
+            }
+        };
+        match formatter.format(&text) {
+            Ok(formatted) if formatted == text => (),
+            Ok(_) if args.check => {
+                println!("{}", file);
+                ok = false;
+            }
+            Ok(formatted) => {
+                if let Err(e) = std::fs::write(&file, formatted) {
+                    eprintln!("{}: {}", file, e);
+                    ok = false;
+                }
+            }
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+/// Writes the API reference of the inputs, or of the source directories of the manifest.
+/// Returns false if a file could not be parsed or a page could not be written.
+fn document(args: &Args) -> bool {
+    let files = match &args.manifest {
+        Some(path) => read_manifest(path, args).map(|manifest| manifest.sources()),
+        None => Ok(input_files(args)),
+    };
+    let files = match files.and_then(|files| files.jack_files().map_err(|e| format!("Error: {}", e))) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let tokenizer = Tokenizer::new();
+    let parser = Parser::new().allow_file_name_mismatch(args.allow_file_name_mismatch);
+    let mut parse_trees: Vec<ParseTree> = Vec::new();
+    let mut ok = true;
+    for file in files {
+        let text = match std::fs::read_to_string(&file) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                ok = false;
+                continue;
+            }
+        };
+        let parse_tree = tokenizer
+            .tokenize(&text)
+            .map_err(|e| e.to_string())
+            .and_then(|tokens| parser.parse(tokens, &file).map_err(|e| e.to_string()));
+        match parse_tree {
+            Ok(parse_tree) => parse_trees.push(parse_tree),
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                ok = false;
+            }
+        }
+    }
+    if !ok {
+        return false;
+    }
+    let classes: Vec<_> = parse_trees.iter().map(|parse_tree| &parse_tree.class_node).collect();
+    let pages = DocWriter::new().format(args.doc_format).write(&classes);
+    let dir = args.out_dir.clone().unwrap_or_else(|| PathBuf::from(DOC_DIR));
+    let written = std::fs::create_dir_all(&dir)
+        .and_then(|_| pages.iter().try_for_each(|page| std::fs::write(dir.join(&page.file_name), &page.content)));
+    if let Err(e) = written {
+        eprintln!("Error: failed to write the documentation to '{}': {}", dir.display(), e);
+        return false;
+    }
+    eprintln!("Documented {} classes in '{}'", classes.len(), dir.display());
+    true
+}
+
+fn main() {
+    let args = parse_args();
+    match args.subcommand {
+        Subcommand::Watch => watch(&args),
+        Subcommand::Fmt => std::process::exit(if format(&args) { 0 } else { 1 }),
+        Subcommand::Doc => std::process::exit(if document(&args) { 0 } else { 1 }),
+        Subcommand::Profile => {
+            if let Err(e) = profile(&args) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Subcommand::Build => (),
+    }
+    let output = match compile(&args) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    print_diagnostics(&output);
+    if let Err(e) = write_output(&output, &args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if output.cache.is_some() && !args.stdout {
+        eprintln!("{}", output.summary());
+    }
+}
+
+/// Compiles the inputs, or the program described by the manifest, links it with the
+/// operating system and runs it in the emulator. Prints the flat profile, and writes
+/// the folded stacks if `--folded` is given.
+fn profile(args: &Args) -> Result<(), String> {
+    let (mut output, os) = match &args.manifest {
+        Some(path) => {
+            let mut manifest = read_manifest(path, args)?;
+            manifest.options.emit = Emit::default();
+            manifest.options.cache = None;
+            let compiler = Compiler::with_options(manifest.options.clone());
+            let mut output = compiler
+                .compile_program(&manifest.sources(), &manifest.libraries(), &manifest.entry)
+                .map_err(|e| e.to_string())?;
+            // the libraries are not part of the output of a build, but they are run
+            let libraries = compiler.compile_files(&manifest.libraries()).map_err(|e| e.to_string())?;
+            output.classes.extend(libraries.classes);
+            (output, manifest.os)
+        }
+        None => {
+            let mut options = CompileOptions::default();
+            args.apply(&mut options);
+            let output = Compiler::with_options(options)
+                .compile_files(&input_files(args))
+                .map_err(|e| e.to_string())?;
+            (output, Os::Native)
+        }
+    };
+    print_diagnostics(&output);
+    // the emulator has no built-in operating system
+    let os = match args.os.clone().unwrap_or(os) {
+        Os::Native => Os::Bundled,
+        os => os,
+    };
+    os.link(&mut output, Path::new("")).map_err(|e| e.to_string())?;
+
+    let mut program = Program::new();
+    for class in output.classes.iter() {
+        if let Some(vm) = class.artifact(ArtifactKind::Vm) {
+            program
+                .add_file(&vm.path.to_string_lossy(), &vm.content)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    if !program.function("Sys.init").is_some_and(|init| program.functions()[init].is_defined()) {
+        return Err("Error: the program has no function 'Sys.init'. Link it with an operating system \
+                    that has a class Sys: --os bundled, --os <DIR>, or the 'os' of the manifest"
+            .to_string());
+    }
+    let mut profiler = Profiler::new(&program);
+    let mut emulator = Emulator::new(&program);
+    let result = emulator.run(&mut profiler, args.max_instructions);
+    print!("{}", profiler.flat_profile());
+    if let Some(path) = &args.folded {
+        fs::write(path, profiler.folded_stacks())
+            .map_err(|e| format!("Error: failed to write the folded stacks to '{}': {}", path.display(), e))?;
+    }
+    match result.map_err(|e| e.to_string())? {
+        Stop::Halted => eprintln!("The program halted after {} VM instructions", emulator.executed()),
+        Stop::Returned => eprintln!("Sys.init returned after {} VM instructions", emulator.executed()),
+        Stop::Limit => eprintln!(
+            "Stopped the program after {} VM instructions (--max-instructions)",
+            emulator.executed()
+        ),
+    }
+    Ok(())
 }
@@ -0,0 +1,384 @@
+//! Reads the project manifest, `jack.toml`, which describes a program that is spread
+//! over several directories.
+//!
+//! ```toml
+//! [program]
+//! entry = "Main"               # the class with the `main` function
+//! sources = ["src"]            # compiled, default: the directory of the manifest
+//! libraries = ["../lib"]       # available to the program, but not compiled again
+//! os = "native"                # "native", "bundled" or a directory of .vm files
+//!
+//! [options]                    # the defaults for the command line options
+//! emit = ["vm"]
+//! out_dir = "build"
+//! recursive = true
+//! include = ["**/*.jack"]
+//! exclude = ["*Test.jack"]
+//! annotate = false
+//! debug_info = false
+//! allow_file_name_mismatch = false
+//! warnings = false             # report code that is probably a mistake
+//! opt_level = 0                # 1 folds expressions of constants
+//! extensions = []              # e.g. ["precedence"]
+//! jobs = 4                     # default: one per CPU core
+//! cache = "build/.jack-cache"  # only compile the classes that changed
+//! ```
+//!
+//! Relative paths are relative to the directory of the manifest. Only a subset of TOML is
+//! accepted: inline tables, arrays of tables, dotted and quoted keys, multi-line strings,
+//! floats and dates are rejected.
+mod toml;
+use toml::{Entry, Value};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::compiler::options::{CompileOptions, Emit, Extension, OptLevel};
+use crate::compiler::output::{Artifact, ArtifactKind, ClassOutput, CompileOutput};
+use crate::io::source::DiskProvider;
+use crate::os;
+
+/// The file name of the manifest.
+pub const MANIFEST_FILE: &str = "jack.toml";
+
+/// Describes a program, see the [module documentation](self).
+#[derive(Debug)]
+pub struct Manifest {
+    /// The class with the `main` function.
+    pub entry: String,
+    /// The directories that are compiled.
+    pub sources: Vec<PathBuf>,
+    /// The directories whose classes the program uses, but that are not compiled again.
+    pub libraries: Vec<PathBuf>,
+    pub os: Os,
+    /// The default compile options.
+    pub options: CompileOptions,
+    /// Search the subdirectories of the source and library directories as well.
+    pub recursive: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+/// The implementation of the operating system (`Math`, `Memory`, `Output`, ...)
+/// the program is linked with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Os {
+    /// The built-in implementation of the VM emulator.
+    Native,
+    /// The [operating system that is bundled with the compiler](crate::os).
+    /// Its classes are compiled next to the compiled program.
+    Bundled,
+    /// A directory of `.vm` files, e.g. the solution of project 12.
+    /// The files are copied next to the compiled program.
+    Dir(PathBuf),
+}
+
+impl Manifest {
+    /// Reads the manifest at the given path.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Manifest, ManifestError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| ManifestError {
+            file: path.to_path_buf(),
+            line: 0,
+            message: format!("failed to read the manifest: {}", e),
+        })?;
+        let root = match path.parent() {
+            Some(root) if !root.as_os_str().is_empty() => root,
+            _ => Path::new("."),
+        };
+        Manifest::parse(&text, root).map_err(|e| ManifestError {
+            file: path.to_path_buf(),
+            ..e
+        })
+    }
+
+    /// Parses the contents of a manifest. Relative paths are resolved against `root`.
+    pub fn parse(text: &str, root: &Path) -> Result<Manifest, ManifestError> {
+        let mut document = toml::parse(text)?;
+        let mut program = take_table(&mut document, "program");
+        let options_table = take_table(&mut document, "options");
+        if let Some((table, keys)) = document.iter().find(|(_, keys)| !keys.is_empty()) {
+            let (key, entry) = keys.iter().next().unwrap();
+            let message = match table.as_str() {
+                "" => format!("Key '{}' must be in a table, e.g. '[program]'", key),
+                _ => format!("Unknown table '{}'", table),
+            };
+            return Err(ManifestError::new(entry.line, &message));
+        }
+
+        let entry = match program.remove("entry") {
+            Some(entry) => string(&entry)?,
+            None => return Err(ManifestError::new(0, "Missing key 'entry' in table 'program'")),
+        };
+        let sources = match program.remove("sources") {
+            Some(sources) => paths(&sources, root)?,
+            None => vec![root.to_path_buf()],
+        };
+        let libraries = match program.remove("libraries") {
+            Some(libraries) => paths(&libraries, root)?,
+            None => Vec::new(),
+        };
+        let os = match program.remove("os") {
+            Some(os) => match string(&os)?.as_str() {
+                "native" => Os::Native,
+                "bundled" => Os::Bundled,
+                dir => Os::Dir(root.join(dir)),
+            },
+            None => Os::Native,
+        };
+        no_unknown_keys(&program, "program")?;
+
+        let mut options = CompileOptions::default();
+        let mut recursive = false;
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        for (key, entry) in options_table.iter() {
+            match key.as_str() {
+                "emit" => options.emit = emit(entry)?,
+                "out_dir" => options.out_dir = Some(root.join(string(entry)?)),
+                "recursive" => recursive = boolean(entry)?,
+                "include" => include = strings(entry)?,
+                "exclude" => exclude = strings(entry)?,
+                "annotate" => options.annotate = boolean(entry)?,
+                "debug_info" => options.debug_info = boolean(entry)?,
+                "allow_file_name_mismatch" => options.allow_file_name_mismatch = boolean(entry)?,
+                "warnings" => options.warnings = boolean(entry)?,
+                "opt_level" => options.optimization = opt_level(entry)?,
+                "extensions" => options.extensions = extensions(entry)?,
+                "jobs" => options.jobs = positive_integer(entry)?,
+                "cache" => options.cache = Some(root.join(string(entry)?)),
+                _ => {
+                    return Err(ManifestError::new(
+                        entry.line,
+                        &format!("Unknown key '{}' in table 'options'", key),
+                    ))
+                }
+            }
+        }
+
+        Ok(Manifest {
+            entry,
+            sources,
+            libraries,
+            os,
+            options,
+            recursive,
+            include,
+            exclude,
+        })
+    }
+
+    /// Returns the [`DiskProvider`] of the source directories.
+    pub fn sources(&self) -> DiskProvider {
+        self.provider(&self.sources)
+    }
+
+    /// Returns the [`DiskProvider`] of the library directories.
+    pub fn libraries(&self) -> DiskProvider {
+        self.provider(&self.libraries)
+    }
+
+    fn provider(&self, paths: &[PathBuf]) -> DiskProvider {
+        let mut files = DiskProvider::with_paths(paths).recursive(self.recursive);
+        for pattern in self.include.iter() {
+            files = files.include(pattern);
+        }
+        for pattern in self.exclude.iter() {
+            files = files.exclude(pattern);
+        }
+        files
+    }
+}
+
+impl Os {
+    /// Adds the `.vm` files of the operating system to the output, in `dir`.
+    /// A class of the operating system is left out if the program or one of its libraries
+    /// defines a class with the same name, so that single classes of the operating system
+    /// can be replaced.
+    pub fn link(&self, output: &mut CompileOutput, dir: &Path) -> Result<(), ManifestError> {
+        // the name, the source and the VM code of every class
+        let classes: Vec<(String, String, String)> = match self {
+            Os::Native => return Ok(()),
+            Os::Bundled => os::compile()
+                .map_err(|e| ManifestError::new(0, &format!("failed to compile the bundled OS: {}", e)))?
+                .into_iter()
+                .map(|(name, content)| (name.clone(), format!("<bundled OS>/{}.jack", name), content))
+                .collect(),
+            Os::Dir(os_dir) => read_os_dir(os_dir)?,
+        };
+        for (name, source, content) in classes {
+            if output.class(&name).is_some() || output.libraries.contains(&name) {
+                continue;
+            }
+            let path_in_dir = dir.join(format!("{}{}", name, ArtifactKind::Vm.suffix()));
+            let up_to_date = fs::read_to_string(&path_in_dir).is_ok_and(|copy| copy == content);
+            output.classes.push(ClassOutput {
+                name,
+                source,
+                artifacts: vec![Artifact {
+                    kind: ArtifactKind::Vm,
+                    path: path_in_dir,
+                    content,
+                }],
+                diagnostics: Vec::new(),
+                up_to_date,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name, the path and the content of every `.vm` file in the directory.
+fn read_os_dir(os_dir: &Path) -> Result<Vec<(String, String, String)>, ManifestError> {
+    let read_error = |e: std::io::Error| {
+        ManifestError::new(0, &format!("failed to read the OS directory '{}': {}", os_dir.display(), e))
+    };
+    let mut files = Vec::new();
+    for entry in fs::read_dir(os_dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        if path.extension().is_some_and(|ending| ending == "vm") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    let mut classes = Vec::new();
+    for path in files {
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let content = fs::read_to_string(&path).map_err(read_error)?;
+        classes.push((name, path.to_string_lossy().into_owned(), content));
+    }
+    Ok(classes)
+}
+
+/// This error occurs when the manifest cannot be read or contains invalid values.
+#[derive(Debug)]
+pub struct ManifestError {
+    pub file: PathBuf,
+    /// The line the error occurred in, or 0 if the error does not belong to a line.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ManifestError {
+    fn new(line: usize, message: &str) -> ManifestError {
+        ManifestError {
+            file: PathBuf::from(MANIFEST_FILE),
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "{}: Error: {}", self.file.display(), self.message),
+            line => write!(f, "{}: Error in line {}: {}", self.file.display(), line, self.message),
+        }
+    }
+}
+
+impl error::Error for ManifestError {}
+
+//------------------------------
+// VALUES
+//------------------------------
+
+fn take_table(document: &mut toml::Document, name: &str) -> BTreeMap<String, Entry> {
+    document.remove(name).unwrap_or_default()
+}
+
+fn no_unknown_keys(table: &BTreeMap<String, Entry>, name: &str) -> Result<(), ManifestError> {
+    match table.iter().next() {
+        Some((key, entry)) => Err(ManifestError::new(
+            entry.line,
+            &format!("Unknown key '{}' in table '{}'", key, name),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn string(entry: &Entry) -> Result<String, ManifestError> {
+    match &entry.value {
+        Value::String(s) => Ok(s.clone()),
+        _ => Err(ManifestError::new(entry.line, "Expected a string")),
+    }
+}
+
+fn boolean(entry: &Entry) -> Result<bool, ManifestError> {
+    match &entry.value {
+        Value::Boolean(b) => Ok(*b),
+        _ => Err(ManifestError::new(entry.line, "Expected true or false")),
+    }
+}
+
+fn positive_integer(entry: &Entry) -> Result<usize, ManifestError> {
+    match &entry.value {
+        Value::Integer(n) if *n > 0 => Ok(*n as usize),
+        _ => Err(ManifestError::new(entry.line, "Expected a number greater than 0")),
+    }
+}
+
+fn strings(entry: &Entry) -> Result<Vec<String>, ManifestError> {
+    match &entry.value {
+        Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                Value::String(s) => Ok(s.clone()),
+                _ => Err(ManifestError::new(entry.line, "Expected an array of strings")),
+            })
+            .collect(),
+        _ => Err(ManifestError::new(entry.line, "Expected an array of strings")),
+    }
+}
+
+fn paths(entry: &Entry, root: &Path) -> Result<Vec<PathBuf>, ManifestError> {
+    Ok(strings(entry)?.iter().map(|path| root.join(path)).collect())
+}
+
+fn emit(entry: &Entry) -> Result<Emit, ManifestError> {
+    let mut emit = Emit {
+        tokens: false,
+        xml: false,
+        vm: false,
+    };
+    for kind in strings(entry)? {
+        match kind.as_str() {
+            "tokens" => emit.tokens = true,
+            "xml" => emit.xml = true,
+            "vm" => emit.vm = true,
+            _ => {
+                return Err(ManifestError::new(
+                    entry.line,
+                    &format!("Unknown output '{}' (expected tokens, xml or vm)", kind),
+                ))
+            }
+        }
+    }
+    Ok(emit)
+}
+
+fn opt_level(entry: &Entry) -> Result<OptLevel, ManifestError> {
+    match &entry.value {
+        Value::Integer(n) => OptLevel::get(&n.to_string()),
+        _ => None,
+    }
+    .ok_or_else(|| ManifestError::new(entry.line, "Expected the optimization level 0 or 1"))
+}
+
+fn extensions(entry: &Entry) -> Result<BTreeSet<Extension>, ManifestError> {
+    strings(entry)?
+        .iter()
+        .map(|name| {
+            Extension::get(name).ok_or_else(|| {
+                ManifestError::new(
+                    entry.line,
+                    &format!("Unknown extension '{}' (expected precedence)", name),
+                )
+            })
+        })
+        .collect()
+}
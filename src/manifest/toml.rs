@@ -0,0 +1,229 @@
+//! Reads the subset of TOML used by the manifest, which is:
+//!
+//! - table headers like `[program]`, every table at most once
+//! - `key = value` pairs with bare keys, i.e. letters, digits, `_` and `-`
+//! - basic strings `"..."` with the escapes `\b \t \n \f \r \" \\ \uXXXX \UXXXXXXXX`,
+//!   and literal strings `'...'`
+//! - the booleans `true` and `false`
+//! - decimal integers with an optional sign, and `_` between two digits
+//! - arrays of these values, which may span several lines and end with a comma
+//! - `#` comments
+//!
+//! Everything else is an error: inline tables, arrays of tables, dotted and quoted keys,
+//! multi-line strings, floats, dates, and integers in hexadecimal, octal or binary.
+use std::collections::BTreeMap;
+
+use super::ManifestError;
+
+/// A TOML value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Value {
+    String(String),
+    Boolean(bool),
+    Integer(i64),
+    Array(Vec<Value>),
+}
+
+/// A key-value pair, together with the line it was defined in.
+#[derive(Debug)]
+pub(super) struct Entry {
+    pub(super) value: Value,
+    pub(super) line: usize,
+}
+
+/// Maps the table names to their keys. The keys before the first table header
+/// are in the table with the empty name.
+pub(super) type Document = BTreeMap<String, BTreeMap<String, Entry>>;
+
+pub(super) fn parse(text: &str) -> Result<Document, ManifestError> {
+    let mut document = Document::new();
+    let mut table = String::new();
+    document.insert(table.clone(), BTreeMap::new());
+    let mut lines = text.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let number = index + 1;
+        let mut content = strip_comment(line).trim().to_string();
+        if content.is_empty() {
+            continue;
+        }
+        if content.starts_with("[[") {
+            return Err(ManifestError::new(number, "Arrays of tables are not supported"));
+        }
+        if content.starts_with('[') {
+            if !content.ends_with(']') {
+                return Err(ManifestError::new(number, "Expected a table header like '[program]'"));
+            }
+            table = content[1..content.len() - 1].trim().to_string();
+            if document.contains_key(&table) {
+                return Err(ManifestError::new(number, &format!("Table '{}' is defined twice", table)));
+            }
+            document.insert(table.clone(), BTreeMap::new());
+            continue;
+        }
+        let idx = match content.find('=') {
+            Some(idx) => idx,
+            None => return Err(ManifestError::new(number, "Expected 'key = value'")),
+        };
+        let key = content[..idx].trim().to_string();
+        if key.starts_with('"') || key.starts_with('\'') {
+            return Err(ManifestError::new(number, "Quoted keys are not supported"));
+        }
+        if key.contains('.') {
+            return Err(ManifestError::new(number, "Dotted keys are not supported"));
+        }
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(ManifestError::new(number, &format!("Invalid key '{}'", key)));
+        }
+        // an array may span several lines
+        while content.contains('[') && !is_balanced(&content) {
+            match lines.next() {
+                Some((_, next)) => {
+                    content.push(' ');
+                    content.push_str(strip_comment(next).trim());
+                }
+                None => return Err(ManifestError::new(number, "Unterminated array")),
+            }
+        }
+        let value_text = content[idx + 1..].trim();
+        let (value, rest) = parse_value(value_text).map_err(|message| ManifestError::new(number, &message))?;
+        if !rest.trim().is_empty() {
+            return Err(ManifestError::new(
+                number,
+                &format!("Unexpected '{}' after the value", rest.trim()),
+            ));
+        }
+        let keys = document.get_mut(&table).unwrap();
+        if keys.contains_key(&key) {
+            return Err(ManifestError::new(number, &format!("Key '{}' is defined twice", key)));
+        }
+        keys.insert(key, Entry { value, line: number });
+    }
+    Ok(document)
+}
+
+/// Removes a `#` comment that is not inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote == Some('"') => escaped = true,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            _ if quote == Some(c) => quote = None,
+            '#' if quote.is_none() => return &line[..idx],
+            _ => (),
+        }
+    }
+    line
+}
+
+/// Returns true if every `[` outside of strings is closed.
+fn is_balanced(text: &str) -> bool {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote == Some('"') => escaped = true,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            _ if quote == Some(c) => quote = None,
+            '[' if quote.is_none() => depth += 1,
+            ']' if quote.is_none() => depth -= 1,
+            _ => (),
+        }
+    }
+    depth <= 0
+}
+
+/// Parses a value at the start of `text` and returns it together with the rest of `text`.
+fn parse_value(text: &str) -> Result<(Value, &str), String> {
+    if text.starts_with("\"\"\"") || text.starts_with("'''") {
+        return Err("Multi-line strings are not supported".to_string());
+    }
+    if let Some(rest) = text.strip_prefix('\'') {
+        return match rest.find('\'') {
+            Some(idx) => Ok((Value::String(rest[..idx].to_string()), &rest[idx + 1..])),
+            None => Err("Unterminated string".to_string()),
+        };
+    }
+    if let Some(rest) = text.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::String(value), &rest[idx + 1..])),
+                '\\' => match chars.next() {
+                    Some((_, 'b')) => value.push('\u{8}'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 'f')) => value.push('\u{c}'),
+                    Some((_, 'r')) => value.push('\r'),
+                    Some((_, c @ ('"' | '\\'))) => value.push(c),
+                    Some((start, c @ ('u' | 'U'))) => {
+                        let digits = if c == 'u' { 4 } else { 8 };
+                        let hex = rest.get(start + 1..start + 1 + digits).unwrap_or_default();
+                        match u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                            Some(c) if hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+                                value.push(c);
+                                chars.nth(digits - 1);
+                            }
+                            _ => return Err("Invalid unicode escape sequence in string".to_string()),
+                        }
+                    }
+                    _ => return Err("Invalid escape sequence in string".to_string()),
+                },
+                _ => value.push(c),
+            }
+        }
+        return Err("Unterminated string".to_string());
+    }
+    if text.starts_with('{') {
+        return Err("Inline tables are not supported".to_string());
+    }
+    if let Some(mut rest) = text.strip_prefix('[') {
+        let mut values = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(values), after));
+            }
+            let (value, after) = parse_value(rest)?;
+            values.push(value);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return Err("Expected ',' or ']' in array".to_string());
+            }
+        }
+    }
+    let end = text
+        .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+        .unwrap_or(text.len());
+    let (word, rest) = text.split_at(end);
+    match word {
+        "true" => Ok((Value::Boolean(true), rest)),
+        "false" => Ok((Value::Boolean(false), rest)),
+        _ => match integer(word) {
+            Some(n) => Ok((Value::Integer(n), rest)),
+            None if ["0x", "0o", "0b"].iter().any(|prefix| word.starts_with(prefix)) => {
+                Err(format!("Invalid value '{}', only decimal integers are supported", word))
+            }
+            None => Err(format!("Invalid value '{}'", word)),
+        },
+    }
+}
+
+/// Parses a decimal integer like `-1_000`. Leading zeros are not allowed, and every `_`
+/// must be between two digits.
+fn integer(word: &str) -> Option<i64> {
+    let digits = word.strip_prefix(['+', '-']).unwrap_or(word);
+    let valid = digits.split('_').all(|group| !group.is_empty() && group.chars().all(|c| c.is_ascii_digit()))
+        && (digits == "0" || !digits.starts_with('0'));
+    match valid {
+        true => word.replace('_', "").parse().ok(),
+        false => None,
+    }
+}
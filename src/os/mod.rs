@@ -0,0 +1,29 @@
+//! The operating system that is bundled with the compiler, see
+//! [`Os::Bundled`](crate::manifest::Os::Bundled): the classes `Array`, `Keyboard`,
+//! `Math`, `Memory`, `Output`, `Screen`, `String` and `Sys` of the book, written in Jack.
+//!
+//! Programs linked with it run on the CPU emulator and in the [`emulator`](crate::emulator),
+//! which has no built-in implementation of the operating system.
+use crate::compiler::error::CompileError;
+use crate::compiler::Compiler;
+
+/// The name and the Jack code of every class of the operating system.
+pub const CLASSES: [(&str, &str); 8] = [
+    ("Array", include_str!("Array.jack")),
+    ("Keyboard", include_str!("Keyboard.jack")),
+    ("Math", include_str!("Math.jack")),
+    ("Memory", include_str!("Memory.jack")),
+    ("Output", include_str!("Output.jack")),
+    ("Screen", include_str!("Screen.jack")),
+    ("String", include_str!("String.jack")),
+    ("Sys", include_str!("Sys.jack")),
+];
+
+/// Compiles the operating system and returns the name and the VM code of every class.
+pub fn compile() -> Result<Vec<(String, String)>, CompileError> {
+    let compiler = Compiler::new();
+    CLASSES
+        .iter()
+        .map(|(name, text)| Ok((name.to_string(), compiler.compile_str(&format!("{}.jack", name), text)?)))
+        .collect()
+}
@@ -0,0 +1,117 @@
+//! Lossless concrete syntax tree. Unlike the [`ParseTree`](super::parse_tree::ParseTree),
+//! it keeps every token with its original text, whitespace and comments, so that
+//! printing the tree gives back the Jack file unchanged. This is the basis for tools
+//! that rewrite Jack code, such as formatters.
+//!
+//! The tree is built by the parser of the parse tree, which records the grammar rules
+//! it enters, see [`Parser::parse_lossless`](super::Parser::parse_lossless).
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::tokenizer::tokens::SyntaxToken;
+
+/// The grammar rule a [`SyntaxNode`] was built from. The names follow the
+/// non-terminals of the XML output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The root node: the class followed by the end of file token.
+    File,
+    Class,
+    ClassVarDec,
+    SubroutineDec,
+    ParameterList,
+    SubroutineBody,
+    VarDec,
+    Statements,
+    LetStatement,
+    IfStatement,
+    WhileStatement,
+    DoStatement,
+    ReturnStatement,
+    Expression,
+    Term,
+    ExpressionList,
+}
+
+/// A node of the concrete syntax tree.
+#[derive(Debug)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+/// A child of a [`SyntaxNode`]: either another node or a token.
+#[derive(Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    fn new(kind: NodeKind) -> SyntaxNode {
+        SyntaxNode {
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the tokens of the node and all of its descendants, in source order.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => write!(f, "{}", node)?,
+                SyntaxElement::Token(token) => write!(f, "{}", token)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What the parser did, in the order it did it, see
+/// [`Cursor::recording`](super::cursor::Cursor::recording).
+pub(super) enum Event {
+    /// Entered the grammar rule of a node.
+    Start(NodeKind),
+    /// Consumed the next token.
+    Token,
+    /// Left the grammar rule of the last started node.
+    Finish,
+}
+
+/// Builds the tree of a file from the events of a successful parse and the tokens of
+/// [`tokenize_lossless`](crate::tokenizer::Tokenizer::tokenize_lossless), which carry
+/// the whitespace and comments between the tokens the parser consumed.
+pub(super) fn build(events: Vec<Event>, tokens: Vec<SyntaxToken>) -> SyntaxNode {
+    let mut tokens = tokens.into_iter();
+    let mut nodes = vec![SyntaxNode::new(NodeKind::File)];
+    for event in events {
+        match event {
+            Event::Start(kind) => nodes.push(SyntaxNode::new(kind)),
+            Event::Token => {
+                let token = tokens.next().expect("every consumed token is a lossless token");
+                nodes.last_mut().unwrap().children.push(SyntaxElement::Token(token));
+            }
+            Event::Finish => {
+                let node = nodes.pop().unwrap();
+                nodes.last_mut().unwrap().children.push(SyntaxElement::Node(node));
+            }
+        }
+    }
+    let mut file = nodes.pop().unwrap();
+    // the end of file token, which holds the trivia after the class
+    file.children.extend(tokens.map(SyntaxElement::Token));
+    file
+}
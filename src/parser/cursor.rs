@@ -0,0 +1,156 @@
+//! Walks over the tokens of a Jack file, one token at a time.
+use super::cst::{Event, NodeKind};
+use super::error::ParseError;
+use crate::tokenizer::tokens::{Keyword, Position, Symbol, Token, TokenWrapper};
+
+/// Points at the next token to be consumed by the parser. Tokens are only ever
+/// consumed from the front, so the parser looks at every token once.
+///
+/// A cursor made with [`recording`](Cursor::recording) also records the grammar rules
+/// the parser enters and the tokens it consumes, from which
+/// [`parse_lossless`](super::Parser::parse_lossless) builds the concrete syntax tree.
+pub(super) struct Cursor<'a> {
+    tokens: &'a [TokenWrapper],
+    index: usize,
+    events: Option<Vec<Event>>,
+}
+
+impl<'a> Cursor<'a> {
+    pub(super) fn new(tokens: &'a [TokenWrapper]) -> Cursor<'a> {
+        Cursor {
+            tokens,
+            index: 0,
+            events: None,
+        }
+    }
+
+    /// Returns a cursor that records the [`Event`]s of the parser.
+    pub(super) fn recording(tokens: &'a [TokenWrapper]) -> Cursor<'a> {
+        Cursor {
+            events: Some(Vec::new()),
+            ..Cursor::new(tokens)
+        }
+    }
+
+    /// Returns the recorded events.
+    pub(super) fn into_events(self) -> Vec<Event> {
+        self.events.unwrap_or_default()
+    }
+
+    /// Starts a node of the concrete syntax tree, which holds the tokens that are consumed
+    /// until the matching [`finish`](Cursor::finish).
+    pub(super) fn start(&mut self, kind: NodeKind) {
+        if let Some(events) = self.events.as_mut() {
+            events.push(Event::Start(kind));
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        if let Some(events) = self.events.as_mut() {
+            events.push(Event::Finish);
+        }
+    }
+
+    /// Returns the next token without consuming it, or `None` at the end of the file.
+    pub(super) fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.index).map(|token| &token.token)
+    }
+
+    /// Returns the doc comment before the next token.
+    pub(super) fn doc(&self) -> Option<String> {
+        self.tokens.get(self.index).and_then(|token| token.doc.clone())
+    }
+
+    /// Returns true if the next token is the given symbol.
+    pub(super) fn at(&self, symbol: Symbol) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    /// Returns true if the next token is the given keyword.
+    pub(super) fn at_keyword(&self, keyword: Keyword) -> bool {
+        matches!(self.peek(), Some(Token::Keyword(k)) if *k == keyword)
+    }
+
+    /// Returns the position of the next token.
+    pub(super) fn position(&self) -> Result<Position, ParseError> {
+        match self.tokens.get(self.index) {
+            Some(token) => Ok(token.position()),
+            None => Err(self.error("Unexpected end of file")),
+        }
+    }
+
+    /// Returns the last consumed token.
+    pub(super) fn previous(&self) -> &'a TokenWrapper {
+        &self.tokens[self.index - 1]
+    }
+
+    /// Consumes the next token.
+    pub(super) fn advance(&mut self) -> Result<&'a TokenWrapper, ParseError> {
+        match self.tokens.get(self.index) {
+            Some(token) => {
+                self.index += 1;
+                if let Some(events) = self.events.as_mut() {
+                    events.push(Event::Token);
+                }
+                Ok(token)
+            }
+            None => Err(self.error("Unexpected end of file")),
+        }
+    }
+
+    /// Consumes the next token, which must be the given symbol.
+    pub(super) fn expect(&mut self, symbol: Symbol) -> Result<&'a TokenWrapper, ParseError> {
+        if !self.at(symbol) {
+            return Err(self.error(&format!("Expected '{}' but found {}", symbol, self.found())));
+        }
+        self.advance()
+    }
+
+    /// Consumes the next token, which must be the given keyword.
+    pub(super) fn expect_keyword(&mut self, keyword: Keyword) -> Result<&'a TokenWrapper, ParseError> {
+        if !self.at_keyword(keyword) {
+            return Err(self.error(&format!("Expected '{}' but found {}", keyword, self.found())));
+        }
+        self.advance()
+    }
+
+    /// Consumes the next token, which must be an identifier, and returns its name.
+    pub(super) fn expect_identifier(&mut self) -> Result<&'a str, ParseError> {
+        match self.peek() {
+            Some(Token::Identifier(name)) => {
+                self.advance()?;
+                Ok(name)
+            }
+            _ => Err(self.error(&format!("Expected an identifier but found {}", self.found()))),
+        }
+    }
+
+    /// Describes the next token for error messages.
+    fn found(&self) -> String {
+        match self.peek() {
+            Some(token) => format!("'{}'", token),
+            None => "end of file".to_string(),
+        }
+    }
+
+    /// Returns an error at the next token, or at the last token at the end of the file.
+    pub(super) fn error(&self, message: &str) -> ParseError {
+        match self.tokens.get(self.index).or(self.tokens.last()) {
+            Some(token) => self.error_at(token, message),
+            None => ParseError {
+                message: message.to_string(),
+                position: Position::default(),
+                line: String::new(),
+            },
+        }
+    }
+
+    /// Returns an error at the given token.
+    pub(super) fn error_at(&self, token: &TokenWrapper, message: &str) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+            position: token.position(),
+            line: token.line.content.clone(),
+        }
+    }
+}
@@ -1,18 +1,31 @@
 //! Defines parse errors.
+use std::error;
 use std::fmt;
-use std::fmt::Formatter;
+
+use crate::tokenizer::tokens::Position;
 
 /// This error occurs when the Jack code violates the syntax rules.
-pub struct ParseError;
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    /// The position of the token the error occurred at,
+    /// or line 0 if the file contains no tokens at all.
+    pub position: Position,
+    /// The content of the line the error occurred in.
+    pub line: String,
+}
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Parse Error")
+        if self.position.line == 0 {
+            return write!(f, "Parse Error: {}", self.message);
+        }
+        write!(
+            f,
+            "Parse Error in line {}, column {}: {}: {}",
+            self.position.line, self.position.column, self.message, self.line
+        )
     }
 }
 
-impl fmt::Debug for ParseError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{{ file: {}, line: {}}}", file!(), line!())
-    }
-}
\ No newline at end of file
+impl error::Error for ParseError {}
@@ -1,16 +1,23 @@
 //! Reads the tokens and builds a parse tree.
-use crate::tokenizer::tokens::{Constant, Token, TokenWrapper};
+use std::sync::Arc;
+
+use crate::io::line::Line;
+use crate::tokenizer::tokens::{Constant, Keyword, Symbol, SyntaxToken, Token, TokenWrapper};
+
+pub mod cst;
+use cst::{NodeKind, SyntaxNode};
+
+mod cursor;
+use cursor::Cursor;
 
 pub mod error;
 use error::ParseError;
 
 pub mod parse_tree;
 use parse_tree::class::{ClassNode, ClassVarNode};
-use parse_tree::expression::{
-    ExpressionElement, ExpressionNode, SubroutineCallNode, TermElement, TermNode,
-};
+use parse_tree::expression::{BinaryOp, Const, Expr, SubroutineCall, UnaryOp};
 use parse_tree::statement::{
-    DoStatementNode, IfStatementNode, LetStatementNode, ReturnStatementNode, Statement,
+    DoStatementNode, IfStatementNode, LetStatementNode, LetTarget, ReturnStatementNode, Statement,
     WhileStatementNode,
 };
 use parse_tree::subroutine::{ParameterListNode, ParameterNode};
@@ -19,1111 +26,515 @@ use parse_tree::var::{VarKind, VarNode, VarType};
 use parse_tree::ParseTree;
 
 /// Reads the tokens and builds a parse tree.
-pub struct Parser {}
+pub struct Parser {
+    check_file_name: bool,
+    operator_precedence: bool,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Parser {
     pub fn new() -> Parser {
-        Parser {}
+        Parser {
+            check_file_name: true,
+            operator_precedence: false,
+        }
+    }
+
+    /// If set to true, the class name does not have to match the file name.
+    pub fn allow_file_name_mismatch(mut self, allow: bool) -> Self {
+        self.check_file_name = !allow;
+        self
+    }
+
+    /// If set to true, the binary operators bind by their
+    /// [`precedence`](parse_tree::expression::BinaryOp::precedence), so `1 + 2 * 3` is `7`.
+    /// Otherwise they are evaluated from left to right, as in standard Jack, and it is `9`.
+    pub fn operator_precedence(mut self, operator_precedence: bool) -> Self {
+        self.operator_precedence = operator_precedence;
+        self
     }
 
     /// Takes in a Vec of [`TokenWrapper`](crate::tokenizer::tokens::TokenWrapper) values,
     /// and returns a [`ParseTree`](parse_tree::ParseTree).
-    pub fn parse(&self, tokens: Vec<TokenWrapper>, filepath: &str) -> ParseTree {
-        let filepath_wo_ending = match filepath.rfind(".jack") {
-            Some(idx) => &filepath[..idx],
-            None => &filepath,
-        };
+    /// Returns a [`ParseError`](error::ParseError) for the first syntax error.
+    pub fn parse(&self, tokens: Vec<TokenWrapper>, filepath: &str) -> Result<ParseTree, ParseError> {
+        let filepath_wo_ending = filepath.strip_suffix(".jack").unwrap_or(filepath);
         let filename = match filepath_wo_ending.rfind("/") {
             Some(idx) => &filepath_wo_ending[idx + 1..],
             None => filepath_wo_ending,
         };
 
-        let (class, _) = self.parse_class(&tokens[..], 0, filename);
-        ParseTree { class_node: class }
+        let mut cursor = Cursor::new(&tokens);
+        let class = self.parse_file(&mut cursor, filename)?;
+        let mut lines: Vec<Arc<Line>> = Vec::new();
+        for token in tokens.iter() {
+            if lines.last().map(|line| line.number) != Some(token.line.number) {
+                lines.push(token.line.clone());
+            }
+        }
+        Ok(ParseTree {
+            class_node: class,
+            file: filepath.to_string(),
+            lines,
+        })
+    }
+
+    /// Takes in the tokens of
+    /// [`tokenize_lossless`](crate::tokenizer::Tokenizer::tokenize_lossless) and returns the
+    /// lossless [`SyntaxNode`](cst::SyntaxNode) of the file.
+    /// Returns a [`ParseError`](error::ParseError) for the first syntax error.
+    /// The tree is built by the same grammar as the [`ParseTree`](parse_tree::ParseTree),
+    /// but the class name does not have to match a file name.
+    pub fn parse_lossless(&self, tokens: Vec<SyntaxToken>) -> Result<SyntaxNode, ParseError> {
+        let significant: Vec<TokenWrapper> = tokens.iter().filter_map(|token| token.token.clone()).collect();
+        let mut cursor = Cursor::recording(&significant);
+        let parser = Parser {
+            check_file_name: false,
+            ..*self
+        };
+        parser.parse_file(&mut cursor, "")?;
+        Ok(cst::build(cursor.into_events(), tokens))
+    }
+
+    /// Parses the class of a file, which must be followed by the end of the file.
+    fn parse_file(&self, cursor: &mut Cursor, filename: &str) -> Result<ClassNode, ParseError> {
+        let class = self.parse_class(cursor, filename)?;
+        match cursor.peek() {
+            None => Ok(class),
+            Some(Token::Keyword(Keyword::Class)) => Err(cursor.error("Only one class is allowed per file")),
+            Some(_) => Err(cursor.error("Unexpected token after the end of the class")),
+        }
     }
 
     //------------------------------
     // CLASSES
     //------------------------------
 
-    fn parse_class(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-        filename: &str
-    ) -> (ClassNode, usize) {
-        match &tokens[index].token {
-            Token::Keyword(class_keyword) if class_keyword == "class" => {
-                if let Token::Identifier(name) = &tokens[index + 1].token {
-                    if name != filename {
-                        eprintln!(
-                            "Parse Error in line {}: Class name \"{}\" must match file name \"{}\": {}",
-                            tokens[index + 1].line.number,
-                            name,
-                            filename,
-                            tokens[index + 1].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                    match &tokens[index + 2].token {
-                        Token::Symbol(symbol) if symbol == "{" => {
-                            let mut index = index + 2;
-                            index += 1;
+    fn parse_class(&self, cursor: &mut Cursor, filename: &str) -> Result<ClassNode, ParseError> {
+        let doc = cursor.doc();
+        cursor.start(NodeKind::Class);
+        cursor.expect_keyword(Keyword::Class)?;
+        let name = cursor.expect_identifier()?;
+        let position = cursor.previous().position();
+        if self.check_file_name && name != filename {
+            return Err(cursor.error_at(
+                cursor.previous(),
+                &format!("Class name \"{}\" must match file name \"{}\"", name, filename),
+            ));
+        }
+        cursor.expect(Symbol::LeftBrace)?;
 
-                            // parse class vars
-                            let mut class_vars = Vec::new();
-                            loop {
-                                match &tokens[index].token {
-                                    Token::Keyword(keyword)
-                                        if keyword == "static" || keyword == "field" =>
-                                    {
-                                        let (class_var, j) =
-                                            self.parse_class_variable_declaration(tokens, index);
-                                        index = j;
-                                        class_vars.push(class_var);
-                                    }
-                                    _ => break,
-                                }
-                            }
+        // parse class vars
+        let mut class_vars = Vec::new();
+        while let Some(Token::Keyword(var_kind @ (Keyword::Static | Keyword::Field))) = cursor.peek() {
+            class_vars.push(self.parse_class_variable_declaration(cursor, var_kind)?);
+        }
 
-                            // parse constructors, methods, functions
-                            let mut subroutines = Vec::new();
-                            loop {
-                                match &tokens[index].token {
-                                    Token::Keyword(keyword) if self.is_subroutine(keyword) => {
-                                        let (subroutine, j) =
-                                            self.parse_subroutine(tokens, index, keyword, false);
-                                        index = j;
-                                        subroutines.push(subroutine);
-                                    }
-                                    Token::Symbol(symbol) if symbol == "}" => {
-                                        break;
-                                    }
-                                    _ => {
-                                        eprintln!(
-                                            "Parse Error in line {}: Only subroutines are allowed here: {}",
-                                            tokens[index].line.number, tokens[index].line.content
-                                        );
-                                        std::process::exit(1);
-                                    }
-                                }
-                            }
-                            (
-                                ClassNode {
-                                    name: name.to_string(),
-                                    variables: class_vars,
-                                    subroutines,
-                                },
-                                index + 1,
-                            )
-                        }
-                        _ => {
-                            eprintln!(
-                                "Parse Error in line {}: Missing curly bracket for class definition: {}",
-                                tokens[index + 2].line.number,
-                                tokens[index + 2].line.content
-                            );
-                            std::process::exit(1);
-                        }
-                    }
-                } else {
-                    eprintln!(
-                        "Parse Error in line {}: Missing class identifier: {}",
-                        tokens[index + 1].line.number,
-                        tokens[index + 1].line.content
-                    );
-                    std::process::exit(1);
+        // parse constructors, methods, functions
+        let mut subroutines = Vec::new();
+        loop {
+            match cursor.peek() {
+                Some(Token::Keyword(keyword)) if self.is_subroutine(keyword) => {
+                    subroutines.push(self.parse_subroutine(cursor, keyword)?);
                 }
-            }
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: Missing class keyword: {}",
-                    tokens[index].line.number, tokens[index].line.content
-                );
-                std::process::exit(1);
+                Some(Token::Symbol(Symbol::RightBrace)) => break,
+                _ => return Err(cursor.error("Only subroutines are allowed here")),
             }
         }
+        cursor.expect(Symbol::RightBrace)?;
+        cursor.finish();
+
+        Ok(ClassNode {
+            name: name.to_string(),
+            variables: class_vars,
+            subroutines,
+            position,
+            doc,
+        })
     }
 
-    fn parse_class_variable_declaration(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-    ) -> (ClassVarNode, usize) {
-        let mut index = index;
-
-        if let Token::Keyword(var_kind) = &tokens[index].token {
-            let var_kind = VarKind::get(var_kind);
-            index += 1;
-            let var_type = match &tokens[index].token {
-                Token::Identifier(var_type) | Token::Keyword(var_type) => VarType::get(&var_type),
-                _ => {
-                    eprintln!(
-                        "Parse Error in line {}: Var kind must be followed by a var type: {}",
-                        tokens[index].line.number, tokens[index].line.content
-                    );
-                    std::process::exit(1);
-                }
-            };
-            index += 1;
-
-            let mut var_names = Vec::new();
-            loop {
-                match &tokens[index].token {
-                    Token::Symbol(symbol) if symbol == ";" => break,
-                    Token::Symbol(symbol) if symbol == "," => (),
-                    Token::Identifier(var_name) => {
-                        var_names.push(var_name.clone());
-                    }
-                    _ => {
-                        eprintln!(
-                            "Parse Error in line {}: Unexpected token in variable declaration: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                }
-                index += 1;
-            }
-            (
-                ClassVarNode {
-                    names: var_names,
-                    var_kind,
-                    var_type,
-                },
-                index + 1,
-            )
-        } else {
-            eprintln!(
-                "Parse Error in line {}: Class variable declaration must start with a keyword: {}",
-                tokens[index].line.number, tokens[index].line.content
-            );
-            std::process::exit(1);
-        }
+    fn parse_class_variable_declaration(&self, cursor: &mut Cursor, var_kind: &Keyword) -> Result<ClassVarNode, ParseError> {
+        let doc = cursor.doc();
+        cursor.start(NodeKind::ClassVarDec);
+        let (var_kind, var_type, names) = self.parse_var_names(cursor, var_kind)?;
+        cursor.finish();
+        Ok(ClassVarNode {
+            names,
+            var_kind,
+            var_type,
+            doc,
+        })
     }
 
     //------------------------------
     // SUBROUTINES
     //------------------------------
 
-    fn parse_subroutine(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-        subroutine_type: &str,
-        inside_function: bool,
-    ) -> (SubroutineNode, usize) {
-        if inside_function {
-            eprintln!(
-                "Parse Error in line {}: Functions inside functions are not allowed: {}",
-                tokens[index].line.number, tokens[index].line.content
-            );
-        }
-        let mut index = index + 1; // skip function type: constructor, function, method
-        match &tokens[index].token {
-            Token::Keyword(return_val) | Token::Identifier(return_val) => {
-                index += 1;
-                match &tokens[index].token {
-                    Token::Identifier(subroutine_name) => {
-                        index += 1;
-                        match &tokens[index].token {
-                            Token::Symbol(symbol) if symbol == "(" => {
-                                let (parameters, mut index) =
-                                    self.parse_parameter_list(tokens, index);
-                                match &tokens[index].token {
-                                    Token::Symbol(symbol) if symbol == "{" => {
-                                        let body_end_index =
-                                            self.find_closing_bracket(tokens, index);
-                                        let mut var_declarations = Vec::new();
-                                        index += 1;
-                                        loop {
-                                            match &tokens[index].token {
-                                                Token::Keyword(keyword) if keyword == "var" => {
-                                                    let (var_dec, j) = self
-                                                        .parse_variable_declaration(
-                                                            tokens, index, false,
-                                                        );
-                                                    index = j;
-                                                    var_declarations.push(var_dec);
-                                                }
-                                                _ => break,
-                                            }
-                                        }
-                                        let (body, _) =
-                                            self.parse_body(&tokens[..body_end_index], index);
-                                        let subroutine_body = SubroutineBodyNode {
-                                            variables: var_declarations,
-                                            statements: body,
-                                        };
-                                        let return_type = if return_val == "void" {
-                                            None
-                                        } else {
-                                            Some(VarType::get(return_val))
-                                        };
-                                        (
-                                            SubroutineNode {
-                                                name: subroutine_name.clone(),
-                                                subroutine_type: SubroutineType::get(
-                                                    subroutine_type,
-                                                ),
-                                                parameter_list: parameters,
-                                                body: subroutine_body,
-                                                return_type,
-                                            },
-                                            body_end_index + 1,
-                                        )
-                                    }
-                                    _ => {
-                                        eprintln!(
-                                            "Parse Error in line {}: Missing subroutine body: {}",
-                                            tokens[index].line.number, tokens[index].line.content
-                                        );
-                                        std::process::exit(1);
-                                    }
-                                }
-                            }
-                            _ => {
-                                eprintln!(
-                                    "Parse Error in line {}: Missing parameter list for subroutine: {}",
-                                    tokens[index].line.number, tokens[index].line.content
-                                );
-                                std::process::exit(1);
-                            }
-                        }
-                    }
-                    _ => {
-                        eprintln!(
-                            "Parse Error in line {}: Missing subroutine identifier or missing return type: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                }
-            }
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: Missing subroutine identifier or missing return type: {}",
-                    tokens[index].line.number, tokens[index].line.content
-                );
-                std::process::exit(1);
-            }
+    fn parse_subroutine(&self, cursor: &mut Cursor, subroutine_type: &Keyword) -> Result<SubroutineNode, ParseError> {
+        let position = cursor.position()?;
+        let doc = cursor.doc();
+        let subroutine_type = SubroutineType::get(subroutine_type)
+            .ok_or_else(|| cursor.error("Expected 'constructor', 'function' or 'method'"))?;
+        cursor.start(NodeKind::SubroutineDec);
+        cursor.advance()?; // skip function type: constructor, function, method
+        let return_type = if cursor.at_keyword(Keyword::Void) {
+            cursor.advance()?;
+            None
+        } else {
+            Some(self.parse_var_type(cursor, "Missing subroutine identifier or missing return type")?)
+        };
+        let name = cursor.expect_identifier()?;
+
+        cursor.expect(Symbol::LeftParen)?;
+        let parameters = self.parse_parameter_list(cursor)?;
+        cursor.expect(Symbol::RightParen)?;
+
+        cursor.start(NodeKind::SubroutineBody);
+        cursor.expect(Symbol::LeftBrace)?;
+        let mut var_declarations = Vec::new();
+        while let Some(Token::Keyword(var_kind @ Keyword::Var)) = cursor.peek() {
+            var_declarations.push(self.parse_variable_declaration(cursor, var_kind)?);
         }
+        let statements = self.parse_statements(cursor)?;
+        cursor.expect(Symbol::RightBrace)?;
+        cursor.finish();
+        cursor.finish();
+
+        Ok(SubroutineNode {
+            name: name.to_string(),
+            subroutine_type,
+            parameter_list: parameters,
+            body: SubroutineBodyNode {
+                variables: var_declarations,
+                statements,
+            },
+            return_type,
+            position,
+            doc,
+        })
     }
 
     fn parse_variable_declaration(
         &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-        class_vars: bool,
-    ) -> (VarNode, usize) {
-        let mut index = index;
-
-        if let Token::Keyword(var_kind) = &tokens[index].token {
-            let var_kind = VarKind::get(var_kind);
-            index += 1;
-            let var_type = match &tokens[index].token {
-                Token::Identifier(var_type) | Token::Keyword(var_type) => VarType::get(&var_type),
-                _ => {
-                    eprintln!(
-                        "Parse Error in line {}: Var kind must be followed by a var type: {}",
-                        tokens[index].line.number, tokens[index].line.content
-                    );
-                    std::process::exit(1);
-                }
-            };
-            index += 1;
-
-            let mut var_names = Vec::new();
-            loop {
-                match &tokens[index].token {
-                    Token::Symbol(symbol) if symbol == ";" => break,
-                    Token::Symbol(symbol) if symbol == "," => (),
-                    Token::Identifier(var_name) => {
-                        var_names.push(var_name.to_string());
-                    }
-                    _ => {
-                        eprintln!(
-                            "Parse Error in line {}: Unexpected token in variable declaration: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                }
-                index += 1;
-            }
-            (
-                VarNode {
-                    var_names,
-                    var_kind: var_kind.clone(),
-                    var_type: var_type.clone(),
-                    class_var: class_vars,
-                },
-                index + 1,
-            )
-        } else {
-            eprintln!(
-                "Parse Error in line {}: Variable declaration must start with keyword: {}",
-                tokens[index].line.number, tokens[index].line.content
-            );
-            std::process::exit(1);
-        }
+        cursor: &mut Cursor,
+        var_kind: &Keyword,
+    ) -> Result<VarNode, ParseError> {
+        let position = cursor.position()?;
+        cursor.start(NodeKind::VarDec);
+        let (var_kind, var_type, var_names) = self.parse_var_names(cursor, var_kind)?;
+        cursor.finish();
+        Ok(VarNode {
+            var_names,
+            var_kind,
+            var_type,
+            position,
+        })
     }
 
-    fn parse_body(&self, tokens: &[TokenWrapper], index: usize) -> (Vec<Statement>, usize) {
-        let mut index = index;
-        let mut statements = Vec::new();
-        loop {
-            if index == tokens.len() {
-                break;
-            }
-            match &tokens[index].token {
-                Token::Keyword(keyword) if self.is_statement(keyword) => {
-                    let (statement, j) = self.parse_statement(tokens, keyword, index);
-                    index = j;
-                    statements.push(statement);
-                }
-                _ => {
-                    println!("{:?}", &tokens[index].token);
-                    eprintln!(
-                        "Parse Error in line {}: DEBUG: for now, only statements are allowed in bodies: {}",
-                        tokens[index].line.number, tokens[index].line.content
-                    );
-                    std::process::exit(1);
-                }
-            }
+    /// Parses `(static | field | var) type varName (, varName)* ;`
+    fn parse_var_names(&self, cursor: &mut Cursor, var_kind: &Keyword) -> Result<(VarKind, VarType, Vec<String>), ParseError> {
+        let var_kind = VarKind::get(var_kind).ok_or_else(|| cursor.error("Expected 'static', 'field' or 'var'"))?;
+        cursor.advance()?;
+        let var_type = self.parse_var_type(cursor, "Var kind must be followed by a var type")?;
+        let mut var_names = vec![cursor.expect_identifier()?.to_string()];
+        while cursor.at(Symbol::Comma) {
+            cursor.advance()?;
+            var_names.push(cursor.expect_identifier()?.to_string());
         }
-        (statements, index)
-    }
-
-    fn is_subroutine(&self, keyword: &str) -> bool {
-        keyword == "constructor" || keyword == "function" || keyword == "method"
+        cursor.expect(Symbol::Semicolon)?;
+        Ok((var_kind, var_type, var_names))
     }
 
-    fn parse_parameter_list(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-    ) -> (ParameterListNode, usize) {
-        let mut index = index + 1;
-
+    fn parse_parameter_list(&self, cursor: &mut Cursor) -> Result<ParameterListNode, ParseError> {
         let mut parameters = Vec::new();
-        loop {
-            match &tokens[index].token {
-                Token::Symbol(symbol) if symbol == ")" => {
-                    index += 1;
+        cursor.start(NodeKind::ParameterList);
+        if !cursor.at(Symbol::RightParen) {
+            loop {
+                let var_type = self.parse_var_type(cursor, "Unexpected token in parameter list")?;
+                let name = cursor.expect_identifier()?;
+                parameters.push(ParameterNode {
+                    name: name.to_string(),
+                    var_type,
+                });
+                if !cursor.at(Symbol::Comma) {
                     break;
                 }
-                Token::Keyword(var_type) | Token::Identifier(var_type) => {
-                    index += 1;
-                    if let Token::Identifier(var_name) = &tokens[index].token {
-                        index += 1;
-                        parameters.push(ParameterNode {
-                            name: var_name.clone(),
-                            var_type: VarType::get(&var_type[..]),
-                        });
-                    } else {
-                        eprintln!(
-                            "Parse Error in line {}: Missing identifier in parameter list: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                }
-                Token::Symbol(symbol) if symbol == "," => index += 1,
-                _ => {
-                    eprintln!(
-                        "Parse Error in line {}: Unexpected token in parameter list: {}",
-                        tokens[index].line.number, tokens[index].line.content
-                    );
-                    std::process::exit(1);
-                }
+                cursor.advance()?;
             }
         }
-
-        (ParameterListNode { parameters }, index)
+        cursor.finish();
+        Ok(ParameterListNode { parameters })
     }
 
     //------------------------------
     // STATEMENTS
     //------------------------------
 
-    fn parse_statement(
-        &self,
-        tokens: &[TokenWrapper],
-        statement: &str,
-        index: usize,
-    ) -> (Statement, usize) {
-        if statement == "let" {
-            let (statement, index) = self.parse_let_statement(tokens, index, index);
-            (Statement::Let(statement), index)
-        } else if statement == "do" {
-            let (statement, index) = self.parse_do_statement(tokens, index);
-            (Statement::Do(statement), index)
-        } else if statement == "if" {
-            let (statement, index) = self.parse_if_statement(tokens, index);
-            (Statement::If(statement), index)
-        } else if statement == "while" {
-            let (statement, index) = self.parse_while_statement(tokens, index);
-            (Statement::While(statement), index)
-        } else if statement == "return" {
-            let (statement, index) = self.parse_return_statement(tokens, index);
-            (Statement::Return(statement), index)
-        } else {
-            // cannot happen
-            std::process::exit(1);
-        }
-    }
-
-    fn parse_let_statement(
-        &self,
-        tokens: &[TokenWrapper],
-        start_index: usize,
-        end_index: usize,
-    ) -> (LetStatementNode, usize) {
-        let mut index = start_index + 1;
-
-        if let Token::Identifier(var_name) = &tokens[index].token {
-            index += 1;
-            // parse [ expression ]
-            let lhs_expression = match &tokens[index].token {
-                Token::Symbol(symbol) if symbol == "[" => {
-                    let expr_end_index = self.find_closing_bracket(tokens, index);
-                    let lhs_expression =
-                        self.parse_expression(tokens, index + 1, expr_end_index - 1);
-                    index = expr_end_index + 1;
-                    Some(lhs_expression)
-                }
-                _ => None,
-            };
-            // parse expression after =
-            let rhs_expression = match &tokens[index].token {
-                Token::Symbol(symbol) if symbol == "=" => {
-                    index += 1;
-                    let semicolon_index =
-                        self.find_symbol(tokens, ";", index, end_index)
-                            .expect(&format!(
-                            "Parse Error in Line {}: let statement must end with a semicolon: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        ));
-
-                    let rhs_expression = self.parse_expression(tokens, index, semicolon_index - 1);
-                    index = semicolon_index;
-                    rhs_expression
-                }
-                _ => {
-                    eprintln!(
-                        "Parse Error in line {}: let statement in missing the '=' symbol: {}",
-                        tokens[index].line.number, tokens[index].line.content
-                    );
-                    std::process::exit(1);
-                }
+    fn parse_statements(&self, cursor: &mut Cursor) -> Result<Vec<Statement>, ParseError> {
+        let mut statements = Vec::new();
+        cursor.start(NodeKind::Statements);
+        while let Some(Token::Keyword(keyword)) = cursor.peek() {
+            let statement = match keyword {
+                Keyword::Let => Statement::Let(self.parse_let_statement(cursor)?),
+                Keyword::Do => Statement::Do(self.parse_do_statement(cursor)?),
+                Keyword::If => Statement::If(self.parse_if_statement(cursor)?),
+                Keyword::While => Statement::While(self.parse_while_statement(cursor)?),
+                Keyword::Return => Statement::Return(self.parse_return_statement(cursor)?),
+                _ => break,
             };
-            (
-                LetStatementNode {
-                    var_name: var_name.to_string(),
-                    lhs_expression,
-                    rhs_expression,
-                },
-                index + 1,
-            )
-        } else {
-            eprintln!(
-                "Parse Error in line {}: 'let' keyword must be followed by an identifier: {}",
-                tokens[index].line.number, tokens[index].line.content
-            );
-            std::process::exit(1);
+            statements.push(statement);
         }
+        cursor.finish();
+        Ok(statements)
     }
 
-    fn parse_do_statement(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-    ) -> (DoStatementNode, usize) {
-        let mut index = index + 1;
+    /// Parses `{ statements }`
+    fn parse_block(&self, cursor: &mut Cursor) -> Result<Vec<Statement>, ParseError> {
+        cursor.expect(Symbol::LeftBrace)?;
+        let statements = self.parse_statements(cursor)?;
+        cursor.expect(Symbol::RightBrace)?;
+        Ok(statements)
+    }
 
-        let caller = if let Token::Identifier(caller) = &tokens[index].token {
-            let mut j = index + 1;
-            match &tokens[j].token {
-                Token::Symbol(symbol) if symbol == "." => {
-                    j += 1;
-                    if let Token::Identifier(_func_name) = &tokens[j].token {
-                        index = j;
-                        Some(caller.clone())
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            }
-        } else {
-            eprintln!(
-                "Parse Error in line {}: 'do' keyword must be followed by an identifier: {}",
-                tokens[index].line.number, tokens[index].line.content
-            );
-            std::process::exit(1);
-        };
-        let subroutine_call = if let Token::Identifier(func_name) = &tokens[index].token {
-            index += 1;
-            let expression_nodes = match &tokens[index].token {
-                Token::Symbol(symbol) if symbol == "(" => {
-                    let closing_bracket_index = self.find_closing_bracket(tokens, index);
-                    let expression_list =
-                        self.parse_expression_list(tokens, index + 1, closing_bracket_index - 1);
-                    index = closing_bracket_index + 1;
-                    expression_list
-                }
-                _ => {
-                    eprintln!(
-                        "Parse Error in line {}: Subroutine name in do statement must be followed by an open bracket: {}",
-                        tokens[index].line.number, tokens[index].line.content
-                    );
-                    std::process::exit(1);
-                }
-            };
-            SubroutineCallNode {
-                subroutine_name: func_name.to_string(),
-                caller,
-                expression_list: expression_nodes,
-                semicolon: true,
-            }
+    fn parse_let_statement(&self, cursor: &mut Cursor) -> Result<LetStatementNode, ParseError> {
+        let position = cursor.position()?;
+        cursor.start(NodeKind::LetStatement);
+        cursor.advance()?;
+        let name = cursor.expect_identifier()?.to_string();
+        // parse [ expression ]
+        let target = if cursor.at(Symbol::LeftBracket) {
+            cursor.advance()?;
+            let index = self.parse_expression(cursor)?;
+            cursor.expect(Symbol::RightBracket)?;
+            LetTarget::Index { name, index }
         } else {
-            eprintln!(
-                "Parse Error in line {}: Missing subroutine name in do statement: {}",
-                tokens[index].line.number, tokens[index].line.content
-            );
-            std::process::exit(1);
+            LetTarget::Var(name)
         };
-        match &tokens[index].token {
-            Token::Symbol(symbol) if symbol == ";" => (),
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: do statement must end with a semicolon: {}",
-                    tokens[index].line.number, tokens[index].line.content
-                );
-                std::process::exit(1);
-            }
-        }
-        (DoStatementNode { subroutine_call }, index + 1)
+        // parse expression after =
+        cursor.expect(Symbol::Equal)?;
+        let value = self.parse_expression(cursor)?;
+        cursor.expect(Symbol::Semicolon)?;
+        cursor.finish();
+        Ok(LetStatementNode {
+            target,
+            value,
+            position,
+        })
     }
 
-    fn parse_return_statement(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-    ) -> (ReturnStatementNode, usize) {
-        let index = index + 1;
-        match &tokens[index].token {
-            Token::Symbol(symbol) if symbol == ";" => {
-                (ReturnStatementNode { expression: None }, index + 1)
-            }
-            _ => {
-                let semicolon_index =
-                    self.find_symbol(tokens, ";", index, tokens.len())
-                        .expect(&format!(
-                            "Parse Error in line {}: Missing semicolon in return statement: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        ));
-
-                let expression = self.parse_expression(tokens, index, semicolon_index - 1);
-                (
-                    ReturnStatementNode {
-                        expression: Some(expression),
-                    },
-                    semicolon_index + 1,
-                )
-            }
-        }
+    fn parse_do_statement(&self, cursor: &mut Cursor) -> Result<DoStatementNode, ParseError> {
+        let position = cursor.position()?;
+        cursor.start(NodeKind::DoStatement);
+        cursor.advance()?;
+        let name = cursor.expect_identifier()?;
+        let subroutine_call = self.parse_subroutine_call(cursor, name)?;
+        cursor.expect(Symbol::Semicolon)?;
+        cursor.finish();
+        Ok(DoStatementNode {
+            subroutine_call,
+            position,
+        })
     }
 
-    fn parse_if_statement(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-    ) -> (IfStatementNode, usize) {
-        let mut index = index + 1;
-        let condition = match &tokens[index].token {
-            Token::Symbol(symbol) if symbol == "(" => {
-                let closing_bracket_index = self.find_closing_bracket(tokens, index);
-                let expression =
-                    self.parse_expression(tokens, index + 1, closing_bracket_index - 1);
-                index = closing_bracket_index + 1;
-                expression
-            }
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: if statement must be followed by an open bracket: {}",
-                    tokens[index].line.number, tokens[index].line.content
-                );
-                std::process::exit(1);
-            }
+    fn parse_return_statement(&self, cursor: &mut Cursor) -> Result<ReturnStatementNode, ParseError> {
+        let position = cursor.position()?;
+        cursor.start(NodeKind::ReturnStatement);
+        cursor.advance()?;
+        let expression = if cursor.at(Symbol::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expression(cursor)?)
         };
-
-        match &tokens[index].token {
-            Token::Symbol(symbol) if symbol == "{" => {
-                let end_if_body = self.find_closing_bracket(tokens, index);
-                let (if_block, _) = self.parse_body(&tokens[..end_if_body], index + 1);
-                index = end_if_body + 1; // skip closing curly bracket
-                let (else_block, j) = if index < tokens.len() {
-                    match &tokens[index].token {
-                        Token::Keyword(keyword) if keyword == "else" => {
-                            index += 1;
-                            match &tokens[index].token {
-                                Token::Symbol(symbol) if symbol == "{" => {
-                                    let end_else_body = self.find_closing_bracket(tokens, index);
-                                    let (else_block, _) =
-                                        self.parse_body(&tokens[..end_else_body], index + 1);
-                                    (Some(else_block), end_else_body + 1)
-                                }
-                                _ => {
-                                    eprintln!(
-                                        "Parse Error in line {}: 'else' keyword in if statement must be followed by an open curly bracket: {}",
-                                        tokens[index].line.number, tokens[index].line.content
-                                    );
-                                    std::process::exit(1);
-                                }
-                            }
-                        }
-                        _ => (None, index),
-                    }
-                } else {
-                    (None, index)
-                };
-                index = j;
-                (
-                    IfStatementNode {
-                        condition,
-                        if_block,
-                        else_block,
-                    },
-                    index,
-                )
-            }
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: Expression in if statement must be followed by an open curly bracket: {}",
-                    tokens[index].line.number, tokens[index].line.content
-                );
-                std::process::exit(1);
-            }
-        }
+        cursor.expect(Symbol::Semicolon)?;
+        cursor.finish();
+        Ok(ReturnStatementNode {
+            expression,
+            position,
+        })
     }
 
-    fn parse_while_statement(
-        &self,
-        tokens: &[TokenWrapper],
-        index: usize,
-    ) -> (WhileStatementNode, usize) {
-        let mut index = index + 1;
-
-        let condition = match &tokens[index].token {
-            Token::Symbol(symbol) if symbol == "(" => {
-                let closing_bracket_index = self.find_closing_bracket(tokens, index);
-                let expression =
-                    self.parse_expression(tokens, index + 1, closing_bracket_index - 1);
-                index = closing_bracket_index + 1;
-                expression
-            }
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: while statement must be followed by an open bracket: {}",
-                    tokens[index].line.number, tokens[index].line.content
-                );
-                std::process::exit(1);
-            }
+    fn parse_if_statement(&self, cursor: &mut Cursor) -> Result<IfStatementNode, ParseError> {
+        let position = cursor.position()?;
+        cursor.start(NodeKind::IfStatement);
+        cursor.advance()?;
+        cursor.expect(Symbol::LeftParen)?;
+        let condition = self.parse_expression(cursor)?;
+        cursor.expect(Symbol::RightParen)?;
+        let if_block = self.parse_block(cursor)?;
+        let else_block = if cursor.at_keyword(Keyword::Else) {
+            cursor.advance()?;
+            Some(self.parse_block(cursor)?)
+        } else {
+            None
         };
+        cursor.finish();
+        Ok(IfStatementNode {
+            condition,
+            if_block,
+            else_block,
+            position,
+        })
+    }
 
-        match &tokens[index].token {
-            Token::Symbol(symbol) if symbol == "{" => {
-                let end_body = self.find_closing_bracket(tokens, index);
-                let (block, _) = self.parse_body(&tokens[..end_body], index + 1);
-                (WhileStatementNode { condition, block }, end_body + 1)
-            }
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: Expression in while statement must be followed by an open curly bracket: {}",
-                    tokens[index].line.number, tokens[index].line.content
-                );
-                std::process::exit(1);
-            }
-        }
+    fn parse_while_statement(&self, cursor: &mut Cursor) -> Result<WhileStatementNode, ParseError> {
+        let position = cursor.position()?;
+        cursor.start(NodeKind::WhileStatement);
+        cursor.advance()?;
+        cursor.expect(Symbol::LeftParen)?;
+        let condition = self.parse_expression(cursor)?;
+        cursor.expect(Symbol::RightParen)?;
+        let block = self.parse_block(cursor)?;
+        cursor.finish();
+        Ok(WhileStatementNode {
+            condition,
+            block,
+            position,
+        })
     }
 
     //------------------------------
     // EXPRESSIONS
     //------------------------------
 
-    fn parse_expression(
-        &self,
-        tokens: &[TokenWrapper],
-        start_index: usize,
-        end_index: usize,
-    ) -> ExpressionNode {
-        let mut expr_elements = Vec::new();
-        let mut index = start_index;
+    /// Parses `term (op term)*`
+    fn parse_expression(&self, cursor: &mut Cursor) -> Result<Expr, ParseError> {
+        cursor.start(NodeKind::Expression);
+        let expression = self.parse_binary(cursor, 0)?;
+        cursor.finish();
+        Ok(expression)
+    }
 
-        // 0 = nothing processed, 1 = token processed, 2 = operator processed
-        let mut status = 0;
-        while index < tokens.len() && index <= end_index {
-            match &tokens[index].token {
-                Token::Symbol(symbol) if symbol == ";" => {
-                    if status == 0 {
-                        eprintln!(
-                            "Parse Error in line {}: Expression must contain at least one term: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                    break;
-                }
-                Token::Symbol(symbol) if self.is_operator(symbol) && status == 1 => {
-                    // check status == 1 to avoid this case when there is a unary op in front of a term
-                    if status == 2 {
-                        eprintln!(
-                            "Parse Error in line {}: Operator cannot be followed by an operator in expression: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                    status = 2;
-                    expr_elements.push(ExpressionElement::Operator(symbol.to_string()));
-                    index += 1;
-                }
-                _ => {
-                    if status == 1 {
-                        eprintln!(
-                            "Parse Error in line {}: Term cannot be followed by a term in expression: {}",
-                            tokens[index].line.number, tokens[index].line.content
-                        );
-                        std::process::exit(1);
-                    }
-                    status = 1;
-                    let (term_node, j) = self.parse_term(tokens, index);
-                    expr_elements.push(ExpressionElement::Term(term_node));
-                    index = j;
-                }
+    /// Parses terms joined by the operators that bind at least as strongly as `min_precedence`.
+    /// Without [`operator_precedence`](Parser::operator_precedence) every operator binds
+    /// equally, so the right operand is always a single term.
+    fn parse_binary(&self, cursor: &mut Cursor, min_precedence: u8) -> Result<Expr, ParseError> {
+        let mut expression = self.parse_term(cursor)?;
+        while let Some(Token::Symbol(symbol)) = cursor.peek() {
+            let op = match BinaryOp::from_symbol(*symbol) {
+                Some(op) => op,
+                None => break,
+            };
+            let precedence = if self.operator_precedence { op.precedence() } else { 0 };
+            if precedence < min_precedence {
+                break;
             }
+            cursor.advance()?;
+            let rhs = self.parse_binary(cursor, precedence + 1)?;
+            expression = Expr::Binary {
+                lhs: Box::new(expression),
+                op,
+                rhs: Box::new(rhs),
+            };
         }
-        ExpressionNode {
-            elements: expr_elements,
-        }
+        Ok(expression)
     }
 
-    fn parse_term(&self, tokens: &[TokenWrapper], start_index: usize) -> (TermNode, usize) {
-        let index = start_index;
-        match &tokens[index].token {
-            Token::Constant(Constant::IntegerConstant(value)) => (
-                TermNode {
-                    elements: vec![TermElement::IntegerConstant(*value)],
-                },
-                index + 1,
-            ),
-            Token::Constant(Constant::StringConstant(value)) => (
-                TermNode {
-                    elements: vec![TermElement::StringConstant(value.to_string())],
-                },
-                index + 1,
-            ),
-            Token::Symbol(symbol) if symbol == "(" || symbol == "[" => {
-                let closing_bracket_index = self.find_closing_bracket(tokens, index);
-                let expression =
-                    self.parse_expression(tokens, index + 1, closing_bracket_index - 1);
-                let mut term_elems = Vec::new();
-                term_elems.push(TermElement::Symbol(symbol.to_string()));
-                term_elems.push(TermElement::Expression(expression));
-                let closing_bracket = if symbol == "(" {
-                    ")".to_string()
-                } else {
-                    "]".to_string()
-                };
-                term_elems.push(TermElement::Symbol(closing_bracket));
-                (
-                    TermNode {
-                        elements: term_elems,
-                    },
-                    closing_bracket_index + 1,
-                )
+    fn parse_term(&self, cursor: &mut Cursor) -> Result<Expr, ParseError> {
+        cursor.start(NodeKind::Term);
+        let token = cursor.advance()?;
+        let term = match &token.token {
+            Token::Constant(Constant::IntegerConstant(value)) => Expr::Const(Const::Integer(*value)),
+            Token::Constant(Constant::StringConstant(value)) => {
+                Expr::Const(Const::String(value.to_string()))
+            }
+            Token::Keyword(keyword) if keyword.is_constant() => Expr::Const(Const::Keyword(*keyword)),
+            Token::Symbol(Symbol::LeftParen) => {
+                // (expression)
+                let expression = self.parse_expression(cursor)?;
+                cursor.expect(Symbol::RightParen)?;
+                Expr::Paren(Box::new(expression))
+            }
+            Token::Symbol(symbol) if UnaryOp::from_symbol(*symbol).is_some() => {
+                // unary operator followed by term
+                Expr::Unary {
+                    op: UnaryOp::from_symbol(*symbol).unwrap(),
+                    operand: Box::new(self.parse_term(cursor)?),
+                }
             }
-            Token::Identifier(identifier) => {
-                let mut j = index + 1;
-                match &tokens[j].token {
-                    Token::Symbol(symbol) if symbol == "[" => {
-                        // foo[expression]
-                        let closing_bracket_index = self.find_closing_bracket(tokens, j);
-                        let expression =
-                            self.parse_expression(tokens, j + 1, closing_bracket_index - 1);
-                        let mut term_elems = Vec::new();
-                        term_elems.push(TermElement::Identifier(identifier.to_string()));
-                        term_elems.push(TermElement::Symbol("[".to_string()));
-                        term_elems.push(TermElement::Expression(expression));
-                        term_elems.push(TermElement::Symbol("]".to_string()));
-                        (
-                            TermNode {
-                                elements: term_elems,
-                            },
-                            closing_bracket_index + 1,
-                        )
-                    }
-                    Token::Symbol(symbol) if symbol == "." => {
-                        // foo.bar(expressionList)
-                        // Foo.bar(expressionList)
-                        j += 1;
-                        match &tokens[j].token {
-                            Token::Identifier(func_name) => {
-                                j += 1;
-                                match &tokens[j].token {
-                                    Token::Symbol(symbol) if symbol == "(" => {
-                                        let closing_bracket_index =
-                                            self.find_closing_bracket(tokens, j);
-                                        let expression_list = self.parse_expression_list(
-                                            tokens,
-                                            j + 1,
-                                            closing_bracket_index - 1,
-                                        );
-
-                                        let subroutine_call = SubroutineCallNode {
-                                            subroutine_name: func_name.to_string(),
-                                            expression_list,
-                                            caller: Some(identifier.to_string()),
-                                            semicolon: false,
-                                        };
-                                        (
-                                            TermNode {
-                                                elements: vec![TermElement::SubroutineCall(
-                                                    subroutine_call,
-                                                )],
-                                            },
-                                            closing_bracket_index + 1,
-                                        )
-                                    }
-                                    _ => {
-                                        eprintln!(
-                                            "Parse Error in line {}: Missing opening bracket after subroutine name: {:?}",
-                                            tokens[index].line.number, tokens[index].token
-                                        );
-                                        std::process::exit(1);
-                                    }
-                                }
-                            }
-                            _ => {
-                                eprintln!(
-                                    "Parse Error in line {}: Missing subroutine name: {:?}",
-                                    tokens[index].line.number, tokens[index].token
-                                );
-                                std::process::exit(1);
-                            }
-                        }
-                    }
-                    Token::Symbol(symbol) if symbol == "(" => {
-                        // bar(expression)
-                        let closing_bracket_index = self.find_closing_bracket(tokens, j);
-                        let expression_list =
-                            self.parse_expression_list(tokens, j + 1, closing_bracket_index - 1);
-
-                        let subroutine_call = SubroutineCallNode {
-                            subroutine_name: identifier.to_string(),
-                            expression_list,
-                            caller: None,
-                            semicolon: false,
-                        };
-                        (
-                            TermNode {
-                                elements: vec![TermElement::SubroutineCall(subroutine_call)],
-                            },
-                            closing_bracket_index + 1,
-                        )
-                    }
-                    _ => {
-                        // foo
-                        (
-                            TermNode {
-                                elements: vec![TermElement::Identifier(identifier.to_string())],
-                            },
-                            index + 1,
-                        )
+            // an identifier is the only place where the grammar needs a second token of lookahead
+            Token::Identifier(identifier) => match cursor.peek() {
+                Some(Token::Symbol(Symbol::LeftBracket)) => {
+                    // foo[expression]
+                    cursor.advance()?;
+                    let index = self.parse_expression(cursor)?;
+                    cursor.expect(Symbol::RightBracket)?;
+                    Expr::Index {
+                        name: identifier.to_string(),
+                        index: Box::new(index),
                     }
                 }
-            }
-            Token::Keyword(keyword) => (
-                TermNode {
-                    elements: vec![TermElement::KeywordConstant(keyword.to_string())],
-                },
-                index + 1,
-            ),
-            Token::Symbol(symbol)
-            if (symbol == "-" || symbol == "~")
-                && index > 0
-                && self.token_is_symbol(&tokens[index].token) =>
-                {
-                    // unary operator followed by term
-                    let (term_node, j) = self.parse_term(tokens, index + 1);
-                    let op = TermElement::Symbol(symbol.to_string());
-                    (
-                        TermNode {
-                            elements: vec![op, TermElement::Term(term_node)],
-                        },
-                        j + 1,
-                    )
+                Some(Token::Symbol(Symbol::LeftParen | Symbol::Dot)) => {
+                    // bar(expressionList), foo.bar(expressionList), Foo.bar(expressionList)
+                    Expr::Call(self.parse_subroutine_call(cursor, identifier)?)
                 }
-            _ => {
-                eprintln!(
-                    "Parse Error in line {}: Unexpected token found in term: {:?}",
-                    tokens[index].line.number, tokens[index].token
-                );
-                std::process::exit(1);
-            }
-        }
-    }
-
-    fn parse_expression_list(
-        &self,
-        tokens: &[TokenWrapper],
-        start_index: usize,
-        end_index: usize,
-    ) -> Vec<ExpressionNode> {
-        let mut index = start_index;
-        let mut expression_nodes = Vec::new();
-        while index <= end_index {
-            let expr_end_index = match self.next_comma_in_expression_list(tokens, index, end_index) {
-                Ok(comma_index) => comma_index,
-                Err(_) => end_index + 1,
-            };
-            let expression = self.parse_expression(tokens, index, expr_end_index - 1);
-            expression_nodes.push(expression);
-            index = expr_end_index + 1;
-        }
-        expression_nodes
-    }
-
-    //------------------------------
-    // HELPER FUNCTIONS
-    //------------------------------
-
-    fn is_statement(&self, keyword: &str) -> bool {
-        keyword == "let"
-            || keyword == "if"
-            || keyword == "else"
-            || keyword == "while"
-            || keyword == "do"
-            || keyword == "return"
+                // foo
+                _ => Expr::Var(identifier.to_string()),
+            },
+            _ => return Err(cursor.error_at(token, "Unexpected token found in term")),
+        };
+        cursor.finish();
+        Ok(term)
     }
 
-    fn find_closing_bracket(&self, tokens: &[TokenWrapper], start_index: usize) -> usize {
-        let (opening_bracket, closing_bracket) = match &tokens[start_index].token {
-            Token::Symbol(symbol) if symbol == "{" => ("{", "}"),
-            Token::Symbol(symbol) if symbol == "(" => ("(", ")"),
-            Token::Symbol(symbol) if symbol == "[" => ("[", "]"),
-            _ => {
-                eprintln!(
-                    "Parse Error: Start token for closing bracket search must be an open bracket."
-                );
-                std::process::exit(1);
-            }
+    /// Parses the rest of a subroutine call after its first identifier:
+    /// `(. subroutineName)? ( expressionList )`
+    fn parse_subroutine_call(&self, cursor: &mut Cursor, identifier: &str) -> Result<SubroutineCall, ParseError> {
+        let (caller, subroutine_name) = if cursor.at(Symbol::Dot) {
+            cursor.advance()?;
+            (Some(identifier.to_string()), cursor.expect_identifier()?)
+        } else {
+            (None, identifier)
         };
-
-        let mut stack = Vec::new();
-        stack.push(opening_bracket);
-
-        for i in start_index + 1..tokens.len() {
-            match &tokens[i].token {
-                Token::Symbol(symbol) => {
-                    if *symbol == opening_bracket {
-                        stack.push(opening_bracket);
-                    } else if *symbol == closing_bracket {
-                        if let Some(bracket) = stack.pop() {
-                            if bracket != opening_bracket {
-                                eprintln!(
-                                    "Parse Error: Cannot find opening bracket for bracket in line {}: {}.",
-                                    tokens[i].line.number,
-                                    tokens[i].line.content
-                                );
-                                std::process::exit(1);
-                            } else {
-                                if stack.is_empty() {
-                                    return i;
-                                }
-                            }
-                        } else {
-                            eprintln!(
-                                "Parse Error: Cannot find closing bracket for bracket in line {}: {}.",
-                                tokens[start_index].line.number,
-                                tokens[start_index].line.content
-                            );
-                            std::process::exit(1);
-                        }
-                    }
-                }
-                _ => (),
-            }
-        }
-        // dummy return
-        0
+        cursor.expect(Symbol::LeftParen)?;
+        let arguments = self.parse_expression_list(cursor)?;
+        cursor.expect(Symbol::RightParen)?;
+        Ok(SubroutineCall {
+            caller,
+            subroutine_name: subroutine_name.to_string(),
+            arguments,
+        })
     }
 
-    fn find_symbol(
-        &self,
-        tokens: &[TokenWrapper],
-        target_symbol: &str,
-        start_index: usize,
-        end_index: usize,
-    ) -> Result<usize, ParseError> {
-        let mut index = start_index;
-        loop {
-            if index == end_index {
-                return Err(ParseError);
-            }
-            if let Token::Symbol(symbol) = &tokens[index].token {
-                if symbol == target_symbol {
-                    return Ok(index);
-                }
+    fn parse_expression_list(&self, cursor: &mut Cursor) -> Result<Vec<Expr>, ParseError> {
+        let mut expressions = Vec::new();
+        cursor.start(NodeKind::ExpressionList);
+        if !cursor.at(Symbol::RightParen) {
+            expressions.push(self.parse_expression(cursor)?);
+            while cursor.at(Symbol::Comma) {
+                cursor.advance()?;
+                expressions.push(self.parse_expression(cursor)?);
             }
-            index += 1;
         }
+        cursor.finish();
+        Ok(expressions)
     }
 
-    fn next_comma_in_expression_list(
-        &self,
-        tokens: &[TokenWrapper],
-        start_index: usize,
-        end_index: usize,
-    ) -> Result<usize, ParseError> {
-        let mut index = start_index;
-        let mut bracket_count = 0;
-        loop {
-            if index == end_index {
-                return Err(ParseError);
-            }
-            if let Token::Symbol(symbol) = &tokens[index].token {
-                if symbol == "(" {
-                    bracket_count += 1;
-                }
-                if symbol == ")" {
-                    bracket_count -= 1;
-                }
-                if symbol == "," && bracket_count == 0 {
-                    return Ok(index);
-                }
-            }
-            index += 1;
-        }
-    }
+    //------------------------------
+    // HELPER FUNCTIONS
+    //------------------------------
 
-    fn token_is_symbol(&self, token: &Token) -> bool {
-        match token {
-            Token::Symbol(_) => true,
-            _ => false,
-        }
+    fn is_subroutine(&self, keyword: &Keyword) -> bool {
+        matches!(
+            keyword,
+            Keyword::Constructor | Keyword::Function | Keyword::Method
+        )
     }
 
-    fn is_operator(&self, symbol: &str) -> bool {
-        symbol == "+"
-            || symbol == "-"
-            || symbol == "*"
-            || symbol == "/"
-            || symbol == "&"
-            || symbol == "|"
-            || symbol == "<"
-            || symbol == ">"
-            || symbol == "="
+    /// Consumes `int`, `char`, `boolean` or a class name.
+    fn parse_var_type(&self, cursor: &mut Cursor, message: &str) -> Result<VarType, ParseError> {
+        let var_type = match cursor.peek() {
+            Some(Token::Keyword(Keyword::Int)) => VarType::Int,
+            Some(Token::Keyword(Keyword::Char)) => VarType::Char,
+            Some(Token::Keyword(Keyword::Boolean)) => VarType::Boolean,
+            Some(Token::Identifier(class_name)) => VarType::ClassName(class_name.clone()),
+            _ => return Err(cursor.error(message)),
+        };
+        cursor.advance()?;
+        Ok(var_type)
     }
 }
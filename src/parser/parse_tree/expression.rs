@@ -1,174 +1,135 @@
 //! Represents an expression in the parse tree.
-use std::fmt;
-use std::fmt::Formatter;
+use crate::tokenizer::tokens::{Keyword, Symbol};
 
 /// Represents an expression in the parse tree.
 /// Grammar rule: term (op term)*
+///
+/// Jack has no operator precedence, `a + b * c` is evaluated from left to right.
+/// A chain of binary operators is therefore a left-leaning tree of [`Expr::Binary`]
+/// whose right operands are terms.
 #[derive(Debug)]
-pub struct ExpressionNode {
-    pub elements: Vec<ExpressionElement>,
+pub enum Expr {
+    /// integerConstant | stringConstant | keywordConstant
+    Const(Const),
+    /// varName
+    Var(String),
+    /// varName `[` expression `]`
+    Index { name: String, index: Box<Expr> },
+    /// subroutineCall
+    Call(SubroutineCall),
+    /// unaryOp term
+    Unary { op: UnaryOp, operand: Box<Expr> },
+    /// expression op term
+    Binary {
+        lhs: Box<Expr>,
+        op: BinaryOp,
+        rhs: Box<Expr>,
+    },
+    /// `(` expression `)`. Has no effect on the evaluation, but is kept
+    /// so that the XML output can reproduce the parentheses.
+    Paren(Box<Expr>),
 }
 
-impl fmt::Display for ExpressionNode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-
-        let mut elements = String::new();
-        if self.elements.is_empty() {
-            elements.push_str("<expression></expression>");
-        } else {
-            elements.push_str("<expression>\n");
-            for element in self.elements.iter() {
-                elements.push_str(&format!("{}\n", element));
-            }
-            elements.push_str("</expression>");
-        }
-        write!(f, "{}", elements)
-    }
+/// Represents a constant in the parse tree.
+#[derive(Debug)]
+pub enum Const {
+    Integer(u32),
+    String(String),
+    /// `true`, `false`, `null` or `this`
+    Keyword(Keyword),
 }
 
-/// Represents an expression element. An expression element is either a term or an operator.
+/// Represents a subroutine call in the parse tree.
+/// Grammar rule: subroutineName `(` expressionList `)` |
+/// (className | varName) `.` subroutineName `(` expressionList `)`
 #[derive(Debug)]
-pub enum ExpressionElement {
-    Term(TermNode),
-    Operator(String),
+pub struct SubroutineCall {
+    pub caller: Option<String>, // className or varName
+    pub subroutine_name: String,
+    pub arguments: Vec<Expr>,
 }
 
-impl fmt::Display for ExpressionElement {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            ExpressionElement::Term(term) => {
-                write!(f, "{}", term)
-            }
-            ExpressionElement::Operator(op) => {
-                // xml special characters
-                let op_str = match op.as_str() {
-                    "<" => "&lt;",
-                    ">" => "&gt;",
-                    "&" => "&amp;",
-                    "\"" => "&quot",
-                    "'" => "&apos;",
-                    _ => op,
-                };
-                write!(f, "<symbol> {} </symbol>", op_str)
-            }
-        }
-    }
+/// The binary operators: `+`, `-`, `*`, `/`, `&`, `|`, `<`, `>`, `=`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    And,
+    Or,
+    Lt,
+    Gt,
+    Eq,
 }
 
-/// Represents a term in the parse tree. A term node can contain
-/// arbitrary many terms.
-#[derive(Debug)]
-pub struct TermNode {
-    pub elements: Vec<TermElement>,
-}
+impl BinaryOp {
+    /// Returns the operator for the given symbol, or `None` if the symbol is not a binary operator.
+    pub fn from_symbol(symbol: Symbol) -> Option<BinaryOp> {
+        let op = match symbol {
+            Symbol::Plus => BinaryOp::Add,
+            Symbol::Minus => BinaryOp::Sub,
+            Symbol::Asterisk => BinaryOp::Mul,
+            Symbol::Slash => BinaryOp::Div,
+            Symbol::Ampersand => BinaryOp::And,
+            Symbol::Pipe => BinaryOp::Or,
+            Symbol::LessThan => BinaryOp::Lt,
+            Symbol::GreaterThan => BinaryOp::Gt,
+            Symbol::Equal => BinaryOp::Eq,
+            _ => return None,
+        };
+        Some(op)
+    }
 
-impl fmt::Display for TermNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        if self.elements.is_empty() {
-            write!(f, "<term>\n</term>")
-        } else {
-            let mut term_elems = String::new();
-            for i in 0..self.elements.len() - 1 {
-                term_elems.push_str(&format!("{}\n", self.elements[i]));
-            }
-            term_elems.push_str(&format!("{}", self.elements[self.elements.len() - 1]));
-            write!(f, "<term>\n{}\n</term>", term_elems)
+    /// Returns how strongly the operator binds with the
+    /// [`operator_precedence`](crate::parser::Parser::operator_precedence) extension,
+    /// from `|` (1) over `&`, the comparisons and `+` `-` to `*` `/` (5).
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Eq => 3,
+            BinaryOp::Add | BinaryOp::Sub => 4,
+            BinaryOp::Mul | BinaryOp::Div => 5,
         }
     }
-}
-
-/// Represents a term in the parse tree.
-/// Grammar rule: integerConstant | stringConstant | keywordConstant | varName |
-/// varName `[` expression `]` | subroutineCall | `(` expression `)` | unaryOp term
-#[derive(Debug)]
-pub enum TermElement {
-    Identifier(String),
-    Symbol(String),
-    IntegerConstant(u32),
-    KeywordConstant(String),
-    StringConstant(String),
-    Expression(ExpressionNode),
-    Term(TermNode),
-    SubroutineCall(SubroutineCallNode),
-}
 
-impl fmt::Display for TermElement {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    pub fn symbol(&self) -> Symbol {
         match self {
-            TermElement::Identifier(s) => {
-                write!(f, "<identifier> {} </identifier>", s)
-            }
-            TermElement::Symbol(s) => {
-                write!(f, "<symbol> {} </symbol>", s)
-            }
-            TermElement::IntegerConstant(n) => {
-                write!(f, "<integerConstant> {} </integerConstant>", n)
-            }
-            TermElement::StringConstant(n) => {
-                write!(f, "<stringConstant> {} </stringConstant>", n)
-            }
-            TermElement::KeywordConstant(keyword) => {
-                write!(f, "<keyword> {} </keyword>", keyword)
-            }
-            TermElement::Expression(expression) => {
-                write!(f, "{}", expression)
-            }
-            TermElement::Term(term) => {
-                write!(f, "{}", term)
-            }
-            TermElement::SubroutineCall(subroutine_call) => {
-                write!(f, "{}", subroutine_call)
-            }
+            BinaryOp::Add => Symbol::Plus,
+            BinaryOp::Sub => Symbol::Minus,
+            BinaryOp::Mul => Symbol::Asterisk,
+            BinaryOp::Div => Symbol::Slash,
+            BinaryOp::And => Symbol::Ampersand,
+            BinaryOp::Or => Symbol::Pipe,
+            BinaryOp::Lt => Symbol::LessThan,
+            BinaryOp::Gt => Symbol::GreaterThan,
+            BinaryOp::Eq => Symbol::Equal,
         }
     }
 }
 
-/// Represents a term in the parse tree.
-/// Grammar rule: integerConstant | stringConstant | keywordConstant | varName |
-/// varName `[` expression `]` | subroutineCall | `(` expression `)` | unaryOp term
-#[derive(Debug)]
-pub struct SubroutineCallNode {
-    pub subroutine_name: String,
-    pub expression_list: Vec<ExpressionNode>,
-    pub caller: Option<String>, // className or varName
-    pub semicolon: bool,
+/// The unary operators: `-` (negation) and `~` (bitwise not).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
 }
 
-impl fmt::Display for SubroutineCallNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let caller = match &self.caller {
-            Some(name) => format!(
-                "<identifier> {} </identifier>\n<symbol> . </symbol>\n",
-                name
-            ),
-            None => "".to_string(),
-        };
-        let sub_name = format!("<identifier> {} </identifier>", self.subroutine_name);
-        let mut expr_list = String::new();
-        if self.expression_list.is_empty() {
-            expr_list.push_str(
-                "<symbol> ( </symbol>\n<expressionList>\n</expressionList>\n<symbol> ) </symbol>",
-            );
-        } else {
-            expr_list.push_str("<symbol> ( </symbol>\n<expressionList>\n");
-            for i in 0..self.expression_list.len() - 1 {
-                expr_list.push_str(&format!(
-                    "{}\n<symbol> , </symbol>\n",
-                    self.expression_list[i]
-                ));
-            }
-            expr_list.push_str(&format!(
-                "{}\n",
-                self.expression_list[self.expression_list.len() - 1]
-            ));
-            expr_list.push_str("</expressionList>\n<symbol> ) </symbol>");
+impl UnaryOp {
+    /// Returns the operator for the given symbol, or `None` if the symbol is not a unary operator.
+    pub fn from_symbol(symbol: Symbol) -> Option<UnaryOp> {
+        match symbol {
+            Symbol::Minus => Some(UnaryOp::Neg),
+            Symbol::Tilde => Some(UnaryOp::Not),
+            _ => None,
         }
-        let semicolon = if self.semicolon { "\n<symbol> ; </symbol>" } else { "" };
-        write!(
-            f,
-            "{}{}\n{}{}",
-            caller, sub_name, expr_list, semicolon
-        )
     }
-}
 
+    pub fn symbol(&self) -> Symbol {
+        match self {
+            UnaryOp::Neg => Symbol::Minus,
+            UnaryOp::Not => Symbol::Tilde,
+        }
+    }
+}
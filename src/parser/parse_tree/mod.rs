@@ -4,11 +4,29 @@ pub mod subroutine;
 pub mod statement;
 pub mod expression;
 pub mod var;
+pub mod visit;
+
+use std::sync::Arc;
 
 use super::parse_tree::class::ClassNode;
+use crate::io::line::Line;
 
 /// Defines the parse tree.
 pub struct ParseTree {
-    pub class_node: ClassNode
+    pub class_node: ClassNode,
+    /// The path of the Jack file, as given to [`Parser::parse`](crate::parser::Parser::parse).
+    pub file: String,
+    /// The lines of the Jack file that contain tokens, in ascending order.
+    pub lines: Vec<Arc<Line>>,
+}
+
+impl ParseTree {
+    /// Returns the (trimmed) content of the line with the given number.
+    pub fn source_line(&self, number: usize) -> Option<&str> {
+        self.lines
+            .binary_search_by_key(&number, |line| line.number)
+            .ok()
+            .map(|index| self.lines[index].content.as_str())
+    }
 }
 
@@ -1,8 +1,6 @@
 //! Represents the statements in the parse tree.
-use std::fmt;
-use std::fmt::Formatter;
-
-use super::expression::{ExpressionNode, SubroutineCallNode};
+use super::expression::{Expr, SubroutineCall};
+use crate::tokenizer::tokens::Position;
 
 /// Represents a statement in the parse tree.
 #[derive(Debug)]
@@ -14,24 +12,15 @@ pub enum Statement {
     Return(ReturnStatementNode),
 }
 
-impl fmt::Display for Statement {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+impl Statement {
+    /// Returns the position of the keyword that starts the statement.
+    pub fn position(&self) -> Position {
         match self {
-            Statement::Let(statement) => {
-                write!(f, "{}", statement)
-            }
-            Statement::If(statement) => {
-                write!(f, "{}", statement)
-            }
-            Statement::While(statement) => {
-                write!(f, "{}", statement)
-            }
-            Statement::Do(statement) => {
-                write!(f, "{}", statement)
-            }
-            Statement::Return(statement) => {
-                write!(f, "{}", statement)
-            }
+            Statement::Let(statement) => statement.position,
+            Statement::If(statement) => statement.position,
+            Statement::While(statement) => statement.position,
+            Statement::Do(statement) => statement.position,
+            Statement::Return(statement) => statement.position,
         }
     }
 }
@@ -40,32 +29,9 @@ impl fmt::Display for Statement {
 /// Grammar rule: `while` `(` expression `)` `{` statement* `}`
 #[derive(Debug)]
 pub struct WhileStatementNode {
-    pub condition: ExpressionNode,
+    pub condition: Expr,
     pub block: Vec<Statement>,
-}
-
-impl fmt::Display for WhileStatementNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let keyword = "<keyword> while </keyword>";
-        let open_bracket = "<symbol> ( </symbol>";
-        let closing_bracket = "<symbol> ) </symbol>";
-        let mut block = String::from("<symbol> { </symbol>\n<statements>\n");
-        for statement in self.block.iter() {
-            block.push_str(&format!("{}", statement));
-        }
-        block.push_str("</statements>\n<symbol> } </symbol>\n");
-        write!(
-            f,
-            "<whileStatement>\n\
-            {}\n\
-            {}\n\
-            {}\n\
-            {}\n\
-            {}\
-            </whileStatement>\n",
-            keyword, open_bracket, self.condition, closing_bracket, block
-        )
-    }
+    pub position: Position,
 }
 
 /// Represents an if statement in the parse tree.
@@ -73,123 +39,42 @@ impl fmt::Display for WhileStatementNode {
 /// (`else` `{` statement* `}`)?
 #[derive(Debug)]
 pub struct IfStatementNode {
-    pub condition: ExpressionNode,
+    pub condition: Expr,
     pub if_block: Vec<Statement>,
     pub else_block: Option<Vec<Statement>>,
-}
-
-impl fmt::Display for IfStatementNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let keyword = "<keyword> if </keyword>";
-        let open_bracket = "<symbol> ( </symbol>";
-        let closing_bracket = "<symbol> ) </symbol>";
-        let mut if_block = String::from("<symbol> { </symbol>\n<statements>\n");
-        for statement in self.if_block.iter() {
-            if_block.push_str(&format!("{}", statement));
-        }
-        if_block.push_str("</statements>\n<symbol> } </symbol>\n");
-        let else_block = match &self.else_block {
-            Some(statements) => {
-                let mut else_block =
-                    String::from("<keyword> else </keyword>\n<symbol> { </symbol>\n<statements>\n");
-                for statement in statements.iter() {
-                    else_block.push_str(&format!("{}", statement));
-                }
-                else_block.push_str("</statements>\n<symbol> } </symbol>\n");
-                else_block
-            }
-            None => "".to_string(),
-        };
-        write!(
-            f,
-            "<ifStatement>\n\
-            {}\n\
-            {}\n\
-            {}\n\
-            {}\n\
-            {}\
-            {}\
-            </ifStatement>\n",
-            keyword, open_bracket, self.condition, closing_bracket, if_block, else_block
-        )
-    }
+    pub position: Position,
 }
 
 /// Represents a return statement in the parse tree.
 /// Grammar rule: `return` expression? `;`
 #[derive(Debug)]
 pub struct ReturnStatementNode {
-    pub expression: Option<ExpressionNode>,
-}
-
-impl fmt::Display for ReturnStatementNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match &self.expression {
-            Some(expr) => {
-                write!(f, "<returnStatement>\n<keyword> return </keyword>\n{}\n<symbol> ; </symbol>\n</returnStatement>\n", expr)
-            }
-            None => {
-                write!(f, "<returnStatement>\n<keyword> return </keyword>\n<symbol> ; </symbol>\n</returnStatement>\n")
-            }
-        }
-    }
+    pub expression: Option<Expr>,
+    pub position: Position,
 }
 
 /// Represents a let statement in the parse tree.
 /// Grammar rule: `let` varName (`[` expression `]`)? `=` expression `;`
 #[derive(Debug)]
 pub struct LetStatementNode {
-    pub var_name: String,
-    pub lhs_expression: Option<ExpressionNode>,
-    pub rhs_expression: ExpressionNode,
+    pub target: LetTarget,
+    pub value: Expr,
+    pub position: Position,
 }
 
-impl fmt::Display for LetStatementNode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let keyword = "<keyword> let </keyword>";
-        let identifier = format!("<identifier> {} </identifier>", self.var_name);
-        let lhs_expression = match &self.lhs_expression {
-            Some(expression) => {
-                format!(
-                    "<symbol> [ </symbol>\n{}\n<symbol> ] </symbol>\n",
-                    expression
-                )
-            }
-            None => "".to_string(),
-        };
-        let equal_sign = "<symbol> = </symbol>";
-        let semicolon = "<symbol> ; </symbol>";
-        write!(
-            f,
-            "<letStatement>\n\
-            {}\n\
-            {}\n\
-            {}\
-            {}\n\
-            {}\n\
-            {}\n\
-            </letStatement>\n",
-            keyword, identifier, lhs_expression, equal_sign, self.rhs_expression, semicolon
-        )
-    }
+/// The left-hand side of a let statement.
+#[derive(Debug)]
+pub enum LetTarget {
+    /// varName
+    Var(String),
+    /// varName `[` expression `]`
+    Index { name: String, index: Expr },
 }
 
 /// Represents a do statement in the parse tree.
 /// Grammar rule: `do` subroutineCall `;`
 #[derive(Debug)]
 pub struct DoStatementNode {
-    pub subroutine_call: SubroutineCallNode,
-}
-
-impl fmt::Display for DoStatementNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "<doStatement>\n\
-            <keyword> do </keyword>\n\
-            {}\n\
-            </doStatement>\n",
-            self.subroutine_call
-        )
-    }
+    pub subroutine_call: SubroutineCall,
+    pub position: Position,
 }
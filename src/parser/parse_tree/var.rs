@@ -3,43 +3,16 @@ use std::fmt;
 use std::fmt::Formatter;
 use std::convert;
 
+use crate::tokenizer::tokens::{Keyword, Position};
+
 /// Represents a variable in the parse tree.
 #[derive(Debug)]
 pub struct VarNode {
     pub var_names: Vec<String>,
     pub var_kind: VarKind,
     pub var_type: VarType,
-    pub class_var: bool,
-}
-
-impl fmt::Display for VarNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let class_var = if self.class_var {
-            "classVarDec"
-        } else {
-            "varDec"
-        };
-        let var_type = match &self.var_type {
-            VarType::ClassName(name) => format!("<identifier> {} </identifier>", name),
-            _ => format!("<keyword> {} </keyword>", self.var_type),
-        };
-
-        let mut var_names = String::new();
-        for i in 0..self.var_names.len() - 1 {
-            var_names.push_str(&format!("<identifier> {} </identifier>\n<symbol> , </symbol>\n", self.var_names[i]));
-        }
-        var_names.push_str(&format!("<identifier> {} </identifier>\n", self.var_names[self.var_names.len() - 1]));
-        write!(
-            f,
-            "<{}>\n\
-            <keyword> {} </keyword>\n\
-            {}\n\
-            {}\
-            <symbol> ; </symbol>\n\
-            </{}>",
-            class_var, self.var_kind, var_type, var_names, class_var
-        )
-    }
+    /// The position of the `var` keyword.
+    pub position: Position,
 }
 
 /// The variable kind: `static`, `field`, `argument`, `variable` (local).
@@ -75,15 +48,13 @@ impl convert::From<&VarKind> for String {
 }
 
 impl VarKind {
-    pub fn get(var_kind: &str) -> VarKind {
+    /// Returns the var kind of the keyword, or `None` if it does not declare a variable.
+    pub fn get(var_kind: &Keyword) -> Option<VarKind> {
         match var_kind {
-            "static" => VarKind::Static,
-            "field" => VarKind::Field,
-            "var" => VarKind::Var,
-            _ => {
-                eprintln!("Parse Error: Unknown var kind: {}", var_kind);
-                std::process::exit(1);
-            }
+            Keyword::Static => Some(VarKind::Static),
+            Keyword::Field => Some(VarKind::Field),
+            Keyword::Var => Some(VarKind::Var),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,309 @@
+//! Traversal of the parse tree.
+//!
+//! [`Visitor`] walks over a parse tree by reference, [`VisitorMut`] by mutable reference.
+//! Every `visit_*` method defaults to the matching `walk_*` function, which visits the
+//! children of the node. An implementation only overrides the methods for the nodes it
+//! is interested in, and calls the `walk_*` function if it wants to continue below them.
+use super::class::{ClassNode, ClassVarNode};
+use super::expression::{Expr, SubroutineCall};
+use super::statement::{
+    DoStatementNode, IfStatementNode, LetStatementNode, LetTarget, ReturnStatementNode, Statement,
+    WhileStatementNode,
+};
+use super::subroutine::{ParameterNode, SubroutineNode};
+use super::var::VarNode;
+
+/// Walks over a parse tree by reference.
+pub trait Visitor {
+    fn visit_class(&mut self, class: &ClassNode) {
+        walk_class(self, class);
+    }
+
+    fn visit_class_var(&mut self, _class_var: &ClassVarNode) {}
+
+    fn visit_subroutine(&mut self, subroutine: &SubroutineNode) {
+        walk_subroutine(self, subroutine);
+    }
+
+    fn visit_parameter(&mut self, _parameter: &ParameterNode) {}
+
+    fn visit_var(&mut self, _var: &VarNode) {}
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_let_statement(&mut self, statement: &LetStatementNode) {
+        walk_let_statement(self, statement);
+    }
+
+    fn visit_if_statement(&mut self, statement: &IfStatementNode) {
+        walk_if_statement(self, statement);
+    }
+
+    fn visit_while_statement(&mut self, statement: &WhileStatementNode) {
+        walk_while_statement(self, statement);
+    }
+
+    fn visit_do_statement(&mut self, statement: &DoStatementNode) {
+        walk_do_statement(self, statement);
+    }
+
+    fn visit_return_statement(&mut self, statement: &ReturnStatementNode) {
+        walk_return_statement(self, statement);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_subroutine_call(&mut self, subroutine_call: &SubroutineCall) {
+        walk_subroutine_call(self, subroutine_call);
+    }
+}
+
+pub fn walk_class<V: Visitor + ?Sized>(visitor: &mut V, class: &ClassNode) {
+    for class_var in class.variables.iter() {
+        visitor.visit_class_var(class_var);
+    }
+    for subroutine in class.subroutines.iter() {
+        visitor.visit_subroutine(subroutine);
+    }
+}
+
+pub fn walk_subroutine<V: Visitor + ?Sized>(visitor: &mut V, subroutine: &SubroutineNode) {
+    for parameter in subroutine.parameter_list.parameters.iter() {
+        visitor.visit_parameter(parameter);
+    }
+    for var in subroutine.body.variables.iter() {
+        visitor.visit_var(var);
+    }
+    walk_statements(visitor, &subroutine.body.statements);
+}
+
+fn walk_statements<V: Visitor + ?Sized>(visitor: &mut V, statements: &[Statement]) {
+    for statement in statements.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Let(statement) => visitor.visit_let_statement(statement),
+        Statement::If(statement) => visitor.visit_if_statement(statement),
+        Statement::While(statement) => visitor.visit_while_statement(statement),
+        Statement::Do(statement) => visitor.visit_do_statement(statement),
+        Statement::Return(statement) => visitor.visit_return_statement(statement),
+    }
+}
+
+pub fn walk_let_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &LetStatementNode) {
+    if let LetTarget::Index { index, .. } = &statement.target {
+        visitor.visit_expr(index);
+    }
+    visitor.visit_expr(&statement.value);
+}
+
+pub fn walk_if_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &IfStatementNode) {
+    visitor.visit_expr(&statement.condition);
+    walk_statements(visitor, &statement.if_block);
+    if let Some(else_block) = &statement.else_block {
+        walk_statements(visitor, else_block);
+    }
+}
+
+pub fn walk_while_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &WhileStatementNode) {
+    visitor.visit_expr(&statement.condition);
+    walk_statements(visitor, &statement.block);
+}
+
+pub fn walk_do_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &DoStatementNode) {
+    visitor.visit_subroutine_call(&statement.subroutine_call);
+}
+
+pub fn walk_return_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    statement: &ReturnStatementNode,
+) {
+    if let Some(expression) = &statement.expression {
+        visitor.visit_expr(expression);
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Const(_) | Expr::Var(_) => (),
+        Expr::Index { index, .. } => visitor.visit_expr(index),
+        Expr::Call(subroutine_call) => visitor.visit_subroutine_call(subroutine_call),
+        Expr::Unary { operand, .. } => visitor.visit_expr(operand),
+        Expr::Binary { lhs, rhs, .. } => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+        Expr::Paren(expr) => visitor.visit_expr(expr),
+    }
+}
+
+pub fn walk_subroutine_call<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    subroutine_call: &SubroutineCall,
+) {
+    for argument in subroutine_call.arguments.iter() {
+        visitor.visit_expr(argument);
+    }
+}
+
+/// Walks over a parse tree by mutable reference, so that nodes can be rewritten in place.
+pub trait VisitorMut {
+    fn visit_class_mut(&mut self, class: &mut ClassNode) {
+        walk_class_mut(self, class);
+    }
+
+    fn visit_class_var_mut(&mut self, _class_var: &mut ClassVarNode) {}
+
+    fn visit_subroutine_mut(&mut self, subroutine: &mut SubroutineNode) {
+        walk_subroutine_mut(self, subroutine);
+    }
+
+    fn visit_parameter_mut(&mut self, _parameter: &mut ParameterNode) {}
+
+    fn visit_var_mut(&mut self, _var: &mut VarNode) {}
+
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement);
+    }
+
+    fn visit_let_statement_mut(&mut self, statement: &mut LetStatementNode) {
+        walk_let_statement_mut(self, statement);
+    }
+
+    fn visit_if_statement_mut(&mut self, statement: &mut IfStatementNode) {
+        walk_if_statement_mut(self, statement);
+    }
+
+    fn visit_while_statement_mut(&mut self, statement: &mut WhileStatementNode) {
+        walk_while_statement_mut(self, statement);
+    }
+
+    fn visit_do_statement_mut(&mut self, statement: &mut DoStatementNode) {
+        walk_do_statement_mut(self, statement);
+    }
+
+    fn visit_return_statement_mut(&mut self, statement: &mut ReturnStatementNode) {
+        walk_return_statement_mut(self, statement);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_subroutine_call_mut(&mut self, subroutine_call: &mut SubroutineCall) {
+        walk_subroutine_call_mut(self, subroutine_call);
+    }
+}
+
+pub fn walk_class_mut<V: VisitorMut + ?Sized>(visitor: &mut V, class: &mut ClassNode) {
+    for class_var in class.variables.iter_mut() {
+        visitor.visit_class_var_mut(class_var);
+    }
+    for subroutine in class.subroutines.iter_mut() {
+        visitor.visit_subroutine_mut(subroutine);
+    }
+}
+
+pub fn walk_subroutine_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    subroutine: &mut SubroutineNode,
+) {
+    for parameter in subroutine.parameter_list.parameters.iter_mut() {
+        visitor.visit_parameter_mut(parameter);
+    }
+    for var in subroutine.body.variables.iter_mut() {
+        visitor.visit_var_mut(var);
+    }
+    walk_statements_mut(visitor, &mut subroutine.body.statements);
+}
+
+fn walk_statements_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statements: &mut [Statement]) {
+    for statement in statements.iter_mut() {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::Let(statement) => visitor.visit_let_statement_mut(statement),
+        Statement::If(statement) => visitor.visit_if_statement_mut(statement),
+        Statement::While(statement) => visitor.visit_while_statement_mut(statement),
+        Statement::Do(statement) => visitor.visit_do_statement_mut(statement),
+        Statement::Return(statement) => visitor.visit_return_statement_mut(statement),
+    }
+}
+
+pub fn walk_let_statement_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    statement: &mut LetStatementNode,
+) {
+    if let LetTarget::Index { index, .. } = &mut statement.target {
+        visitor.visit_expr_mut(index);
+    }
+    visitor.visit_expr_mut(&mut statement.value);
+}
+
+pub fn walk_if_statement_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    statement: &mut IfStatementNode,
+) {
+    visitor.visit_expr_mut(&mut statement.condition);
+    walk_statements_mut(visitor, &mut statement.if_block);
+    if let Some(else_block) = &mut statement.else_block {
+        walk_statements_mut(visitor, else_block);
+    }
+}
+
+pub fn walk_while_statement_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    statement: &mut WhileStatementNode,
+) {
+    visitor.visit_expr_mut(&mut statement.condition);
+    walk_statements_mut(visitor, &mut statement.block);
+}
+
+pub fn walk_do_statement_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    statement: &mut DoStatementNode,
+) {
+    visitor.visit_subroutine_call_mut(&mut statement.subroutine_call);
+}
+
+pub fn walk_return_statement_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    statement: &mut ReturnStatementNode,
+) {
+    if let Some(expression) = &mut statement.expression {
+        visitor.visit_expr_mut(expression);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Const(_) | Expr::Var(_) => (),
+        Expr::Index { index, .. } => visitor.visit_expr_mut(index),
+        Expr::Call(subroutine_call) => visitor.visit_subroutine_call_mut(subroutine_call),
+        Expr::Unary { operand, .. } => visitor.visit_expr_mut(operand),
+        Expr::Binary { lhs, rhs, .. } => {
+            visitor.visit_expr_mut(lhs);
+            visitor.visit_expr_mut(rhs);
+        }
+        Expr::Paren(expr) => visitor.visit_expr_mut(expr),
+    }
+}
+
+pub fn walk_subroutine_call_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    subroutine_call: &mut SubroutineCall,
+) {
+    for argument in subroutine_call.arguments.iter_mut() {
+        visitor.visit_expr_mut(argument);
+    }
+}
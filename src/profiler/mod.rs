@@ -0,0 +1,182 @@
+//! Counts the VM instructions a program executes in each subroutine, see [`Profiler`].
+//!
+//! On the Hack platform the running time of a program is the number of instructions it
+//! executes, so the profile shows where a program is slow: the subroutines of the program
+//! as well as the ones of the operating system, such as `Math.multiply`.
+use std::collections::HashMap;
+
+use crate::emulator::{Observer, Program};
+
+/// A subroutine in the call tree, i.e. a chain of calls from `Sys.init`.
+struct Node {
+    /// The index of the function in [`Program::functions`], or `None` for the root.
+    function: Option<usize>,
+    parent: usize,
+    children: HashMap<usize, usize>,
+    /// The number of times the chain of calls was made.
+    calls: u64,
+    /// The number of instructions executed in the function itself.
+    instructions: u64,
+    /// True if the function already appears further up the chain, i.e. it called itself.
+    recursive: bool,
+}
+
+/// The profile of one subroutine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileEntry {
+    /// The name of the subroutine, e.g. `Main.main`.
+    pub name: String,
+    /// The instructions executed in the subroutine and in the subroutines it called.
+    pub inclusive: u64,
+    /// The instructions executed in the subroutine itself.
+    pub exclusive: u64,
+    /// The number of times the subroutine was called.
+    pub calls: u64,
+}
+
+/// An [`Observer`] that builds the call tree of a program while the
+/// [`Emulator`](crate::emulator::Emulator) runs it.
+pub struct Profiler {
+    names: Vec<String>,
+    nodes: Vec<Node>,
+    current: usize,
+    total: u64,
+}
+
+impl Profiler {
+    pub fn new(program: &Program) -> Profiler {
+        Profiler {
+            names: program.functions().iter().map(|function| function.name.clone()).collect(),
+            nodes: vec![Node {
+                function: None,
+                parent: 0,
+                children: HashMap::new(),
+                calls: 0,
+                instructions: 0,
+                recursive: false,
+            }],
+            current: 0,
+            total: 0,
+        }
+    }
+
+    /// Returns the number of instructions executed in total.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the profile of every subroutine that was called, the subroutines that
+    /// executed the most instructions themselves first.
+    pub fn entries(&self) -> Vec<ProfileEntry> {
+        // the instructions of each node and all of its descendants; children are always
+        // added after their parents, so going backwards sees the children first
+        let mut subtree: Vec<u64> = self.nodes.iter().map(|node| node.instructions).collect();
+        for idx in (1..self.nodes.len()).rev() {
+            subtree[self.nodes[idx].parent] += subtree[idx];
+        }
+        let mut entries: HashMap<usize, ProfileEntry> = HashMap::new();
+        for (idx, node) in self.nodes.iter().enumerate().skip(1) {
+            let function = node.function.unwrap_or_default();
+            let entry = entries.entry(function).or_insert_with(|| ProfileEntry {
+                name: self.names[function].clone(),
+                inclusive: 0,
+                exclusive: 0,
+                calls: 0,
+            });
+            // a recursive call is already included in the outermost call
+            if !node.recursive {
+                entry.inclusive += subtree[idx];
+            }
+            entry.exclusive += node.instructions;
+            entry.calls += node.calls;
+        }
+        let mut entries: Vec<ProfileEntry> = entries.into_values().collect();
+        entries.sort_by(|a, b| b.exclusive.cmp(&a.exclusive).then_with(|| a.name.cmp(&b.name)));
+        entries
+    }
+
+    /// Returns the flat profile as a table, e.g.
+    /// ```text
+    ///  inclusive       %  exclusive       %     calls  subroutine
+    ///      31274  100.0%       2040    6.5%         1  Main.main
+    /// ```
+    pub fn flat_profile(&self) -> String {
+        let percent = |count: u64| 100.0 * count as f64 / self.total.max(1) as f64;
+        let mut profile = format!(
+            "{:>10} {:>7} {:>10} {:>7} {:>9}  subroutine\n",
+            "inclusive", "%", "exclusive", "%", "calls"
+        );
+        for entry in self.entries() {
+            profile.push_str(&format!(
+                "{:>10} {:>6.1}% {:>10} {:>6.1}% {:>9}  {}\n",
+                entry.inclusive,
+                percent(entry.inclusive),
+                entry.exclusive,
+                percent(entry.exclusive),
+                entry.calls,
+                entry.name
+            ));
+        }
+        profile
+    }
+
+    /// Returns the folded stacks that flame graph tools such as `flamegraph.pl`, inferno
+    /// or speedscope read: a line per chain of calls with the instructions executed at its
+    /// end, e.g. `Sys.init;Main.main;Math.multiply 1380`.
+    pub fn folded_stacks(&self) -> String {
+        let mut lines = Vec::new();
+        for (idx, node) in self.nodes.iter().enumerate().skip(1) {
+            if node.instructions == 0 {
+                continue;
+            }
+            let mut names = Vec::new();
+            let mut current = idx;
+            while let Some(function) = self.nodes[current].function {
+                names.push(self.names[function].as_str());
+                current = self.nodes[current].parent;
+            }
+            names.reverse();
+            lines.push(format!("{} {}\n", names.join(";"), node.instructions));
+        }
+        lines.sort();
+        lines.concat()
+    }
+}
+
+impl Observer for Profiler {
+    fn instruction(&mut self) {
+        self.nodes[self.current].instructions += 1;
+        self.total += 1;
+    }
+
+    fn call(&mut self, function: usize) {
+        let child = match self.nodes[self.current].children.get(&function) {
+            Some(child) => *child,
+            None => {
+                let mut recursive = false;
+                let mut ancestor = self.current;
+                while let Some(caller) = self.nodes[ancestor].function {
+                    recursive |= caller == function;
+                    ancestor = self.nodes[ancestor].parent;
+                }
+                self.nodes.push(Node {
+                    function: Some(function),
+                    parent: self.current,
+                    children: HashMap::new(),
+                    calls: 0,
+                    instructions: 0,
+                    recursive,
+                });
+                let child = self.nodes.len() - 1;
+                self.nodes[self.current].children.insert(function, child);
+                child
+            }
+        };
+        self.nodes[child].calls += 1;
+        self.current = child;
+    }
+
+    fn ret(&mut self) {
+        self.current = self.nodes[self.current].parent;
+    }
+}
@@ -0,0 +1,62 @@
+//! Defines tokenizer errors.
+use std::error;
+use std::fmt;
+
+use super::tokens::Position;
+
+/// The kind of [`LexError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A string constant is not closed before the end of the file.
+    UnterminatedString,
+    /// A string constant is not closed before the end of the line.
+    NewlineInString,
+    /// A character that cannot appear outside of comments and string constants.
+    IllegalCharacter(char),
+    /// A word that starts with a digit but is not an integer constant.
+    IdentifierStartsWithDigit(String),
+    /// An integer constant outside of the range 0..32767.
+    IntegerOutOfRange(String),
+    /// A block comment is not closed before the end of the file.
+    UnterminatedComment,
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexErrorKind::UnterminatedString => write!(f, "Unterminated string constant"),
+            LexErrorKind::NewlineInString => {
+                write!(f, "String constants cannot contain a newline")
+            }
+            LexErrorKind::IllegalCharacter(c) => write!(f, "Illegal character '{}'", c),
+            LexErrorKind::IdentifierStartsWithDigit(s) => {
+                write!(f, "Identifiers cannot start with a digit '{}'", s)
+            }
+            LexErrorKind::IntegerOutOfRange(s) => {
+                write!(f, "Integer constant must be in the range 0..32767 '{}'", s)
+            }
+            LexErrorKind::UnterminatedComment => write!(f, "Unterminated block comment"),
+        }
+    }
+}
+
+/// This error occurs when the Jack code contains characters that do not form valid tokens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub position: Position,
+    /// The content of the line the error occurred in.
+    pub line: String,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Lex Error in line {}, column {}: {}: {}",
+            self.position.line, self.position.column, self.kind, self.line
+        )
+    }
+}
+
+impl error::Error for LexError {}
@@ -0,0 +1,278 @@
+//! Splits the characters of a Jack file into tokens.
+use std::sync::Arc;
+
+use super::error::{LexError, LexErrorKind};
+use super::tokens::{
+    Constant, Keyword, Position, Symbol, SyntaxToken, Token, TokenWrapper, Trivia, TriviaKind,
+};
+use crate::io::line::Line;
+
+/// The largest integer constant allowed in Jack.
+const MAX_INTEGER_CONSTANT: u32 = 32767;
+
+/// Walks over the characters of a Jack file and produces one token at a time.
+/// Keeps track of the current line and column, and skips whitespace and comments.
+pub struct Lexer {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+    column: usize,
+    lines: Vec<Arc<Line>>,
+    finished: bool,
+}
+
+impl Lexer {
+    pub fn new(source: &str) -> Lexer {
+        let lines = source
+            .split('\n')
+            .enumerate()
+            .map(|(i, s)| Arc::new(Line::new(s.trim(), i + 1)))
+            .collect();
+        Lexer {
+            chars: source.chars().collect(),
+            index: 0,
+            line: 1,
+            column: 1,
+            lines,
+            finished: false,
+        }
+    }
+
+    /// Returns the next token, or `None` at the end of the file.
+    pub fn next_token(&mut self) -> Option<Result<TokenWrapper, LexError>> {
+        let trivia = match self.read_trivia(false) {
+            Ok(trivia) => trivia,
+            Err(e) => return Some(Err(e)),
+        };
+        self.read_token().map(|token| token.map(|token| with_doc(token, &trivia)))
+    }
+
+    /// Returns the next token together with its text and trivia (lossless mode).
+    /// At the end of the file, a final token without [`TokenWrapper`] is returned that holds
+    /// the remaining trivia, followed by `None`.
+    pub fn next_syntax_token(&mut self) -> Option<Result<SyntaxToken, LexError>> {
+        if self.finished {
+            return None;
+        }
+        let leading_trivia = match self.read_trivia(false) {
+            Ok(trivia) => trivia,
+            Err(e) => return Some(Err(e)),
+        };
+        let start = self.index;
+        let token = match self.read_token() {
+            Some(Ok(token)) => Some(with_doc(token, &leading_trivia)),
+            Some(Err(e)) => return Some(Err(e)),
+            None => {
+                self.finished = true;
+                None
+            }
+        };
+        let text = self.chars[start..self.index].iter().collect();
+        let trailing_trivia = match self.read_trivia(true) {
+            Ok(trivia) => trivia,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(Ok(SyntaxToken {
+            leading_trivia,
+            token,
+            text,
+            trailing_trivia,
+        }))
+    }
+
+    fn read_token(&mut self) -> Option<Result<TokenWrapper, LexError>> {
+        let c = self.peek(0)?;
+        let line = self.lines[self.line - 1].clone();
+        let position = self.position();
+
+        let token = if let Some(symbol) = Symbol::from_char(c) {
+            self.advance();
+            Ok(Token::Symbol(symbol))
+        } else if c == '"' {
+            self.read_string_constant(position)
+        } else if Lexer::is_word_character(c) {
+            self.read_word(position)
+        } else {
+            Err(self.error(LexErrorKind::IllegalCharacter(c), position))
+        };
+        Some(token.map(|token| TokenWrapper {
+            token,
+            line,
+            column: position.column,
+            doc: None,
+        }))
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn error(&self, kind: LexErrorKind, position: Position) -> LexError {
+        LexError {
+            kind,
+            position,
+            line: self.lines[position.line - 1].content.clone(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.index += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// Reads whitespace and comments. If `trailing` is set, stops after the end of the
+    /// current line, so that the trivia on the line of a token is attached to that token.
+    fn read_trivia(&mut self, trailing: bool) -> Result<Vec<Trivia>, LexError> {
+        let mut trivia = Vec::new();
+        let mut end_of_line = false;
+        while let Some(c) = self.peek(0) {
+            let start = self.index;
+            let kind = if c.is_whitespace() {
+                while let Some(c) = self.peek(0) {
+                    if !c.is_whitespace() {
+                        break;
+                    }
+                    self.advance();
+                    if trailing && c == '\n' {
+                        end_of_line = true;
+                        break;
+                    }
+                }
+                TriviaKind::Whitespace
+            } else if c == '/' && self.peek(1) == Some('/') {
+                // line comment, runs until the end of the line
+                while let Some(c) = self.peek(0) {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.advance();
+                }
+                TriviaKind::LineComment
+            } else if c == '/' && self.peek(1) == Some('*') {
+                // block comment (or /** doc comment */), may span several lines
+                let kind = if self.peek(2) == Some('*') && self.peek(3) != Some('/') {
+                    TriviaKind::DocComment
+                } else {
+                    TriviaKind::BlockComment
+                };
+                let position = self.position();
+                self.advance();
+                self.advance();
+                loop {
+                    if self.peek(0).is_none() {
+                        return Err(self.error(LexErrorKind::UnterminatedComment, position));
+                    }
+                    if self.peek(0) == Some('*') && self.peek(1) == Some('/') {
+                        self.advance();
+                        self.advance();
+                        break;
+                    }
+                    self.advance();
+                }
+                kind
+            } else {
+                break;
+            };
+            trivia.push(Trivia {
+                kind,
+                text: self.chars[start..self.index].iter().collect(),
+            });
+            if end_of_line {
+                break;
+            }
+        }
+        Ok(trivia)
+    }
+
+    fn read_string_constant(&mut self, position: Position) -> Result<Token, LexError> {
+        self.advance(); // skip opening double quote
+        let mut string_constant = String::new();
+        loop {
+            match self.peek(0) {
+                Some('"') => break,
+                Some('\n') | Some('\r') => {
+                    return Err(self.error(LexErrorKind::NewlineInString, position));
+                }
+                Some(c) => {
+                    string_constant.push(c);
+                    self.advance();
+                }
+                None => return Err(self.error(LexErrorKind::UnterminatedString, position)),
+            }
+        }
+        self.advance(); // skip closing double quote
+        Ok(Token::Constant(Constant::StringConstant(string_constant)))
+    }
+
+    fn read_word(&mut self, position: Position) -> Result<Token, LexError> {
+        let mut word = String::new();
+        while let Some(c) = self.peek(0) {
+            if !Lexer::is_word_character(c) {
+                break;
+            }
+            word.push(c);
+            self.advance();
+        }
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            // if token starts with digit, it is a number
+            if !word.chars().all(|c| c.is_ascii_digit()) {
+                return Err(self.error(LexErrorKind::IdentifierStartsWithDigit(word), position));
+            }
+            match word.parse::<u32>() {
+                Ok(num) if num <= MAX_INTEGER_CONSTANT => {
+                    Ok(Token::Constant(Constant::IntegerConstant(num)))
+                }
+                _ => Err(self.error(LexErrorKind::IntegerOutOfRange(word), position)),
+            }
+        } else if let Ok(keyword) = word.parse::<Keyword>() {
+            Ok(Token::Keyword(keyword))
+        } else {
+            Ok(Token::Identifier(word))
+        }
+    }
+
+    /// Identifiers, keywords and integer constants consist of letters, digits and underscores.
+    fn is_word_character(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
+    }
+}
+
+/// Attaches the doc comment at the end of the trivia before the token to the token.
+fn with_doc(mut token: TokenWrapper, trivia: &[Trivia]) -> TokenWrapper {
+    let comment = trivia.iter().rev().find(|trivia| trivia.kind != TriviaKind::Whitespace);
+    if let Some(comment) = comment.filter(|comment| comment.kind == TriviaKind::DocComment) {
+        token.doc = Some(doc_text(&comment.text));
+    }
+    token
+}
+
+/// Returns the text of a `/** ... */` comment without the delimiters and the `*`
+/// at the start of each line.
+fn doc_text(comment: &str) -> String {
+    let inner = comment.trim_start_matches("/**");
+    // a comment can be closed with more than one star, e.g. `**/`
+    let inner = inner.strip_suffix("*/").unwrap_or(inner).trim_end_matches('*');
+    let lines: Vec<&str> = inner
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line)
+        })
+        .collect();
+    lines.join("\n").trim().to_string()
+}
@@ -0,0 +1,54 @@
+//! A lazy stream of tokens with lookahead.
+use std::collections::VecDeque;
+
+use super::error::LexError;
+use super::lexer::Lexer;
+use super::tokens::TokenWrapper;
+
+/// Produces the tokens of a Jack file one at a time, only lexing as far as the
+/// consumer has read or peeked. The stream ends after the first [`LexError`].
+pub struct TokenStream {
+    lexer: Lexer,
+    lookahead: VecDeque<Result<TokenWrapper, LexError>>,
+    finished: bool,
+}
+
+impl TokenStream {
+    pub fn new(source: &str) -> TokenStream {
+        TokenStream {
+            lexer: Lexer::new(source),
+            lookahead: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Returns the next token without consuming it.
+    pub fn peek(&mut self) -> Option<&Result<TokenWrapper, LexError>> {
+        self.peek_nth(0)
+    }
+
+    /// Returns the token `n` positions ahead without consuming anything.
+    /// `peek_nth(0)` is the same as [`peek`](TokenStream::peek).
+    pub fn peek_nth(&mut self, n: usize) -> Option<&Result<TokenWrapper, LexError>> {
+        while self.lookahead.len() <= n && !self.finished {
+            match self.lexer.next_token() {
+                Some(token) => {
+                    // the lexer does not recover from errors, so we stop at the first one
+                    self.finished = token.is_err();
+                    self.lookahead.push_back(token);
+                }
+                None => self.finished = true,
+            }
+        }
+        self.lookahead.get(n)
+    }
+}
+
+impl Iterator for TokenStream {
+    type Item = Result<TokenWrapper, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.peek();
+        self.lookahead.pop_front()
+    }
+}
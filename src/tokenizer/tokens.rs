@@ -1,25 +1,336 @@
 //! Defines the token.
-use std::rc::Rc;
+use std::fmt;
+use std::sync::Arc;
+use std::str::FromStr;
+
 use crate::io::line::Line;
 
 /// Wrapper type that holds a token and the corresponding [`Line`](crate::io::line::Line).
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TokenWrapper {
     pub token: Token,
-    pub line: Rc<Line>
+    pub line: Arc<Line>,
+    pub column: usize,
+    /// The text of the `/** ... */` comment directly before the token, without the
+    /// delimiters and the `*` at the start of each line.
+    pub doc: Option<String>,
 }
 
-#[derive(Debug)]
+impl TokenWrapper {
+    /// Returns the position of the token in the Jack file.
+    pub fn position(&self) -> Position {
+        Position {
+            line: self.line.number,
+            column: self.column,
+        }
+    }
+}
+
+/// A position in a Jack file. Line and column numbers start at 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Token {
-    Symbol(String),
-    Keyword(String),
+    Symbol(Symbol),
+    Keyword(Keyword),
     Constant(Constant),
     Identifier(String),
 }
 
-#[derive(Debug)]
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+            Token::Keyword(keyword) => write!(f, "{}", keyword),
+            Token::Constant(Constant::IntegerConstant(i)) => write!(f, "{}", i),
+            Token::Constant(Constant::StringConstant(s)) => write!(f, "\"{}\"", s),
+            Token::Identifier(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Integer and string constants. `true`, `false`, `null` and `this` are
+/// keyword constants and are represented by [`Keyword`].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Constant {
     IntegerConstant(u32),
     StringConstant(String),
-    BooleanConstant(bool),
-}
\ No newline at end of file
+}
+
+/// The keywords of the Jack language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keyword {
+    Class,
+    Constructor,
+    Function,
+    Method,
+    Field,
+    Static,
+    Var,
+    Int,
+    Char,
+    Boolean,
+    Void,
+    True,
+    False,
+    Null,
+    This,
+    Let,
+    Do,
+    If,
+    Else,
+    While,
+    Return,
+}
+
+impl Keyword {
+    /// Returns true for `true`, `false`, `null` and `this`.
+    pub fn is_constant(&self) -> bool {
+        matches!(
+            self,
+            Keyword::True | Keyword::False | Keyword::Null | Keyword::This
+        )
+    }
+}
+
+impl FromStr for Keyword {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "class" => Ok(Keyword::Class),
+            "constructor" => Ok(Keyword::Constructor),
+            "function" => Ok(Keyword::Function),
+            "method" => Ok(Keyword::Method),
+            "field" => Ok(Keyword::Field),
+            "static" => Ok(Keyword::Static),
+            "var" => Ok(Keyword::Var),
+            "int" => Ok(Keyword::Int),
+            "char" => Ok(Keyword::Char),
+            "boolean" => Ok(Keyword::Boolean),
+            "void" => Ok(Keyword::Void),
+            "true" => Ok(Keyword::True),
+            "false" => Ok(Keyword::False),
+            "null" => Ok(Keyword::Null),
+            "this" => Ok(Keyword::This),
+            "let" => Ok(Keyword::Let),
+            "do" => Ok(Keyword::Do),
+            "if" => Ok(Keyword::If),
+            "else" => Ok(Keyword::Else),
+            "while" => Ok(Keyword::While),
+            "return" => Ok(Keyword::Return),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Keyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Keyword::Class => "class",
+            Keyword::Constructor => "constructor",
+            Keyword::Function => "function",
+            Keyword::Method => "method",
+            Keyword::Field => "field",
+            Keyword::Static => "static",
+            Keyword::Var => "var",
+            Keyword::Int => "int",
+            Keyword::Char => "char",
+            Keyword::Boolean => "boolean",
+            Keyword::Void => "void",
+            Keyword::True => "true",
+            Keyword::False => "false",
+            Keyword::Null => "null",
+            Keyword::This => "this",
+            Keyword::Let => "let",
+            Keyword::Do => "do",
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::While => "while",
+            Keyword::Return => "return",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The symbols of the Jack language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symbol {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    LeftBrace,
+    RightBrace,
+    Comma,
+    Semicolon,
+    Dot,
+    Plus,
+    Minus,
+    Asterisk,
+    Slash,
+    Ampersand,
+    Pipe,
+    LessThan,
+    GreaterThan,
+    Equal,
+    Tilde,
+}
+
+impl Symbol {
+    /// Returns the symbol for the given character, or `None` if the character is not a symbol.
+    pub fn from_char(c: char) -> Option<Symbol> {
+        match c {
+            '(' => Some(Symbol::LeftParen),
+            ')' => Some(Symbol::RightParen),
+            '[' => Some(Symbol::LeftBracket),
+            ']' => Some(Symbol::RightBracket),
+            '{' => Some(Symbol::LeftBrace),
+            '}' => Some(Symbol::RightBrace),
+            ',' => Some(Symbol::Comma),
+            ';' => Some(Symbol::Semicolon),
+            '.' => Some(Symbol::Dot),
+            '+' => Some(Symbol::Plus),
+            '-' => Some(Symbol::Minus),
+            '*' => Some(Symbol::Asterisk),
+            '/' => Some(Symbol::Slash),
+            '&' => Some(Symbol::Ampersand),
+            '|' => Some(Symbol::Pipe),
+            '<' => Some(Symbol::LessThan),
+            '>' => Some(Symbol::GreaterThan),
+            '=' => Some(Symbol::Equal),
+            '~' => Some(Symbol::Tilde),
+            _ => None,
+        }
+    }
+
+    /// Returns the character of the symbol.
+    pub fn as_char(&self) -> char {
+        match self {
+            Symbol::LeftParen => '(',
+            Symbol::RightParen => ')',
+            Symbol::LeftBracket => '[',
+            Symbol::RightBracket => ']',
+            Symbol::LeftBrace => '{',
+            Symbol::RightBrace => '}',
+            Symbol::Comma => ',',
+            Symbol::Semicolon => ';',
+            Symbol::Dot => '.',
+            Symbol::Plus => '+',
+            Symbol::Minus => '-',
+            Symbol::Asterisk => '*',
+            Symbol::Slash => '/',
+            Symbol::Ampersand => '&',
+            Symbol::Pipe => '|',
+            Symbol::LessThan => '<',
+            Symbol::GreaterThan => '>',
+            Symbol::Equal => '=',
+            Symbol::Tilde => '~',
+        }
+    }
+
+    /// Returns true for the binary operators `+ - * / & | < > =`.
+    pub fn is_operator(&self) -> bool {
+        matches!(
+            self,
+            Symbol::Plus
+                | Symbol::Minus
+                | Symbol::Asterisk
+                | Symbol::Slash
+                | Symbol::Ampersand
+                | Symbol::Pipe
+                | Symbol::LessThan
+                | Symbol::GreaterThan
+                | Symbol::Equal
+        )
+    }
+
+    /// Returns true for the unary operators `-` and `~`.
+    pub fn is_unary_operator(&self) -> bool {
+        matches!(self, Symbol::Minus | Symbol::Tilde)
+    }
+
+    /// Returns the XML representation of the symbol, with XML special characters escaped.
+    pub fn to_xml(&self) -> String {
+        match self {
+            Symbol::LessThan => "&lt;".to_string(),
+            Symbol::GreaterThan => "&gt;".to_string(),
+            Symbol::Ampersand => "&amp;".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Symbol::from_char(c).ok_or(()),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
+/// Whitespace and comments. They carry no meaning for the compiler, but are kept
+/// in lossless mode so that the Jack file can be reproduced exactly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    /// `// ...`
+    LineComment,
+    /// `/* ... */`
+    BlockComment,
+    /// `/** ... */`
+    DocComment,
+}
+
+impl fmt::Display for Trivia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// A token together with its text as written in the Jack file and the surrounding trivia.
+/// The trailing trivia runs until the end of the line the token is on, everything after
+/// that is leading trivia of the next token.
+///
+/// The last token of a file has no [`TokenWrapper`] and an empty text.
+/// It only holds the trivia after the last real token.
+#[derive(Debug)]
+pub struct SyntaxToken {
+    pub leading_trivia: Vec<Trivia>,
+    pub token: Option<TokenWrapper>,
+    pub text: String,
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in self.leading_trivia.iter() {
+            write!(f, "{}", trivia)?;
+        }
+        write!(f, "{}", self.text)?;
+        for trivia in self.trailing_trivia.iter() {
+            write!(f, "{}", trivia)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! Debug information that is written next to the VM code (`Foo.vmdbg`).
+use std::collections::HashMap;
+
+use super::source_map::SourceMap;
+use super::symbol_table::Symbol;
+use crate::tokenizer::tokens::Position;
+
+/// The variables that are visible in a scope. The scope of a class is named
+/// after the class, the scope of a subroutine is named `Class.subroutine`.
+#[derive(Debug)]
+pub struct Scope {
+    pub name: String,
+    pub symbols: Vec<Symbol>,
+}
+
+/// The conditional jump (`if-goto`) that decides which side of an `if` or `while`
+/// condition is executed. `jumps_if` is the value of the condition for which the jump is taken:
+/// `true` for `if` statements and `false` for `while` statements (the jump leaves the loop).
+#[derive(Debug)]
+pub struct Branch {
+    pub instruction: usize,
+    pub position: Position,
+    pub jumps_if: bool,
+}
+
+/// Holds everything a debugger, profiler or coverage tool needs to map the VM code of a class
+/// back to the Jack file: the origin of each VM instruction, the symbol table of each scope
+/// and the branches of each `if` and `while` statement.
+#[derive(Debug, Default)]
+pub struct DebugInfo {
+    pub file: String,
+    pub instructions: Vec<String>,
+    pub source_map: SourceMap,
+    pub scopes: Vec<Scope>,
+    pub branches: Vec<Branch>,
+}
+
+impl DebugInfo {
+    pub fn new(file: &str) -> DebugInfo {
+        DebugInfo {
+            file: file.to_string(),
+            instructions: Vec::new(),
+            source_map: SourceMap::new(),
+            scopes: Vec::new(),
+            branches: Vec::new(),
+        }
+    }
+
+    /// Records the symbols of a scope, ordered by segment and index.
+    pub fn add_scope(&mut self, name: &str, symbols: &HashMap<String, Symbol>) {
+        let mut symbols: Vec<Symbol> = symbols.values().cloned().collect();
+        symbols.sort_by(|a, b| {
+            String::from(&a.var_kind)
+                .cmp(&String::from(&b.var_kind))
+                .then(a.index.cmp(&b.index))
+        });
+        self.scopes.push(Scope {
+            name: name.to_string(),
+            symbols,
+        });
+    }
+
+    /// Records the conditional jump of an `if` or `while` statement at the given position.
+    /// `instruction` is the index of the `if-goto` instruction.
+    pub fn add_branch(&mut self, instruction: usize, position: Position, jumps_if: bool) {
+        self.branches.push(Branch {
+            instruction,
+            position,
+            jumps_if,
+        });
+    }
+
+    /// Returns the JSON representation of the debug information.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n");
+        json.push_str(&format!("  \"file\": {},\n", json_string(&self.file)));
+
+        json.push_str("  \"instructions\": [");
+        let instructions: Vec<String> = self
+            .instructions
+            .iter()
+            .zip(self.source_map.entries.iter())
+            .map(|(instruction, entry)| {
+                format!(
+                    "\n    {{\"command\": {}, \"line\": {}, \"column\": {}, \"subroutine\": {}}}",
+                    json_string(instruction),
+                    entry.position.line,
+                    entry.position.column,
+                    json_string(&entry.subroutine)
+                )
+            })
+            .collect();
+        json.push_str(&instructions.join(","));
+        json.push_str("\n  ],\n");
+
+        json.push_str("  \"scopes\": [");
+        let scopes: Vec<String> = self
+            .scopes
+            .iter()
+            .map(|scope| {
+                let symbols: Vec<String> = scope
+                    .symbols
+                    .iter()
+                    .map(|symbol| {
+                        format!(
+                            "\n        {{\"name\": {}, \"kind\": {}, \"type\": {}, \"segment\": {}, \"index\": {}}}",
+                            json_string(&symbol.name),
+                            json_string(&symbol.var_kind.to_string()),
+                            json_string(&symbol.var_type.to_string()),
+                            json_string(&String::from(&symbol.var_kind)),
+                            symbol.index
+                        )
+                    })
+                    .collect();
+                let symbols = if symbols.is_empty() {
+                    "[]".to_string()
+                } else {
+                    format!("[{}\n      ]", symbols.join(","))
+                };
+                format!(
+                    "\n    {{\n      \"name\": {},\n      \"symbols\": {}\n    }}",
+                    json_string(&scope.name),
+                    symbols
+                )
+            })
+            .collect();
+        json.push_str(&scopes.join(","));
+        json.push_str("\n  ],\n");
+
+        json.push_str("  \"branches\": [");
+        let branches: Vec<String> = self
+            .branches
+            .iter()
+            .map(|branch| {
+                format!(
+                    "\n    {{\"instruction\": {}, \"line\": {}, \"column\": {}, \"jumps_if\": {}}}",
+                    branch.instruction, branch.position.line, branch.position.column, branch.jumps_if
+                )
+            })
+            .collect();
+        json.push_str(&branches.join(","));
+        json.push_str("\n  ]\n}\n");
+        json
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
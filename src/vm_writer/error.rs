@@ -0,0 +1,28 @@
+//! Defines VM writer errors.
+use std::error;
+use std::fmt;
+
+use crate::tokenizer::tokens::Position;
+
+/// This error occurs when syntactically valid Jack code cannot be translated to VM code,
+/// e.g. because it uses a variable that is not declared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WriteError {
+    pub message: String,
+    /// The position of the statement the error occurred in.
+    pub position: Position,
+    /// The content of the line the error occurred in.
+    pub line: String,
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "VMWrite Error in line {}, column {}: {}: {}",
+            self.position.line, self.position.column, self.message, self.line
+        )
+    }
+}
+
+impl error::Error for WriteError {}
@@ -1,37 +1,133 @@
 //! Takes in a parse tree and writes the corresponding VM code.
 use crate::parser::parse_tree::ParseTree;
 use crate::parser::parse_tree::class::ClassNode;
-use crate::parser::parse_tree::expression::{
-    ExpressionElement, ExpressionNode, SubroutineCallNode, TermElement, TermNode,
-};
+use crate::parser::parse_tree::expression::{BinaryOp, Const, Expr, SubroutineCall, UnaryOp};
 use crate::parser::parse_tree::statement::WhileStatementNode;
 use crate::parser::parse_tree::subroutine::{SubroutineBodyNode, SubroutineNode, SubroutineType};
 use crate::parser::parse_tree::var::{VarKind, VarType};
 use crate::parser::parse_tree::statement::{
-    DoStatementNode, IfStatementNode, LetStatementNode, ReturnStatementNode, Statement,
+    DoStatementNode, IfStatementNode, LetStatementNode, LetTarget, ReturnStatementNode, Statement,
 };
+use crate::tokenizer::tokens::{Keyword, Position};
+
+pub mod debug_info;
+use debug_info::DebugInfo;
+
+pub mod error;
+use error::WriteError;
+
+pub mod source_map;
+use source_map::SourceMap;
 
-mod symbol_table;
+pub mod symbol_table;
 use symbol_table::SymbolTable;
 
 /// Takes in a parse tree and writes the corresponding VM code.
-pub struct VMWriter {}
+pub struct VMWriter {
+    annotate: bool,
+    fold_constants: bool,
+}
+
+impl Default for VMWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl VMWriter {
     pub fn new() -> VMWriter {
-        VMWriter {}
+        VMWriter {
+            annotate: false,
+            fold_constants: false,
+        }
+    }
+
+    /// If `annotate` is true, the VM code is interleaved with comments that show
+    /// the Jack line each subroutine and statement was generated from, e.g.
+    /// `// src/Main.jack:12: let x = x + 1;`. The path is the
+    /// [`file`](crate::parser::parse_tree::ParseTree::file) of the parse tree.
+    pub fn annotate(mut self, annotate: bool) -> Self {
+        self.annotate = annotate;
+        self
+    }
+
+    /// If `fold_constants` is true, an expression whose operands are all constants is
+    /// evaluated by the compiler and pushed as a single constant, e.g. `let x = 60 * 60;`
+    /// pushes `3600` instead of calling `Math.multiply`. Division by zero is left to run time.
+    pub fn fold_constants(mut self, fold_constants: bool) -> Self {
+        self.fold_constants = fold_constants;
+        self
     }
 
     /// Takes in a [`ParseTree`](crate::parser::parse_tree::ParseTree) and returns
     /// a string containing the VM code.
-    pub fn write(&self, parse_tree: &ParseTree) -> String {
+    /// Returns a [`WriteError`](error::WriteError) for the first variable that is not
+    /// declared or string constant that is not ASCII.
+    pub fn write(&self, parse_tree: &ParseTree) -> Result<String, WriteError> {
+        let (vm_code, _) = self.write_with_source_map(parse_tree)?;
+        Ok(vm_code)
+    }
+
+    /// Same as [`write`](VMWriter::write), but additionally returns a
+    /// [`SourceMap`](source_map::SourceMap) that maps every VM instruction
+    /// back to the Jack statement it was generated from.
+    pub fn write_with_source_map(&self, parse_tree: &ParseTree) -> Result<(String, SourceMap), WriteError> {
+        let (vm_code, debug_info) = self.write_with_debug_info(parse_tree, "")?;
+        Ok((vm_code, debug_info.source_map))
+    }
+
+    /// Same as [`write`](VMWriter::write), but additionally returns the
+    /// [`DebugInfo`](debug_info::DebugInfo) of the class. `file` is the name
+    /// of the Jack file the parse tree was read from.
+    pub fn write_with_debug_info(&self, parse_tree: &ParseTree, file: &str) -> Result<(String, DebugInfo), WriteError> {
         let mut vm_code = Vec::new();
         let mut symbol_table = SymbolTable::new();
+        let mut debug_info = DebugInfo::new(file);
+
+        self.write_class(
+            &parse_tree.class_node,
+            &mut vm_code,
+            &mut symbol_table,
+            &mut debug_info,
+        )
+        .map_err(|mut error| {
+            error.line = parse_tree.source_line(error.position.line).unwrap_or("").to_string();
+            error
+        })?;
+        let mut vm_code_str = if self.annotate {
+            self.annotate_vm_code(&vm_code, &debug_info.source_map, parse_tree)
+        } else {
+            vm_code.join("\n")
+        };
+        vm_code_str.push('\n');
+        debug_info.instructions = vm_code;
+        Ok((vm_code_str, debug_info))
+    }
 
-        self.write_class(&parse_tree.class_node, &mut vm_code, &mut symbol_table);
-        let mut vm_code = vm_code.join("\n");
-        vm_code.push('\n');
-        vm_code
+    /// Inserts a comment with the Jack source line before every block of
+    /// instructions that was generated from the same subroutine declaration or statement.
+    fn annotate_vm_code(
+        &self,
+        vm_code: &[String],
+        source_map: &SourceMap,
+        parse_tree: &ParseTree,
+    ) -> String {
+        let mut annotated = Vec::new();
+        let mut previous = None;
+        for (instruction, entry) in vm_code.iter().zip(source_map.entries.iter()) {
+            if previous != Some(entry) {
+                let line = entry.position.line;
+                annotated.push(format!(
+                    "// {}:{}: {}",
+                    parse_tree.file,
+                    line,
+                    parse_tree.source_line(line).unwrap_or("")
+                ));
+                previous = Some(entry);
+            }
+            annotated.push(instruction.clone());
+        }
+        annotated.join("\n")
     }
 
     fn write_class(
@@ -39,7 +135,8 @@ impl VMWriter {
         class_node: &ClassNode,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+        debug_info: &mut DebugInfo,
+    ) -> Result<(), WriteError> {
         let mut field_index = 0;
         let mut static_index = 0;
         for var in class_node.variables.iter() {
@@ -58,6 +155,7 @@ impl VMWriter {
                 symbol_table.define(name, &var.var_type, &var.var_kind, index);
             }
         }
+        debug_info.add_scope(&class_node.name, &symbol_table.class_scope);
         for subroutine in class_node.subroutines.iter() {
             self.write_subroutine(
                 subroutine,
@@ -65,8 +163,10 @@ impl VMWriter {
                 field_index,
                 vm_code,
                 symbol_table,
-            );
+                debug_info,
+            )?;
         }
+        Ok(())
     }
 
     fn write_subroutine(
@@ -76,11 +176,17 @@ impl VMWriter {
         num_fields: u32,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+        debug_info: &mut DebugInfo,
+    ) -> Result<(), WriteError> {
+        debug_info.source_map.enter_subroutine(
+            &format!("{}.{}", class_name, subroutine_node.name),
+            subroutine_node.position,
+            vm_code.len(),
+        );
         let mut param_index = 0;
         if let SubroutineType::Method = subroutine_node.subroutine_type {
             symbol_table.define(
-                &"this".to_string(),
+                "this",
                 &VarType::get(class_name),
                 &VarKind::Arg,
                 param_index,
@@ -103,10 +209,18 @@ impl VMWriter {
             &mut while_index,
             vm_code,
             symbol_table,
+            debug_info,
+        )?;
+        debug_info.add_scope(
+            &format!("{}.{}", class_name, subroutine_node.name),
+            &symbol_table.subroutine_scope,
         );
         symbol_table.clear_subroutine_scope();
+        debug_info.source_map.exit(vm_code.len());
+        Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn write_subroutine_body(
         &self,
         subroutine_body: &SubroutineBodyNode,
@@ -118,12 +232,8 @@ impl VMWriter {
         while_index: &mut u32,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
-
-        if subroutine_name == "nextMask" {
-            println!("{:?}", subroutine_body);
-        }
-
+        debug_info: &mut DebugInfo,
+    ) -> Result<(), WriteError> {
         let mut local_index = 0;
         for var in subroutine_body.variables.iter() {
             for name in var.var_names.iter() {
@@ -159,14 +269,17 @@ impl VMWriter {
                 while_index,
                 vm_code,
                 symbol_table,
-            );
+                debug_info,
+            )?;
         }
+        Ok(())
     }
 
     //------------------------------
     // STATEMENTS
     //------------------------------
 
+    #[allow(clippy::too_many_arguments)]
     fn write_statement(
         &self,
         statement: &Statement,
@@ -175,13 +288,15 @@ impl VMWriter {
         while_index: &mut u32,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+        debug_info: &mut DebugInfo,
+    ) -> Result<(), WriteError> {
+        debug_info.source_map.enter_statement(statement.position(), vm_code.len());
         match statement {
             Statement::Let(let_statement) => {
-                self.write_let_statement(let_statement, class_name, vm_code, symbol_table);
+                self.write_let_statement(let_statement, class_name, vm_code, symbol_table)?;
             }
             Statement::Do(do_statement) => {
-                self.write_do_statement(do_statement, class_name, vm_code, symbol_table);
+                self.write_do_statement(do_statement, class_name, vm_code, symbol_table)?;
             }
             Statement::If(if_statement) => {
                 self.write_if_statement(
@@ -191,7 +306,8 @@ impl VMWriter {
                     while_index,
                     vm_code,
                     symbol_table,
-                );
+                    debug_info,
+                )?;
             }
             Statement::While(while_statement) => {
                 self.write_while_statement(
@@ -201,12 +317,15 @@ impl VMWriter {
                     while_index,
                     vm_code,
                     symbol_table,
-                );
+                    debug_info,
+                )?;
             }
             Statement::Return(return_statement) => {
-                self.write_return_statement(return_statement, class_name, vm_code, symbol_table);
+                self.write_return_statement(return_statement, class_name, vm_code, symbol_table)?;
             }
         }
+        debug_info.source_map.exit(vm_code.len());
+        Ok(())
     }
 
     fn write_let_statement(
@@ -215,47 +334,34 @@ impl VMWriter {
         class_name: &String,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
-        if let Some(lhs_express) = &let_statement.lhs_expression {
-            // array indexing
-            self.write_expression(lhs_express, class_name, vm_code, symbol_table);
-            // lhs expression evaluates to array index, which will be on top of the stack
-            self.push(
-                &symbol_table.get_var_kind(&let_statement.var_name),
-                symbol_table.get_var_index(&let_statement.var_name),
-                vm_code,
-            );
-            vm_code.push("add".to_string());
-            // now evaluate rhs expression
-            self.write_expression(
-                &let_statement.rhs_expression,
-                class_name,
-                vm_code,
-                symbol_table,
-            );
-            // load rhs expression value into "temp 0" segment
-            self.pop("temp", 0, vm_code);
-            // the top of the stack will now be the pointer to the lvalue array index
-            // set the "that" segment to this address
-            self.pop("pointer", 1, vm_code);
-            // load value from "temp 0" into the lvalue array at the specified index
-            self.push("temp", 0, vm_code);
-            self.pop("that", 0, vm_code);
-        } else {
-            self.write_expression(
-                &let_statement.rhs_expression,
-                class_name,
-                vm_code,
-                symbol_table,
-            );
-            // I think we can only have normal variables here
-            // the lvalue that the rhs expression will be assigned to
-            self.pop(
-                &symbol_table.get_var_kind(&let_statement.var_name),
-                symbol_table.get_var_index(&let_statement.var_name),
-                vm_code,
-            );
+    ) -> Result<(), WriteError> {
+        let position = let_statement.position;
+        match &let_statement.target {
+            LetTarget::Index { name, index } => {
+                // array indexing
+                self.write_expression(index, class_name, position, vm_code, symbol_table)?;
+                // lhs expression evaluates to array index, which will be on top of the stack
+                self.push_var(name, position, vm_code, symbol_table)?;
+                vm_code.push("add".to_string());
+                // now evaluate rhs expression
+                self.write_expression(&let_statement.value, class_name, position, vm_code, symbol_table)?;
+                // load rhs expression value into "temp 0" segment
+                self.pop("temp", 0, vm_code);
+                // the top of the stack will now be the pointer to the lvalue array index
+                // set the "that" segment to this address
+                self.pop("pointer", 1, vm_code);
+                // load value from "temp 0" into the lvalue array at the specified index
+                self.push("temp", 0, vm_code);
+                self.pop("that", 0, vm_code);
+            }
+            LetTarget::Var(name) => {
+                self.write_expression(&let_statement.value, class_name, position, vm_code, symbol_table)?;
+                // the lvalue that the rhs expression will be assigned to
+                let (var_kind, index) = self.lookup(name, position, symbol_table)?;
+                self.pop(&var_kind, index, vm_code);
+            }
         }
+        Ok(())
     }
 
     fn write_do_statement(
@@ -264,17 +370,20 @@ impl VMWriter {
         class_name: &String,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+    ) -> Result<(), WriteError> {
         self.write_subroutine_call(
             &do_statement.subroutine_call,
             class_name,
+            do_statement.position,
             vm_code,
             symbol_table,
-        );
+        )?;
         // pop return value from stack and load it into "temp 0"
         self.pop("temp", 0, vm_code);
+        Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn write_if_statement(
         &self,
         if_statement: &IfStatementNode,
@@ -283,10 +392,19 @@ impl VMWriter {
         while_index: &mut u32,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+        debug_info: &mut DebugInfo,
+    ) -> Result<(), WriteError> {
         // evaluate condition and put resulting value on the stack
-        self.write_expression(&if_statement.condition, class_name, vm_code, symbol_table);
+        self.write_expression(
+            &if_statement.condition,
+            class_name,
+            if_statement.position,
+            vm_code,
+            symbol_table,
+        )?;
         let temp_idx = *if_index;
+        // the jump to IF_TRUE is taken if the condition is true
+        debug_info.add_branch(vm_code.len(), if_statement.position, true);
         vm_code.push(format!("if-goto IF_TRUE{}", temp_idx));
         vm_code.push(format!("goto IF_FALSE{}", temp_idx));
         vm_code.push(format!("label IF_TRUE{}", temp_idx));
@@ -301,7 +419,8 @@ impl VMWriter {
                 while_index,
                 vm_code,
                 symbol_table,
-            );
+                debug_info,
+            )?;
         }
 
         // when the else block exists
@@ -317,14 +436,17 @@ impl VMWriter {
                     while_index,
                     vm_code,
                     symbol_table,
-                );
+                    debug_info,
+                )?;
             }
             vm_code.push(format!("label IF_END{}", temp_idx));
         } else {
             vm_code.push(format!("label IF_FALSE{}", temp_idx));
         }
+        Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn write_while_statement(
         &self,
         while_statement: &WhileStatementNode,
@@ -333,17 +455,21 @@ impl VMWriter {
         while_index: &mut u32,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+        debug_info: &mut DebugInfo,
+    ) -> Result<(), WriteError> {
         let temp_idx = *while_index;
         *while_index += 1;
         vm_code.push(format!("label WHILE_EXP{}", temp_idx));
         self.write_expression(
             &while_statement.condition,
             class_name,
+            while_statement.position,
             vm_code,
             symbol_table,
-        );
+        )?;
         vm_code.push("not".to_string());
+        // the jump to WHILE_END is taken if the condition is false
+        debug_info.add_branch(vm_code.len(), while_statement.position, false);
         vm_code.push(format!("if-goto WHILE_END{}", temp_idx));
         // loop body here
         for statement in while_statement.block.iter() {
@@ -354,10 +480,12 @@ impl VMWriter {
                 while_index,
                 vm_code,
                 symbol_table,
-            );
+                debug_info,
+            )?;
         }
         vm_code.push(format!("goto WHILE_EXP{}", temp_idx));
         vm_code.push(format!("label WHILE_END{}", temp_idx));
+        Ok(())
     }
 
     fn write_return_statement(
@@ -366,151 +494,97 @@ impl VMWriter {
         class_name: &String,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+    ) -> Result<(), WriteError> {
         if let Some(expression) = &return_statement.expression {
-            self.write_expression(expression, class_name, vm_code, symbol_table);
+            self.write_expression(expression, class_name, return_statement.position, vm_code, symbol_table)?;
         } else {
             // no return value
             self.push("constant", 0, vm_code);
         }
         vm_code.push("return".to_string());
+        Ok(())
     }
 
     //------------------------------
     // EXPRESSIONS
     //------------------------------
 
+    /// `position` is the position of the statement the expression belongs to.
     fn write_expression(
         &self,
-        expression_node: &ExpressionNode,
+        expression: &Expr,
         class_name: &String,
+        position: Position,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
-        if expression_node.elements.len() >= 1 {
-            if let ExpressionElement::Term(term_node) = &expression_node.elements[0] {
-                // we assume that if the expression node contains only a single element,
-                // this element is a term node (per language specification)
-                self.write_term_node(term_node, class_name, vm_code, symbol_table);
-            }
-            for i in (1..expression_node.elements.len() - 1).step_by(2) {
-                if let ExpressionElement::Term(term_node) = &expression_node.elements[i + 1] {
-                    self.write_term_node(term_node, class_name, vm_code, symbol_table);
-                }
-                let binary_op = &expression_node.elements[i];
-                self.write_binary_operator(binary_op, vm_code);
+    ) -> Result<(), WriteError> {
+        if self.fold_constants && matches!(expression, Expr::Unary { .. } | Expr::Binary { .. } | Expr::Paren(_)) {
+            if let Some(value) = constant_value(expression) {
+                self.push_value(value, vm_code);
+                return Ok(());
             }
         }
-    }
-
-    fn write_term_node(
-        &self,
-        term_node: &TermNode,
-        class_name: &String,
-        vm_code: &mut Vec<String>,
-        symbol_table: &mut SymbolTable,
-    ) {
-        if term_node.elements.len() > 0 {
-            // a[expression]
-            if term_node.elements.len() >= 4 {
-                if let TermElement::Identifier(_) = &term_node.elements[0] {
-                    match &term_node.elements[1] {
-                        TermElement::Symbol(symbol) if symbol == "[" => {
-                            self.evaluate_array_index(term_node, class_name, vm_code, symbol_table);
-                            return;
-                        }
-                        _ => (),
-                    }
-                }
+        match expression {
+            Expr::Const(constant) => self.write_constant(constant, position, vm_code)?,
+            Expr::Var(name) => self.push_var(name, position, vm_code, symbol_table)?,
+            Expr::Index { name, index } => {
+                self.write_array_index(name, index, class_name, position, vm_code, symbol_table)?;
             }
-
-            // (-|~)(term_node)
-            if term_node.elements.len() >= 2 {
-                match &term_node.elements[0] {
-                    TermElement::Symbol(symbol) if symbol == "-" || symbol == "~" => {
-                        self.evaluate_unary_operator(term_node, class_name, vm_code, symbol_table);
-                        return;
-                    }
-                    _ => (),
+            Expr::Call(subroutine_call) => {
+                self.write_subroutine_call(subroutine_call, class_name, position, vm_code, symbol_table)?;
+            }
+            Expr::Unary { op, operand } => {
+                // evaluate the term first, and then apply the function implied by the unary operator
+                self.write_expression(operand, class_name, position, vm_code, symbol_table)?;
+                match op {
+                    UnaryOp::Neg => vm_code.push("neg".to_string()),
+                    UnaryOp::Not => vm_code.push("not".to_string()),
                 }
             }
-
-            for term_elem in term_node.elements.iter() {
-                self.write_term_element(term_elem, class_name, vm_code, symbol_table);
+            Expr::Binary { lhs, op, rhs } => {
+                self.write_expression(lhs, class_name, position, vm_code, symbol_table)?;
+                self.write_expression(rhs, class_name, position, vm_code, symbol_table)?;
+                self.write_binary_operator(op, vm_code);
+            }
+            Expr::Paren(expression) => {
+                self.write_expression(expression, class_name, position, vm_code, symbol_table)?;
             }
         }
+        Ok(())
     }
 
-    fn write_term_element(
-        &self,
-        term_element: &TermElement,
-        class_name: &String,
-        vm_code: &mut Vec<String>,
-        symbol_table: &mut SymbolTable,
-    ) {
-        match term_element {
-            TermElement::Identifier(identifier) => {
-                self.push(
-                    &symbol_table.get_var_kind(identifier),
-                    symbol_table.get_var_index(identifier),
-                    vm_code,
-                );
-            }
-            TermElement::Symbol(_) => {
-                // we can ignore symbols at this stage because
-                // the parenthesis are already implicitly there because of the parse tree structure
-                // and square brackets (array indexing) are already handled somewhere else
-            }
-            TermElement::IntegerConstant(val) => {
-                self.push("constant", *val, vm_code);
-            }
-            TermElement::KeywordConstant(keyword) => match &keyword[..] {
-                "null" => self.push("constant", 0, vm_code),
-                "false" => self.push("constant", 0, vm_code),
-                "true" => {
+    fn write_constant(&self, constant: &Const, position: Position, vm_code: &mut Vec<String>) -> Result<(), WriteError> {
+        match constant {
+            Const::Integer(val) => self.push("constant", *val, vm_code),
+            Const::String(s) => self.write_string_constant(s, position, vm_code)?,
+            Const::Keyword(keyword) => match keyword {
+                Keyword::Null => self.push("constant", 0, vm_code),
+                Keyword::False => self.push("constant", 0, vm_code),
+                Keyword::True => {
                     self.push("constant", 0, vm_code);
                     vm_code.push("not".to_string());
                 }
-                "this" => {
+                Keyword::This => {
                     // push the address of the this object onto the stack
                     self.push("pointer", 0, vm_code);
                 }
                 _ => (),
             },
-            TermElement::StringConstant(s) => {
-                self.write_string_constant(s, vm_code);
-            }
-            TermElement::Expression(expression) => {
-                self.write_expression(expression, class_name, vm_code, symbol_table);
-            }
-            TermElement::Term(term_node) => {
-                for term_elem in term_node.elements.iter() {
-                    self.write_term_element(term_elem, class_name, vm_code, symbol_table);
-                }
-            }
-            TermElement::SubroutineCall(subroutine_call) => {
-                self.write_subroutine_call(subroutine_call, class_name, vm_code, symbol_table);
-            }
         }
+        Ok(())
     }
 
-    fn write_binary_operator(&self, operator: &ExpressionElement, vm_code: &mut Vec<String>) {
-        if let ExpressionElement::Operator(operator) = operator {
-            match &operator[..] {
-                "+" => vm_code.push("add".to_string()),
-                "-" => vm_code.push("sub".to_string()),
-                "=" => vm_code.push("eq".to_string()),
-                "<" => vm_code.push("lt".to_string()),
-                ">" => vm_code.push("gt".to_string()),
-                "&" => vm_code.push("and".to_string()),
-                "|" => vm_code.push("or".to_string()),
-                "*" => vm_code.push("call Math.multiply 2".to_string()),
-                "/" => vm_code.push("call Math.divide 2".to_string()),
-                _ => {
-                    eprintln!("Error: Unknown binary operator {}:", &operator[..]);
-                    std::process::exit(1);
-                }
-            }
+    fn write_binary_operator(&self, operator: &BinaryOp, vm_code: &mut Vec<String>) {
+        match operator {
+            BinaryOp::Add => vm_code.push("add".to_string()),
+            BinaryOp::Sub => vm_code.push("sub".to_string()),
+            BinaryOp::Eq => vm_code.push("eq".to_string()),
+            BinaryOp::Lt => vm_code.push("lt".to_string()),
+            BinaryOp::Gt => vm_code.push("gt".to_string()),
+            BinaryOp::And => vm_code.push("and".to_string()),
+            BinaryOp::Or => vm_code.push("or".to_string()),
+            BinaryOp::Mul => vm_code.push("call Math.multiply 2".to_string()),
+            BinaryOp::Div => vm_code.push("call Math.divide 2".to_string()),
         }
     }
 
@@ -521,6 +595,21 @@ impl VMWriter {
         vm_code.push(format!("push {} {}", var_kind, index));
     }
 
+    /// Pushes any 16-bit value, although `push constant` only takes 0 to 32767.
+    fn push_value(&self, value: i16, vm_code: &mut Vec<String>) {
+        match value {
+            0.. => self.push("constant", value as u32, vm_code),
+            i16::MIN => {
+                self.push("constant", i16::MAX as u32, vm_code);
+                vm_code.push("not".to_string());
+            }
+            _ => {
+                self.push("constant", -value as u32, vm_code);
+                vm_code.push("neg".to_string());
+            }
+        }
+    }
+
     fn pop(&self, var_kind: &str, index: u32, vm_code: &mut Vec<String>) {
         vm_code.push(format!("pop {} {}", var_kind, index));
     }
@@ -529,107 +618,91 @@ impl VMWriter {
         vm_code.push(format!("call {} {}", subroutine_name, num_args));
     }
 
-    fn write_string_constant(&self, s: &str, vm_code: &mut Vec<String>) {
+    /// Returns the segment and index of the variable with the given name.
+    fn lookup(&self, name: &str, position: Position, symbol_table: &SymbolTable) -> Result<(String, u32), WriteError> {
+        match symbol_table.get(name) {
+            Some(symbol) => Ok((String::from(&symbol.var_kind), symbol.index)),
+            None => Err(error(position, &format!("Unknown variable \"{}\"", name))),
+        }
+    }
+
+    /// Pushes the value of the variable with the given name onto the stack.
+    fn push_var(
+        &self,
+        name: &str,
+        position: Position,
+        vm_code: &mut Vec<String>,
+        symbol_table: &SymbolTable,
+    ) -> Result<(), WriteError> {
+        let (var_kind, index) = self.lookup(name, position, symbol_table)?;
+        self.push(&var_kind, index, vm_code);
+        Ok(())
+    }
+
+    fn write_string_constant(&self, s: &str, position: Position, vm_code: &mut Vec<String>) -> Result<(), WriteError> {
+        if !s.is_ascii() {
+            return Err(error(
+                position,
+                &format!("String constants can only consist of ASCII characters \"{}\"", s),
+            ));
+        }
         self.push("constant", s.len() as u32, vm_code);
         self.call("String.new", 1, vm_code);
         for c in s.chars() {
-            if !c.is_ascii() {
-                eprintln!(
-                    "VMWrite error: String constants can only consist of ASCII characters: {}",
-                    s
-                );
-                std::process::exit(1);
-            }
             self.push("constant", c as u32, vm_code);
             self.call("String.appendChar", 2, vm_code);
         }
+        Ok(())
     }
 
-    fn evaluate_array_index(
+    fn write_array_index(
         &self,
-        term_node: &TermNode,
+        var_name: &str,
+        index: &Expr,
         class_name: &String,
+        position: Position,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
-        if let TermElement::Identifier(var_name) = &term_node.elements[0] {
-            if let TermElement::Expression(expression) = &term_node.elements[2] {
-                // this expression evaluates to the index
-                self.write_expression(expression, class_name, vm_code, symbol_table);
-                // base address of array
-                self.push(
-                    &symbol_table.get_var_kind(var_name),
-                    symbol_table.get_var_index(var_name),
-                    vm_code,
-                );
-                // add index to base address
-                vm_code.push("add".to_string());
-                // set the "that" segment to this address
-                self.pop("pointer", 1, vm_code);
-                // push the contents from "that" to the stack
-                self.push("that", 0, vm_code);
-            }
-        };
-    }
-
-    fn evaluate_unary_operator(
-        &self,
-        term_node: &TermNode,
-        class_name: &String,
-        vm_code: &mut Vec<String>,
-        symbol_table: &mut SymbolTable,
-    ) {
-        match &term_node.elements[0] {
-            // if a unary operator (- or ~) is followed by a term,
-            // we have to evaluate the term first, and then apply the
-            // function implied by the unary operator
-            TermElement::Symbol(symbol) if symbol == "-" || symbol == "~" => {
-                if term_node.elements.len() > 1 {
-                    self.write_term_element(
-                        &term_node.elements[1],
-                        class_name,
-                        vm_code,
-                        symbol_table,
-                    );
-                }
-                if symbol == "-" {
-                    vm_code.push("neg".to_string());
-                } else {
-                    vm_code.push("not".to_string());
-                }
-            }
-            _ => (),
-        }
+    ) -> Result<(), WriteError> {
+        // this expression evaluates to the index
+        self.write_expression(index, class_name, position, vm_code, symbol_table)?;
+        // base address of array
+        self.push_var(var_name, position, vm_code, symbol_table)?;
+        // add index to base address
+        vm_code.push("add".to_string());
+        // set the "that" segment to this address
+        self.pop("pointer", 1, vm_code);
+        // push the contents from "that" to the stack
+        self.push("that", 0, vm_code);
+        Ok(())
     }
 
     fn write_subroutine_call(
         &self,
-        subroutine_call: &SubroutineCallNode,
+        subroutine_call: &SubroutineCall,
         class_name: &String,
+        position: Position,
         vm_code: &mut Vec<String>,
         symbol_table: &mut SymbolTable,
-    ) {
+    ) -> Result<(), WriteError> {
         let (caller, this_count) = if let Some(caller) = &subroutine_call.caller {
-            if symbol_table.contains(caller) {
+            if let Some(var_type) = symbol_table.get_var_type(caller) {
                 // I have to clone here because if I borrow here, I cannot create
                 // a mutable reference of symbol_table below when processing the arguments
-                let var_type = symbol_table.get_var_type(caller).clone();
+                let var_type = var_type.clone();
                 if let VarType::ClassName(class_name) = var_type {
                     // in this case the subroutine is a method and we have to push
                     // the this object to the stack
-                    self.push(
-                        &symbol_table.get_var_kind(caller),
-                        symbol_table.get_var_index(caller),
-                        vm_code,
-                    );
+                    self.push_var(caller, position, vm_code, symbol_table)?;
                     (class_name, 1)
                 } else {
-                    // dummy, cannot happen
-                    eprintln!(
-                        "VMWrite Error: Callee must have var type ClassName: {}",
-                        var_type
-                    );
-                    std::process::exit(1);
+                    return Err(error(
+                        position,
+                        &format!(
+                            "Cannot call a method on \"{}\", which has the type {}",
+                            caller, var_type
+                        ),
+                    ));
                 }
             } else {
                 // p.279 of the book: any identifier not found in the symbol table may be assumed
@@ -642,14 +715,62 @@ impl VMWriter {
             (class_name.to_string(), 1)
         };
 
-        let num_args = subroutine_call.expression_list.len() + this_count;
-        for argument in subroutine_call.expression_list.iter() {
-            self.write_expression(argument, class_name, vm_code, symbol_table);
+        let num_args = subroutine_call.arguments.len() + this_count;
+        for argument in subroutine_call.arguments.iter() {
+            self.write_expression(argument, class_name, position, vm_code, symbol_table)?;
         }
         self.call(
             &format!("{}.{}", caller, &subroutine_call.subroutine_name),
             num_args as u32,
             vm_code,
         );
+        Ok(())
     }
 }
+
+/// Returns an error at the given position. The line is filled in by
+/// [`write_with_debug_info`](VMWriter::write_with_debug_info).
+fn error(position: Position, message: &str) -> WriteError {
+    WriteError {
+        message: message.to_string(),
+        position,
+        line: String::new(),
+    }
+}
+
+/// Returns the value of an expression that only consists of constants, computed the way
+/// the VM and the operating system compute it: in 16 bits, with `true` being `-1`.
+/// Returns `None` for anything else, and for a division the compiler cannot do exactly
+/// like `Math.divide`, i.e. by zero or with negative operands.
+fn constant_value(expression: &Expr) -> Option<i16> {
+    let value = match expression {
+        Expr::Const(Const::Integer(n)) => i16::try_from(*n).ok()?,
+        Expr::Const(Const::Keyword(Keyword::True)) => -1,
+        Expr::Const(Const::Keyword(Keyword::False | Keyword::Null)) => 0,
+        Expr::Paren(inner) => constant_value(inner)?,
+        Expr::Unary { op, operand } => {
+            let operand = constant_value(operand)?;
+            match op {
+                UnaryOp::Neg => operand.wrapping_neg(),
+                UnaryOp::Not => !operand,
+            }
+        }
+        Expr::Binary { lhs, op, rhs } => {
+            let (lhs, rhs) = (constant_value(lhs)?, constant_value(rhs)?);
+            match op {
+                BinaryOp::Add => lhs.wrapping_add(rhs),
+                BinaryOp::Sub => lhs.wrapping_sub(rhs),
+                BinaryOp::Mul => lhs.wrapping_mul(rhs),
+                BinaryOp::Div if lhs >= 0 && rhs > 0 => lhs / rhs,
+                BinaryOp::Div => return None,
+                BinaryOp::And => lhs & rhs,
+                BinaryOp::Or => lhs | rhs,
+                BinaryOp::Lt => -((lhs < rhs) as i16),
+                BinaryOp::Gt => -((lhs > rhs) as i16),
+                BinaryOp::Eq => -((lhs == rhs) as i16),
+            }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
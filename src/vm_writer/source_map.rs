@@ -0,0 +1,84 @@
+//! Maps the emitted VM instructions back to the Jack code they were generated from.
+use crate::tokenizer::tokens::Position;
+
+/// The origin of a single VM instruction: the enclosing subroutine and the position
+/// of the Jack statement (or subroutine declaration) that produced it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    pub subroutine: String,
+    pub position: Position,
+}
+
+/// Holds one [`SourceMapEntry`] per emitted VM instruction, in the same order as the instructions.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    pub entries: Vec<SourceMapEntry>,
+    scopes: Vec<SourceMapEntry>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap {
+            entries: Vec::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Returns the entry of the VM instruction with the given index (starting at 0).
+    pub fn get(&self, instruction: usize) -> Option<&SourceMapEntry> {
+        self.entries.get(instruction)
+    }
+
+    /// Returns the index of the first VM instruction that was generated from the given line.
+    pub fn first_instruction_of_line(&self, line: usize) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.position.line == line)
+    }
+
+    /// Returns the index of the first VM instruction of the given subroutine (`Class.subroutine`).
+    pub fn first_instruction_of_subroutine(&self, subroutine: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.subroutine == subroutine)
+    }
+
+    /// Starts the scope of a subroutine (`Class.subroutine`) declared at the given position.
+    /// All instructions emitted from now on are attributed to this scope until
+    /// [`exit`](SourceMap::exit) is called.
+    /// `num_instructions` is the number of instructions emitted so far.
+    pub fn enter_subroutine(&mut self, subroutine: &str, position: Position, num_instructions: usize) {
+        self.fill(num_instructions);
+        self.scopes.push(SourceMapEntry {
+            subroutine: subroutine.to_string(),
+            position,
+        });
+    }
+
+    /// Starts the scope of a statement at the given position inside the current subroutine.
+    pub fn enter_statement(&mut self, position: Position, num_instructions: usize) {
+        self.fill(num_instructions);
+        let subroutine = match self.scopes.last() {
+            Some(scope) => scope.subroutine.clone(),
+            None => String::new(),
+        };
+        self.scopes.push(SourceMapEntry {
+            subroutine,
+            position,
+        });
+    }
+
+    /// Closes the innermost scope. Subsequent instructions are attributed to the enclosing scope.
+    pub fn exit(&mut self, num_instructions: usize) {
+        self.fill(num_instructions);
+        self.scopes.pop();
+    }
+
+    fn fill(&mut self, num_instructions: usize) {
+        if let Some(scope) = self.scopes.last() {
+            while self.entries.len() < num_instructions {
+                self.entries.push(scope.clone());
+            }
+        }
+    }
+}
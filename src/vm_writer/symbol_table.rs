@@ -1,7 +1,8 @@
+//! Keeps track of the variables of the class and subroutine scope.
 use crate::parser::parse_tree::var::{VarKind, VarType};
 use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Symbol {
     pub name: String,
     pub var_type: VarType,
@@ -9,7 +10,7 @@ pub struct Symbol {
     pub index: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct SymbolTable {
     pub class_scope: HashMap<String, Symbol>,
     pub subroutine_scope: HashMap<String, Symbol>,
@@ -23,9 +24,9 @@ impl SymbolTable {
         }
     }
 
-    pub fn define(&mut self, name: &String, var_type: &VarType, var_kind: &VarKind, index: u32) {
+    pub fn define(&mut self, name: &str, var_type: &VarType, var_kind: &VarKind, index: u32) {
         let symbol = Symbol {
-            name: name.clone(),
+            name: name.to_string(),
             var_type: var_type.clone(),
             var_kind: var_kind.clone(),
             index,
@@ -33,49 +34,31 @@ impl SymbolTable {
 
         match var_kind {
             VarKind::Static | VarKind::Field => {
-                self.class_scope.insert(name.clone(), symbol);
+                self.class_scope.insert(name.to_string(), symbol);
             }
             VarKind::Var | VarKind::Arg => {
-                self.subroutine_scope.insert(name.clone(), symbol);
+                self.subroutine_scope.insert(name.to_string(), symbol);
             }
         }
     }
 
-    pub fn get_var_type(&self, name: &String) -> &VarType {
-        if self.subroutine_scope.contains_key(name) {
-            &self.subroutine_scope.get(name).unwrap().var_type
-        } else {
-            &self
-                .class_scope
-                .get(name)
-                .expect(&format!("Unknown variable name: {}", name))
-                .var_type
-        }
+    /// Returns the variable with the given name, or `None` if it is not declared.
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.subroutine_scope
+            .get(name)
+            .or_else(|| self.class_scope.get(name))
     }
 
-    pub fn get_var_kind(&self, name: &String) -> String {
-        if self.subroutine_scope.contains_key(name) {
-            String::from(&self.subroutine_scope.get(name).unwrap().var_kind)
-        } else {
-            String::from(
-                &self
-                    .class_scope
-                    .get(name)
-                    .expect(&format!("Unknown variable name: {}", name))
-                    .var_kind,
-            )
-        }
+    pub fn get_var_type(&self, name: &str) -> Option<&VarType> {
+        self.get(name).map(|symbol| &symbol.var_type)
     }
 
-    pub fn get_var_index(&self, name: &String) -> u32 {
-        if self.subroutine_scope.contains_key(name) {
-            self.subroutine_scope.get(name).unwrap().index
-        } else {
-            self.class_scope
-                .get(name)
-                .expect(&format!("Unknown variable name: {}", name))
-                .index
-        }
+    pub fn get_var_kind(&self, name: &str) -> Option<String> {
+        self.get(name).map(|symbol| String::from(&symbol.var_kind))
+    }
+
+    pub fn get_var_index(&self, name: &str) -> Option<u32> {
+        self.get(name).map(|symbol| symbol.index)
     }
 
     pub fn contains(&self, name: &String) -> bool {
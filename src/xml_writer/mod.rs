@@ -0,0 +1,377 @@
+//! Takes in a parse tree and writes the XML representation specified in the book.
+use crate::parser::parse_tree::ParseTree;
+use crate::parser::parse_tree::class::{ClassNode, ClassVarNode};
+use crate::parser::parse_tree::expression::{Const, Expr, SubroutineCall};
+use crate::parser::parse_tree::statement::{LetTarget, Statement};
+use crate::parser::parse_tree::subroutine::{ParameterListNode, SubroutineBodyNode, SubroutineNode};
+use crate::parser::parse_tree::var::{VarNode, VarType};
+
+/// Takes in a parse tree and writes the XML representation specified in the book.
+/// Every non-terminal of the grammar becomes an element, every token a leaf.
+pub struct XmlWriter {}
+
+impl Default for XmlWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XmlWriter {
+    pub fn new() -> XmlWriter {
+        XmlWriter {}
+    }
+
+    /// Returns the XML representation of the class in the given parse tree.
+    pub fn write(&self, parse_tree: &ParseTree) -> String {
+        self.write_class(&parse_tree.class_node)
+    }
+
+    //------------------------------
+    // CLASSES
+    //------------------------------
+
+    fn write_class(&self, class: &ClassNode) -> String {
+        let mut variables = String::new();
+        for variable in class.variables.iter() {
+            variables.push_str(&format!("{}\n", self.write_class_var(variable)));
+        }
+        let mut subroutines = String::new();
+        for subroutine in class.subroutines.iter() {
+            subroutines.push_str(&self.write_subroutine(subroutine));
+        }
+        format!(
+            "<class>\n\
+            <keyword> class </keyword>\n\
+            <identifier> {} </identifier>\n\
+            <symbol> {{ </symbol>\n\
+            {}\
+            {}\
+            <symbol> }} </symbol>\n\
+            </class>\n",
+            class.name, variables, subroutines
+        )
+    }
+
+    fn write_class_var(&self, class_var: &ClassVarNode) -> String {
+        let mut names = String::new();
+        if !class_var.names.is_empty() {
+            for name in class_var.names[..class_var.names.len() - 1].iter() {
+                names.push_str(&format!(
+                    "<identifier> {} </identifier>\n\
+                     <symbol> , </symbol>\n",
+                    name
+                ));
+            }
+            names.push_str(&format!(
+                "<identifier> {} </identifier>\n\
+                 <symbol> ; </symbol>",
+                class_var.names[class_var.names.len() - 1]
+            ));
+        }
+        format!(
+            "<classVarDec>\n\
+            <keyword> {} </keyword>\n\
+            {}\n\
+            {}\n\
+            </classVarDec>",
+            class_var.var_kind,
+            self.write_var_type(&class_var.var_type),
+            names
+        )
+    }
+
+    fn write_var(&self, var: &VarNode) -> String {
+        let mut var_names = String::new();
+        for i in 0..var.var_names.len() - 1 {
+            var_names.push_str(&format!(
+                "<identifier> {} </identifier>\n<symbol> , </symbol>\n",
+                var.var_names[i]
+            ));
+        }
+        var_names.push_str(&format!(
+            "<identifier> {} </identifier>\n",
+            var.var_names[var.var_names.len() - 1]
+        ));
+        format!(
+            "<varDec>\n\
+            <keyword> {} </keyword>\n\
+            {}\n\
+            {}\
+            <symbol> ; </symbol>\n\
+            </varDec>",
+            var.var_kind,
+            self.write_var_type(&var.var_type),
+            var_names
+        )
+    }
+
+    fn write_var_type(&self, var_type: &VarType) -> String {
+        match var_type {
+            VarType::ClassName(name) => format!("<identifier> {} </identifier>", name),
+            _ => format!("<keyword> {} </keyword>", var_type),
+        }
+    }
+
+    //------------------------------
+    // SUBROUTINES
+    //------------------------------
+
+    fn write_subroutine(&self, subroutine: &SubroutineNode) -> String {
+        let return_type = match &subroutine.return_type {
+            Some(return_type) => self.write_var_type(return_type),
+            None => "<keyword> void </keyword>".to_string(),
+        };
+        format!(
+            "<subroutineDec>\n\
+            <keyword> {} </keyword>\n\
+            {}\n\
+            <identifier> {} </identifier>\n\
+            <symbol> ( </symbol>\n\
+            {}\n\
+            <symbol> ) </symbol>\n\
+            {}\
+            </subroutineDec>\n",
+            subroutine.subroutine_type,
+            return_type,
+            subroutine.name,
+            self.write_parameter_list(&subroutine.parameter_list),
+            self.write_subroutine_body(&subroutine.body)
+        )
+    }
+
+    fn write_parameter_list(&self, parameter_list: &ParameterListNode) -> String {
+        if parameter_list.parameters.is_empty() {
+            return "<parameterList>\n</parameterList>".to_string();
+        }
+        let parameters: Vec<String> = parameter_list
+            .parameters
+            .iter()
+            .map(|parameter| {
+                format!(
+                    "{}\n<identifier> {} </identifier>",
+                    self.write_var_type(&parameter.var_type),
+                    parameter.name
+                )
+            })
+            .collect();
+        format!(
+            "<parameterList>\n{}\n</parameterList>",
+            parameters.join("\n<symbol> , </symbol>\n")
+        )
+    }
+
+    fn write_subroutine_body(&self, body: &SubroutineBodyNode) -> String {
+        let mut vars = String::new();
+        for variable in body.variables.iter() {
+            vars.push_str(&format!("{}\n", self.write_var(variable)));
+        }
+        format!(
+            "<subroutineBody>\n\
+            <symbol> {{ </symbol>\n\
+            {}\
+            <statements>\n\
+            {}\
+            </statements>\n\
+            <symbol> }} </symbol>\n\
+            </subroutineBody>\n",
+            vars,
+            self.write_statements(&body.statements)
+        )
+    }
+
+    //------------------------------
+    // STATEMENTS
+    //------------------------------
+
+    fn write_statements(&self, statements: &[Statement]) -> String {
+        statements
+            .iter()
+            .map(|statement| self.write_statement(statement))
+            .collect()
+    }
+
+    /// `{ statements }`
+    fn write_block(&self, statements: &[Statement]) -> String {
+        format!(
+            "<symbol> {{ </symbol>\n<statements>\n{}</statements>\n<symbol> }} </symbol>\n",
+            self.write_statements(statements)
+        )
+    }
+
+    fn write_statement(&self, statement: &Statement) -> String {
+        match statement {
+            Statement::Let(statement) => {
+                let target = match &statement.target {
+                    LetTarget::Var(name) => format!("<identifier> {} </identifier>\n", name),
+                    LetTarget::Index { name, index } => format!(
+                        "<identifier> {} </identifier>\n\
+                        <symbol> [ </symbol>\n\
+                        {}\n\
+                        <symbol> ] </symbol>\n",
+                        name,
+                        self.write_expression(index)
+                    ),
+                };
+                format!(
+                    "<letStatement>\n\
+                    <keyword> let </keyword>\n\
+                    {}\
+                    <symbol> = </symbol>\n\
+                    {}\n\
+                    <symbol> ; </symbol>\n\
+                    </letStatement>\n",
+                    target,
+                    self.write_expression(&statement.value)
+                )
+            }
+            Statement::If(statement) => {
+                let else_block = match &statement.else_block {
+                    Some(else_block) => {
+                        format!("<keyword> else </keyword>\n{}", self.write_block(else_block))
+                    }
+                    None => String::new(),
+                };
+                format!(
+                    "<ifStatement>\n\
+                    <keyword> if </keyword>\n\
+                    <symbol> ( </symbol>\n\
+                    {}\n\
+                    <symbol> ) </symbol>\n\
+                    {}\
+                    {}\
+                    </ifStatement>\n",
+                    self.write_expression(&statement.condition),
+                    self.write_block(&statement.if_block),
+                    else_block
+                )
+            }
+            Statement::While(statement) => format!(
+                "<whileStatement>\n\
+                <keyword> while </keyword>\n\
+                <symbol> ( </symbol>\n\
+                {}\n\
+                <symbol> ) </symbol>\n\
+                {}\
+                </whileStatement>\n",
+                self.write_expression(&statement.condition),
+                self.write_block(&statement.block)
+            ),
+            Statement::Do(statement) => format!(
+                "<doStatement>\n\
+                <keyword> do </keyword>\n\
+                {}\n\
+                <symbol> ; </symbol>\n\
+                </doStatement>\n",
+                self.write_subroutine_call(&statement.subroutine_call)
+            ),
+            Statement::Return(statement) => match &statement.expression {
+                Some(expression) => format!(
+                    "<returnStatement>\n\
+                    <keyword> return </keyword>\n\
+                    {}\n\
+                    <symbol> ; </symbol>\n\
+                    </returnStatement>\n",
+                    self.write_expression(expression)
+                ),
+                None => "<returnStatement>\n\
+                    <keyword> return </keyword>\n\
+                    <symbol> ; </symbol>\n\
+                    </returnStatement>\n"
+                    .to_string(),
+            },
+        }
+    }
+
+    //------------------------------
+    // EXPRESSIONS
+    //------------------------------
+
+    /// Grammar rule: term (op term)*
+    fn write_expression(&self, expression: &Expr) -> String {
+        let mut elements = Vec::new();
+        self.write_expression_elements(expression, &mut elements);
+        let mut xml = String::from("<expression>\n");
+        for element in elements {
+            xml.push_str(&element);
+            xml.push('\n');
+        }
+        xml.push_str("</expression>");
+        xml
+    }
+
+    /// Flattens a chain of binary operators back into `term (op term)*`. With operator
+    /// precedence the right operand can be a chain as well, which flattens the same way.
+    fn write_expression_elements(&self, expression: &Expr, elements: &mut Vec<String>) {
+        match expression {
+            Expr::Binary { lhs, op, rhs } => {
+                self.write_expression_elements(lhs, elements);
+                elements.push(format!("<symbol> {} </symbol>", op.symbol().to_xml()));
+                self.write_expression_elements(rhs, elements);
+            }
+            _ => elements.push(self.write_term(expression)),
+        }
+    }
+
+    fn write_term(&self, expression: &Expr) -> String {
+        let elements = match expression {
+            Expr::Const(Const::Integer(n)) => {
+                vec![format!("<integerConstant> {} </integerConstant>", n)]
+            }
+            Expr::Const(Const::String(s)) => {
+                vec![format!("<stringConstant> {} </stringConstant>", s)]
+            }
+            Expr::Const(Const::Keyword(keyword)) => {
+                vec![format!("<keyword> {} </keyword>", keyword)]
+            }
+            Expr::Var(name) => vec![format!("<identifier> {} </identifier>", name)],
+            Expr::Index { name, index } => vec![
+                format!("<identifier> {} </identifier>", name),
+                "<symbol> [ </symbol>".to_string(),
+                self.write_expression(index),
+                "<symbol> ] </symbol>".to_string(),
+            ],
+            Expr::Call(subroutine_call) => vec![self.write_subroutine_call(subroutine_call)],
+            Expr::Unary { op, operand } => vec![
+                format!("<symbol> {} </symbol>", op.symbol()),
+                self.write_term(operand),
+            ],
+            Expr::Paren(inner) => self.write_parenthesized(inner),
+            // a binary expression can only be a term inside parentheses
+            Expr::Binary { .. } => self.write_parenthesized(expression),
+        };
+        format!("<term>\n{}\n</term>", elements.join("\n"))
+    }
+
+    fn write_parenthesized(&self, expression: &Expr) -> Vec<String> {
+        vec![
+            "<symbol> ( </symbol>".to_string(),
+            self.write_expression(expression),
+            "<symbol> ) </symbol>".to_string(),
+        ]
+    }
+
+    fn write_subroutine_call(&self, subroutine_call: &SubroutineCall) -> String {
+        let caller = match &subroutine_call.caller {
+            Some(name) => format!(
+                "<identifier> {} </identifier>\n<symbol> . </symbol>\n",
+                name
+            ),
+            None => String::new(),
+        };
+        let mut expression_list = String::from("<symbol> ( </symbol>\n<expressionList>\n");
+        let arguments: Vec<String> = subroutine_call
+            .arguments
+            .iter()
+            .map(|argument| self.write_expression(argument))
+            .collect();
+        if !arguments.is_empty() {
+            expression_list.push_str(&arguments.join("\n<symbol> , </symbol>\n"));
+            expression_list.push('\n');
+        }
+        expression_list.push_str("</expressionList>\n<symbol> ) </symbol>");
+        format!(
+            "{}<identifier> {} </identifier>\n{}",
+            caller, subroutine_call.subroutine_name, expression_list
+        )
+    }
+}
@@ -0,0 +1,22 @@
+//! Helpers shared by the integration tests.
+use std::fs;
+use std::path::Path;
+
+/// Returns the paths of the Jack files in `dir` and its subdirectories, in ascending order.
+pub fn jack_files(dir: &str) -> Vec<String> {
+    let mut files = Vec::new();
+    add_jack_files(Path::new(dir), &mut files);
+    files.sort();
+    files
+}
+
+fn add_jack_files(dir: &Path, files: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            add_jack_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "jack") {
+            files.push(path.to_str().unwrap().to_string());
+        }
+    }
+}
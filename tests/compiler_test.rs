@@ -0,0 +1,568 @@
+use std::fs;
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use jack_compiler::compiler::error::CompileError;
+use jack_compiler::compiler::options::{CompileOptions, Emit, Extension, OptLevel};
+use jack_compiler::compiler::output::ArtifactKind;
+use jack_compiler::compiler::Compiler;
+use jack_compiler::io::source::{DiskProvider, FileProvider, MemoryProvider, Source};
+
+#[test]
+fn compile_str_test() {
+    let compiler = Compiler::new();
+    let source = fs::read_to_string("tests/aux_files/Seven/Main.jack").unwrap();
+    let target_vm = fs::read_to_string("tests/aux_files/Seven/Main.vm").unwrap();
+    assert_eq!(compiler.compile_str("Main.jack", &source).unwrap(), target_vm);
+    let error = compiler.compile_str("Main.jack", "class Main { # }");
+    assert!(matches!(error, Err(CompileError::Lex { .. })));
+    // a syntax error is returned to the caller instead of ending the process
+    let error = compiler.compile_str("Main.jack", "class Main { function void main() { return }").unwrap_err();
+    assert!(matches!(error, CompileError::Parse { .. }));
+    assert_eq!(
+        error.to_string(),
+        "Main.jack: Parse Error in line 1, column 44: Unexpected token found in term: class Main { function void main() { return }"
+    );
+}
+
+#[test]
+fn write_error_test() {
+    let compiler = Compiler::new();
+    let error = compiler
+        .compile_str("Main.jack", "class Main {\n  function void main() {\n    let x = 1;\n    return;\n  }\n}")
+        .unwrap_err();
+    assert!(matches!(error, CompileError::Write { .. }));
+    assert_eq!(
+        error.to_string(),
+        "Main.jack: VMWrite Error in line 3, column 5: Unknown variable \"x\": let x = 1;"
+    );
+    let error = compiler
+        .compile_str("Main.jack", "class Main { function void main() { do Output.printString(\"é\"); return; } }")
+        .unwrap_err();
+    assert!(matches!(error, CompileError::Write { .. }));
+    let error = compiler
+        .compile_str("Main.jack", "class Main { function void main() { var int x; do x.f(); return; } }")
+        .unwrap_err();
+    assert!(error.to_string().contains("Cannot call a method on \"x\", which has the type int"));
+}
+
+#[test]
+fn memory_provider_test() {
+    let mut files = MemoryProvider::new();
+    files.insert(
+        "Main.jack",
+        "class Main { function void main() { do Point.new(); return; } }",
+    );
+    files.insert("Point.jack", "class Point { constructor Point new() { return this; } }");
+    files.insert("README.md", "not a Jack file");
+    assert_eq!(files.jack_files().unwrap(), vec!["Main.jack", "Point.jack"]);
+
+    let output = Compiler::new().compile_files(&files).unwrap();
+    assert_eq!(output.classes.len(), 2);
+    let main = &output.classes[0];
+    assert_eq!(main.name, "Main");
+    let vm = main.artifact(ArtifactKind::Vm).unwrap();
+    assert_eq!(vm.path, PathBuf::from("Main.vm"));
+    assert_eq!(
+        vm.content,
+        "function Main.main 0\ncall Point.new 0\npop temp 0\npush constant 0\nreturn\n"
+    );
+    assert_eq!(output.classes[1].source, "Point.jack");
+    assert!(files.read("Missing.jack").is_err());
+}
+
+#[test]
+fn disk_provider_test() {
+    let files = DiskProvider::new("tests/aux_files/Square");
+    let sources = files.sources().unwrap();
+    let stems: Vec<&str> = sources.iter().map(Source::stem).collect();
+    assert_eq!(stems, vec!["Main", "Square", "SquareGame"]);
+
+    for class in Compiler::new().compile_files(&files).unwrap().classes {
+        let vm = class.artifact(ArtifactKind::Vm).unwrap();
+        assert_eq!(vm.path, PathBuf::from(class.source.replace(".jack", ".vm")));
+        assert_eq!(vm.content, fs::read_to_string(&vm.path).unwrap());
+    }
+    assert!(DiskProvider::new("tests/aux_files/Square/Main.vm").jack_files().is_err());
+}
+
+#[test]
+fn duplicate_class_test() {
+    let mut files = MemoryProvider::new();
+    files.insert("A.jack", "class Main { }");
+    files.insert("B.jack", "// B\nclass Main { }");
+    let compiler = Compiler::with_options(CompileOptions {
+        allow_file_name_mismatch: true,
+        ..CompileOptions::default()
+    });
+    let error = compiler.compile_files(&files).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "B.jack: Error in line 2, column 7: Class \"Main\" is already defined in 'A.jack'"
+    );
+}
+
+#[test]
+fn compile_options_test() {
+    let compiler = Compiler::with_options(CompileOptions {
+        emit: Emit {
+            tokens: true,
+            xml: true,
+            vm: true,
+        },
+        out_dir: Some(PathBuf::from("out")),
+        debug_info: true,
+        ..CompileOptions::default()
+    });
+    let source = Source::new(
+        "src/Square/Main.jack",
+        &fs::read_to_string("tests/aux_files/Square/Main.jack").unwrap(),
+    );
+    let class = compiler.compile(&source).unwrap();
+    let paths: Vec<PathBuf> = class
+        .artifacts
+        .iter()
+        .map(|artifact| artifact.path.clone())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("out/MainT.xml"),
+            PathBuf::from("out/Main.xml"),
+            PathBuf::from("out/Main.vm"),
+            PathBuf::from("out/Main.vmdbg"),
+        ]
+    );
+    let xml = &class.artifact(ArtifactKind::Xml).unwrap().content;
+    let target_xml = fs::read_to_string("tests/aux_files/Square/Main.xml").unwrap();
+    assert_eq!(
+        xml.split_whitespace().collect::<String>(),
+        target_xml.split_whitespace().collect::<String>()
+    );
+    assert!(class
+        .artifact(ArtifactKind::DebugInfo)
+        .unwrap()
+        .content
+        .contains("\"file\": \"Main.jack\""));
+}
+
+#[test]
+fn out_dir_test() {
+    let mut files = MemoryProvider::new();
+    files.insert("Main.jack", "class Main { function void f() { return; } }");
+    files.insert("lib/Point.jack", "class Point { function void g() { return; } }");
+    let compiler = Compiler::with_options(CompileOptions {
+        out_dir: Some(PathBuf::from("out")),
+        ..CompileOptions::default()
+    });
+    let output = compiler.compile_files(&files).unwrap();
+    let paths: Vec<&PathBuf> = output
+        .classes
+        .iter()
+        .map(|class| &class.artifacts[0].path)
+        .collect();
+    assert_eq!(paths, vec![&PathBuf::from("out/Main.vm"), &PathBuf::from("out/lib/Point.vm")]);
+
+    let mut printed = Vec::new();
+    output.print(&mut printed).unwrap();
+    assert_eq!(
+        String::from_utf8(printed).unwrap(),
+        "==> out/Main.vm <==\nfunction Main.f 0\npush constant 0\nreturn\n\n\
+         ==> out/lib/Point.vm <==\nfunction Point.g 0\npush constant 0\nreturn\n"
+    );
+}
+
+#[test]
+fn stdin_test() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+        .args(["--stdout", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(fs::read_to_string("tests/aux_files/Seven/Main.jack").unwrap().as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let target_vm = fs::read_to_string("tests/aux_files/Seven/Main.vm").unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), target_vm);
+}
+
+#[test]
+fn emit_command_test() {
+    let dir = std::env::temp_dir().join("jack_compiler_emit_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/Main.jack"), "class Main { function void main() { return; } }").unwrap();
+    let compile = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+            .args(args)
+            .arg(dir.join("src"))
+            .output()
+            .unwrap()
+    };
+
+    // the outputs are written next to the Jack file
+    assert!(compile(&["--emit", "tokens,xml,vm"]).status.success());
+    assert!(fs::read_to_string(dir.join("src/MainT.xml")).unwrap().starts_with("<tokens>"));
+    assert!(fs::read_to_string(dir.join("src/Main.xml")).unwrap().starts_with("<class>"));
+    assert!(fs::read_to_string(dir.join("src/Main.vm")).unwrap().starts_with("function Main.main 0"));
+
+    // or in the output directory
+    let out_dir = dir.join("out");
+    let out_dir_arg = out_dir.to_str().unwrap();
+    assert!(compile(&["--emit=xml", "-o", out_dir_arg]).status.success());
+    assert!(out_dir.join("Main.xml").is_file());
+    assert!(!out_dir.join("MainT.xml").exists());
+    assert!(!out_dir.join("Main.vm").exists());
+
+    let output = compile(&["--emit", "vm,asm"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown output for --emit: 'asm' (expected tokens, xml or vm)"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn watch_command_test() {
+    let dir = std::env::temp_dir().join("jack_compiler_watch_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/Main.jack"), "class Main { function void main() { return; } }").unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+        .args(["watch", "--exec", "touch 'built main'", "-o", "out", "src"])
+        .current_dir(&dir)
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    // the command is run by the shell, so its arguments can be quoted
+    let built = dir.join("built main");
+    for _ in 0..100 {
+        if built.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(built.is_file());
+    // the cache is kept in the output directory
+    assert!(dir.join("out/.jack-cache").is_file());
+    assert!(!dir.join(".jack-cache").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn profile_command_test() {
+    let dir = std::env::temp_dir().join("jack_compiler_profile_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::create_dir_all(dir.join("os")).unwrap();
+    fs::write(
+        dir.join("src/Main.jack"),
+        "class Main { function void main() { do Output.printInt(6 * 7); return; } }",
+    )
+    .unwrap();
+    // a minimal operating system, compiled ahead like the one of the book
+    let sys = "class Sys { function void init() { do Main.main(); do Sys.halt(); return; } \
+               function void halt() { while (true) {} return; } }";
+    let output = "class Output { function void printInt(int i) { return; } }";
+    let math = "class Math { function int multiply(int x, int y) { return 42; } }";
+    for (name, class) in [("Sys", sys), ("Output", output), ("Math", math)] {
+        let vm_code = Compiler::new().compile_str(&format!("{}.jack", name), class).unwrap();
+        fs::write(dir.join(format!("os/{}.vm", name)), vm_code).unwrap();
+    }
+    let profile = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+            .arg("profile")
+            .args(args)
+            .arg("src")
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+
+    let output = profile(&["--os", "os", "--folded", "main.folded"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with(" inclusive       %  exclusive       %     calls  subroutine\n"));
+    assert!(stdout.contains("         1  Math.multiply\n"));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("The program halted after"));
+    let folded = fs::read_to_string(dir.join("main.folded")).unwrap();
+    assert!(folded.contains("Sys.init;Main.main;Output.printInt 3\n"));
+    // nothing is written next to the Jack files
+    assert!(!dir.join("src/Main.vm").exists());
+
+    // without --os, the program runs on the bundled operating system
+    let output = profile(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("         1  Output.printInt\n"));
+    assert!(stdout.contains("  Memory.alloc\n"));
+
+    fs::create_dir_all(dir.join("empty")).unwrap();
+    let output = profile(&["--os", "empty"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("the program has no function 'Sys.init'"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn subcommand_name_test() {
+    // a directory named like a subcommand is compiled when it is given as a path or after `--`
+    let dir = std::env::temp_dir().join("jack_compiler_subcommand_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("doc")).unwrap();
+    fs::write(dir.join("doc/Main.jack"), "class Main { function void main() { return; } }").unwrap();
+    let compile = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+
+    assert!(compile(&["--", "doc"]).status.success());
+    assert!(dir.join("doc/Main.vm").is_file());
+    fs::remove_file(dir.join("doc/Main.vm")).unwrap();
+    assert!(compile(&["./doc"]).status.success());
+    assert!(dir.join("doc/Main.vm").is_file());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn jobs_test() {
+    let compile = |dir: &str, jobs: usize| {
+        let compiler = Compiler::with_options(CompileOptions {
+            emit: Emit {
+                tokens: true,
+                xml: true,
+                vm: true,
+            },
+            jobs,
+            ..CompileOptions::default()
+        });
+        let output = compiler.compile_files(&DiskProvider::new(dir)).unwrap();
+        let mut artifacts = Vec::new();
+        for class in output.classes {
+            for artifact in class.artifacts {
+                artifacts.push((class.name.clone(), artifact.path, artifact.content));
+            }
+        }
+        artifacts
+    };
+    for dir in ["Pong", "Square", "ComplexArrays", "ConvertToBin"] {
+        let dir = format!("tests/aux_files/{}", dir);
+        let sequential = compile(&dir, 1);
+        assert_eq!(compile(&dir, 4), sequential);
+        assert_eq!(compile(&dir, 0), sequential);
+    }
+
+    // the first error in file order is reported, no matter which thread finds it first
+    let mut files = MemoryProvider::new();
+    for i in 0..16 {
+        let name = format!("C{:02}.jack", i);
+        let text = match i {
+            5 => "class C05 { field int x }".to_string(),
+            9 => "class C09 { ~ }".to_string(),
+            12 => "class C12 { method }".to_string(),
+            _ => format!("class C{:02} {{ function void f() {{ return; }} }}", i),
+        };
+        files.insert(&name, &text);
+    }
+    for jobs in [1, 2, 8] {
+        let compiler = Compiler::with_options(CompileOptions {
+            jobs,
+            ..CompileOptions::default()
+        });
+        let error = compiler.compile_files(&files).unwrap_err();
+        assert!(matches!(error, CompileError::Parse { .. }));
+        assert_eq!(
+            error.to_string(),
+            "C05.jack: Parse Error in line 1, column 25: Expected ';' but found '}': class C05 { field int x }"
+        );
+    }
+}
+
+#[test]
+fn cache_test() {
+    let dir = std::env::temp_dir().join("jack_compiler_cache_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let main = "class Main { function void main() { do Point.new(); return; } }";
+    fs::write(dir.join("Main.jack"), main).unwrap();
+    fs::write(dir.join("Point.jack"), "class Point { constructor Point new() { return this; } }").unwrap();
+    let options = CompileOptions {
+        out_dir: Some(dir.join("out")),
+        cache: Some(dir.join("out/.jack-cache")),
+        ..CompileOptions::default()
+    };
+    let build = |options: &CompileOptions| {
+        let output = Compiler::with_options(options.clone())
+            .compile_files(&DiskProvider::new(&dir))
+            .unwrap();
+        output.write().unwrap();
+        let up_to_date: Vec<&str> = output
+            .classes
+            .iter()
+            .filter(|class| class.up_to_date)
+            .map(|class| class.name.as_str())
+            .collect();
+        (up_to_date.join(","), output.summary(), output)
+    };
+
+    let (up_to_date, summary, first) = build(&options);
+    assert_eq!(up_to_date, "");
+    assert_eq!(summary, "Rebuilt 2 of 2 classes (0 up to date)");
+    let (up_to_date, summary, second) = build(&options);
+    assert_eq!(up_to_date, "Main,Point");
+    assert_eq!(summary, "Rebuilt 0 of 2 classes (2 up to date)");
+    for (a, b) in first.classes.iter().zip(second.classes.iter()) {
+        assert_eq!(a.artifacts[0].path, b.artifacts[0].path);
+        assert_eq!(a.artifacts[0].content, b.artifacts[0].content);
+    }
+
+    // a change inside a subroutine only rebuilds the class itself
+    let point = "class Point { constructor Point new() { var int x; return this; } }";
+    fs::write(dir.join("Point.jack"), point).unwrap();
+    assert_eq!(build(&options).0, "Main");
+    // a changed declaration also rebuilds the classes that use it
+    let point = "class Point { constructor Point new(int x) { return this; } }";
+    fs::write(dir.join("Point.jack"), point).unwrap();
+    assert_eq!(build(&options).0, "");
+    assert_eq!(build(&options).0, "Main,Point");
+    // a missing output file is written again
+    fs::remove_file(dir.join("out/Main.vm")).unwrap();
+    assert_eq!(build(&options).0, "Point");
+    assert!(dir.join("out/Main.vm").is_file());
+    // other options rebuild everything
+    let annotated = CompileOptions {
+        annotate: true,
+        ..options.clone()
+    };
+    assert_eq!(build(&annotated).0, "");
+    assert_eq!(build(&annotated).0, "Main,Point");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn warnings_test() {
+    let mut files = MemoryProvider::new();
+    files.insert(
+        "Main.jack",
+        "class Main {\n  function void main() {\n    var int x, y;\n    var Array a, b;\n    let x = 1;\n    let a[0] = x;\n    do b.dispose();\n    return;\n  }\n}",
+    );
+    let output = Compiler::new().compile_files(&files).unwrap();
+    assert!(output.classes[0].diagnostics.is_empty());
+
+    let options = CompileOptions {
+        warnings: true,
+        ..CompileOptions::default()
+    };
+    let output = Compiler::with_options(options.clone()).compile_files(&files).unwrap();
+    let diagnostics: Vec<String> = output.classes[0].diagnostics.iter().map(|d| d.to_string()).collect();
+    assert_eq!(
+        diagnostics,
+        ["Warning in line 3, column 5: Local variable \"y\" of subroutine \"main\" is never used"]
+    );
+
+    // up-to-date classes report their warnings again
+    let dir = std::env::temp_dir().join("jack_compiler_warnings_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Main.jack"), "class Main { function void main() { var int x, y; return; } }").unwrap();
+    let options = CompileOptions {
+        cache: Some(dir.join(".jack-cache")),
+        ..options
+    };
+    let build = || {
+        let output = Compiler::with_options(options.clone())
+            .compile_files(&DiskProvider::new(&dir))
+            .unwrap();
+        output.write().unwrap();
+        output
+    };
+    let first = build();
+    let second = build();
+    assert!(second.classes[0].up_to_date);
+    assert_eq!(first.classes[0].diagnostics.len(), 2);
+    assert_eq!(second.classes[0].diagnostics, first.classes[0].diagnostics);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn optimization_test() {
+    let main = |expression: &str| {
+        format!("class Main {{ function int main() {{ var int x; let x = 2; return {}; }} }}", expression)
+    };
+    let compile = |optimization: OptLevel, expression: &str| {
+        let options = CompileOptions {
+            optimization,
+            ..CompileOptions::default()
+        };
+        let vm_code = Compiler::with_options(options).compile_str("Main.jack", &main(expression)).unwrap();
+        let body: Vec<String> = vm_code.lines().skip(3).map(String::from).collect();
+        body[..body.len() - 1].join("\n")
+    };
+    assert_eq!(
+        compile(OptLevel::O0, "60 * 60"),
+        "push constant 60\npush constant 60\ncall Math.multiply 2"
+    );
+    assert_eq!(compile(OptLevel::O1, "60 * 60"), "push constant 3600");
+    assert_eq!(compile(OptLevel::O1, "(1 + 2) * 3 - 10"), "push constant 1\nneg");
+    assert_eq!(compile(OptLevel::O1, "-32767 - 1"), "push constant 32767\nnot");
+    assert_eq!(compile(OptLevel::O1, "(1 < 2) & true"), "push constant 1\nneg");
+    assert_eq!(compile(OptLevel::O1, "x + (4 / 2)"), "push local 0\npush constant 2\nadd");
+    // left to run time, which reports the division by zero
+    assert_eq!(
+        compile(OptLevel::O1, "1 / 0"),
+        "push constant 1\npush constant 0\ncall Math.divide 2"
+    );
+}
+
+#[test]
+fn operator_precedence_test() {
+    let class = "class Main { function int main() { return 1 + 2 * 3 = 7 | false; } }";
+    let compile = |extensions: &[Extension], emit: Emit| {
+        let options = CompileOptions {
+            emit,
+            extensions: extensions.iter().copied().collect(),
+            ..CompileOptions::default()
+        };
+        Compiler::with_options(options).compile(&Source::new("Main.jack", class)).unwrap()
+    };
+    let vm_code = |extensions: &[Extension]| {
+        let class = compile(extensions, Emit::default());
+        let vm_code = &class.artifact(ArtifactKind::Vm).unwrap().content;
+        vm_code.lines().skip(1).map(String::from).collect::<Vec<_>>().join(" ")
+    };
+    assert_eq!(
+        vm_code(&[]),
+        "push constant 1 push constant 2 add push constant 3 call Math.multiply 2 \
+         push constant 7 eq push constant 0 or return"
+    );
+    assert_eq!(
+        vm_code(&[Extension::OperatorPrecedence]),
+        "push constant 1 push constant 2 push constant 3 call Math.multiply 2 add \
+         push constant 7 eq push constant 0 or return"
+    );
+
+    // the XML lists the terms and operators in the order of the source either way
+    let xml = |extensions: &[Extension]| {
+        let emit = Emit {
+            tokens: false,
+            xml: true,
+            vm: false,
+        };
+        compile(extensions, emit).artifact(ArtifactKind::Xml).unwrap().content.clone()
+    };
+    assert_eq!(xml(&[Extension::OperatorPrecedence]), xml(&[]));
+}
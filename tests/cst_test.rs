@@ -0,0 +1,96 @@
+mod common;
+
+use std::fs;
+
+use jack_compiler::parser::cst::{NodeKind, SyntaxElement};
+use jack_compiler::parser::Parser;
+use jack_compiler::tokenizer::tokens::TriviaKind;
+use jack_compiler::tokenizer::Tokenizer;
+
+#[test]
+fn round_trip_test() {
+    let files = common::jack_files("tests/aux_files");
+    assert!(!files.is_empty());
+    for file in files {
+        let source = fs::read_to_string(&file).unwrap();
+        let tokens = Tokenizer::new().tokenize_lossless(&source).unwrap();
+        let cst = Parser::new().parse_lossless(tokens).unwrap();
+        assert_eq!(cst.to_string(), source, "{}", file);
+    }
+}
+
+#[test]
+fn trivia_test() {
+    let source = "/** Doc. */\nclass Main { // trailing\n\n  /* block */ field int x;\r\n}\n";
+    let tokens = Tokenizer::new().tokenize_lossless(source).unwrap();
+    assert_eq!(tokens[0].text, "class");
+    assert_eq!(tokens[0].leading_trivia[0].kind, TriviaKind::DocComment);
+    assert_eq!(tokens[2].text, "{");
+    let trailing: Vec<&str> = tokens[2].trailing_trivia.iter().map(|t| t.text.as_str()).collect();
+    assert_eq!(trailing, vec![" ", "// trailing", "\n"]);
+    assert_eq!(tokens[3].text, "field");
+    assert_eq!(tokens[3].leading_trivia[1].kind, TriviaKind::BlockComment);
+    let eof = tokens.last().unwrap();
+    assert!(eof.token.is_none());
+    assert_eq!(eof.to_string(), "");
+
+    let cst = Parser::new().parse_lossless(tokens).unwrap();
+    assert_eq!(cst.kind, NodeKind::File);
+    match &cst.children[0] {
+        SyntaxElement::Node(class) => {
+            assert_eq!(class.kind, NodeKind::Class);
+            assert!(matches!(&class.children[3], SyntaxElement::Node(n) if n.kind == NodeKind::ClassVarDec));
+        }
+        _ => panic!("expected class node"),
+    }
+    assert_eq!(cst.to_string(), source);
+}
+
+#[test]
+fn parse_error_test() {
+    let parse = |source: &str| {
+        let tokens = Tokenizer::new().tokenize_lossless(source).unwrap();
+        Parser::new().parse_lossless(tokens).err().unwrap().to_string()
+    };
+    assert_eq!(
+        parse("class Main { field int x }"),
+        "Parse Error in line 1, column 26: Expected ';' but found '}': class Main { field int x }"
+    );
+    assert_eq!(
+        parse("class Main {\n  function void main() { return 1 +; }\n}"),
+        "Parse Error in line 2, column 36: Unexpected token found in term: function void main() { return 1 +; }"
+    );
+    assert_eq!(
+        parse("class Main { function void main() {"),
+        "Parse Error in line 1, column 35: Expected '}' but found end of file: class Main { function void main() {"
+    );
+    assert_eq!(
+        parse("class A {} class B {}"),
+        "Parse Error in line 1, column 12: Only one class is allowed per file: class A {} class B {}"
+    );
+    assert_eq!(parse(""), "Parse Error: Expected 'class' but found end of file");
+}
+
+#[test]
+fn same_grammar_test() {
+    // the lossless tree and the parse tree come from the same parser, so they accept
+    // the same files and report the same errors
+    let sources = [
+        "class Main { function void main() { do Output.printInt(1 + -2 * (3)); return; } }",
+        "class Main { function void main() { return; } let }",
+        "class Main { method void f() { var int x; let x[1 = 2; return; } }",
+        "class Main { static int x, ; }",
+        "class Main { function int main() { return 1 + 2 * 3; } } }",
+    ];
+    for source in sources {
+        let tokens = Tokenizer::new().tokenize(source).unwrap();
+        let parse_tree = Parser::new().parse(tokens, "Main.jack");
+        let tokens = Tokenizer::new().tokenize_lossless(source).unwrap();
+        let cst = Parser::new().parse_lossless(tokens);
+        match (parse_tree, cst) {
+            (Ok(_), Ok(cst)) => assert_eq!(cst.to_string(), source),
+            (Err(e1), Err(e2)) => assert_eq!(e1.to_string(), e2.to_string()),
+            (parse_tree, cst) => panic!("{}: {:?} but {:?}", source, parse_tree.is_ok(), cst.is_ok()),
+        }
+    }
+}
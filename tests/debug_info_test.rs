@@ -0,0 +1,61 @@
+use jack_compiler::io;
+use jack_compiler::tokenizer::Tokenizer;
+use jack_compiler::parser::Parser;
+use jack_compiler::vm_writer::VMWriter;
+use jack_compiler::vm_writer::debug_info::DebugInfo;
+
+fn compile(path_to_jack_file: &str, file: &str) -> (String, DebugInfo) {
+    let tokenizer = Tokenizer::new();
+    let parser = Parser::new();
+    let vm_writer = VMWriter::new();
+    let source = io::read_file(path_to_jack_file).unwrap();
+    let tokens = tokenizer.tokenize(&source).unwrap();
+    let parse_tree = parser.parse(tokens, path_to_jack_file).unwrap();
+    vm_writer.write_with_debug_info(&parse_tree, file).unwrap()
+}
+
+#[test]
+fn square_square_test() {
+    let (vm_code, debug_info) = compile("tests/aux_files/Square/Square.jack", "Square.jack");
+    assert_eq!(debug_info.file, "Square.jack");
+    assert_eq!(debug_info.instructions.len(), vm_code.lines().count());
+    assert_eq!(debug_info.instructions.len(), debug_info.source_map.entries.len());
+
+    let scope_names: Vec<&str> = debug_info.scopes.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(scope_names[0], "Square");
+    assert!(scope_names.contains(&"Square.moveUp"));
+
+    let class_scope: Vec<(&str, u32)> = debug_info.scopes[0]
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.index))
+        .collect();
+    assert_eq!(class_scope, vec![("x", 0), ("y", 1), ("size", 2)]);
+
+    for branch in debug_info.branches.iter() {
+        assert!(debug_info.instructions[branch.instruction].starts_with("if-goto IF_TRUE"));
+        assert!(branch.jumps_if);
+    }
+    let branch_lines: Vec<usize> = debug_info.branches.iter().map(|b| b.position.line).collect();
+    assert_eq!(branch_lines, vec![45, 55, 65, 77, 89, 101]);
+
+    let json = debug_info.to_json();
+    assert!(json.contains(
+        "{\"command\": \"function Square.new 0\", \"line\": 15, \"column\": 4, \"subroutine\": \"Square.new\"}"
+    ));
+    assert!(json.contains(
+        "{\"name\": \"size\", \"kind\": \"field\", \"type\": \"int\", \"segment\": \"this\", \"index\": 2}"
+    ));
+}
+
+#[test]
+fn square_square_game_test() {
+    let (_, debug_info) = compile("tests/aux_files/Square/SquareGame.jack", "SquareGame.jack");
+    let while_branches: Vec<(usize, bool)> = debug_info
+        .branches
+        .iter()
+        .filter(|b| debug_info.instructions[b.instruction].starts_with("if-goto WHILE_END"))
+        .map(|b| (b.position.line, b.jumps_if))
+        .collect();
+    assert_eq!(while_branches, vec![(56, false), (58, false), (71, false)]);
+}
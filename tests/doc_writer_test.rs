@@ -0,0 +1,126 @@
+use std::fs;
+use std::process::Command;
+
+use jack_compiler::doc_writer::{DocFormat, DocWriter};
+use jack_compiler::io;
+use jack_compiler::parser::parse_tree::ParseTree;
+use jack_compiler::parser::Parser;
+use jack_compiler::tokenizer::Tokenizer;
+
+fn parse(path_to_jack_file: &str) -> ParseTree {
+    let source = io::read_file(path_to_jack_file).unwrap();
+    let tokens = Tokenizer::new().tokenize(&source).unwrap();
+    Parser::new().parse(tokens, path_to_jack_file).unwrap()
+}
+
+fn square() -> Vec<ParseTree> {
+    vec![
+        parse("tests/aux_files/Square/Main.jack"),
+        parse("tests/aux_files/Square/Square.jack"),
+        parse("tests/aux_files/Square/SquareGame.jack"),
+    ]
+}
+
+#[test]
+fn doc_comment_test() {
+    let square = parse("tests/aux_files/Square/Square.jack").class_node;
+    assert_eq!(square.doc.as_deref(), Some("Implements a graphical square."));
+    assert_eq!(
+        square.subroutines[0].doc.as_deref(),
+        Some("Constructs a new square with a given location and size.")
+    );
+    assert!(square.variables.iter().all(|class_var| class_var.doc.is_none()));
+
+    let square_game = parse("tests/aux_files/Square/SquareGame.jack").class_node;
+    let doc = square_game.doc.unwrap();
+    assert!(doc.starts_with("Implements the Square Dance game.\nThis simple game"));
+    assert!(doc.ends_with("The 'q' key is used to quit the game."));
+
+    // a comment that is not a doc comment is not attached
+    let source = "class Main { /** the counter */ static int count; /* not a doc */ function void main() { return; } }";
+    let tokens = Tokenizer::new().tokenize(source).unwrap();
+    let class = Parser::new().parse(tokens, "Main.jack").unwrap().class_node;
+    assert_eq!(class.doc, None);
+    assert_eq!(class.variables[0].doc.as_deref(), Some("the counter"));
+    assert_eq!(class.subroutines[0].doc, None);
+
+    // a comment closed with more than one star
+    let source = "/** The main class. **/ class Main { /**\n * Runs.\n **/ function void main() { return; } }";
+    let tokens = Tokenizer::new().tokenize(source).unwrap();
+    let class = Parser::new().parse(tokens, "Main.jack").unwrap().class_node;
+    assert_eq!(class.doc.as_deref(), Some("The main class."));
+    assert_eq!(class.subroutines[0].doc.as_deref(), Some("Runs."));
+}
+
+#[test]
+fn html_test() {
+    let parse_trees = square();
+    let classes: Vec<_> = parse_trees.iter().map(|parse_tree| &parse_tree.class_node).collect();
+    let pages = DocWriter::new().write(&classes);
+    let file_names: Vec<&str> = pages.iter().map(|page| page.file_name.as_str()).collect();
+    assert_eq!(file_names, ["index.html", "Main.html", "Square.html", "SquareGame.html"]);
+
+    let index = &pages[0].content;
+    assert!(index.contains("<li><a href=\"Square.html\">Square</a> &ndash; Implements a graphical square.</li>"));
+
+    let square_game = &pages[3].content;
+    assert!(square_game.contains("<h3><code>field <a href=\"Square.html\">Square</a> square</code></h3>"));
+    assert!(square_game.contains("<h3><code>constructor <a href=\"SquareGame.html\">SquareGame</a> new()</code></h3>\n<p>Constructs a new Square Game.</p>"));
+    assert!(square_game.contains("<p>Implements the Square Dance game. This simple game"));
+    assert!(square_game.contains("The 'q' key is used to quit the game.</p>"));
+
+    let square = &pages[2].content;
+    assert!(square.contains("<h3><code>field int x, y</code></h3>"));
+    assert!(square.contains("<h3><code>constructor <a href=\"Square.html\">Square</a> new(int Ax, int Ay, int Asize)</code></h3>"));
+    assert!(square.contains("<h3><code>method void dispose()</code></h3>\n<p>Disposes this square.</p>"));
+}
+
+#[test]
+fn markdown_test() {
+    let parse_trees = square();
+    let classes: Vec<_> = parse_trees.iter().map(|parse_tree| &parse_tree.class_node).collect();
+    let pages = DocWriter::new().format(DocFormat::Markdown).write(&classes);
+    assert_eq!(pages[0].file_name, "index.md");
+    assert!(pages[0].content.contains("- [Main](Main.md) - Initializes a new Square Dance game and starts running it."));
+
+    let square_game = &pages[3].content;
+    assert!(square_game.starts_with("[Classes](index.md)\n\n# class SquareGame\n\n"));
+    assert!(square_game.contains("### field [Square](Square.md) square\n\n"));
+    assert!(square_game.contains("### method void moveSquare()\n\nMoves the square in the current direction.\n\n"));
+
+    // classes that are not documented are not linked
+    let source = "class Main { function void main(Point p, Array a) { return; } }";
+    let tokens = Tokenizer::new().tokenize(source).unwrap();
+    let parse_tree = Parser::new().parse(tokens, "Main.jack").unwrap();
+    let pages = DocWriter::new().format(DocFormat::Markdown).write(&[&parse_tree.class_node]);
+    assert!(pages[1].content.contains("### function void main(Point p, Array a)\n\n"));
+}
+
+#[test]
+fn doc_command_test() {
+    let dir = std::env::temp_dir().join("jack_compiler_doc_test");
+    let _ = fs::remove_dir_all(&dir);
+    let doc = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+            .arg("doc")
+            .args(args)
+            .arg("-o")
+            .arg(&dir)
+            .arg("tests/aux_files/Square")
+            .output()
+            .unwrap()
+    };
+
+    assert!(doc(&[]).status.success());
+    assert!(fs::read_to_string(dir.join("index.html")).unwrap().contains("SquareGame.html"));
+    assert!(dir.join("Square.html").is_file());
+    assert!(doc(&["--format", "markdown"]).status.success());
+    assert!(fs::read_to_string(dir.join("Square.md")).unwrap().starts_with("[Classes](index.md)"));
+    assert!(!doc(&["--format", "pdf"]).status.success());
+    // options of the build are rejected
+    let output = doc(&["--emit", "vm"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("option '--emit' cannot be used with 'doc'"));
+    assert!(!doc(&["-j4"]).status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
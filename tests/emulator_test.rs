@@ -0,0 +1,107 @@
+use jack_compiler::compiler::output::ArtifactKind;
+use jack_compiler::compiler::Compiler;
+use jack_compiler::emulator::error::EmulatorError;
+use jack_compiler::emulator::{Emulator, Program, Stop};
+use jack_compiler::io::source::MemoryProvider;
+
+/// A `Sys` class without the rest of the operating system.
+const SYS: &str = "class Sys {
+    function void init() { do Main.main(); do Sys.halt(); return; }
+    function void halt() { while (true) {} return; }
+}";
+
+/// Compiles the classes and loads their VM code, `Main` first.
+fn load(classes: &[(&str, &str)]) -> Program {
+    let mut files = MemoryProvider::new();
+    for (name, text) in classes {
+        files.insert(&format!("{}.jack", name), text);
+    }
+    let output = Compiler::new().compile_files(&files).unwrap();
+    let mut program = Program::new();
+    for (name, _) in classes {
+        let vm_code = &output.class(name).unwrap().artifact(ArtifactKind::Vm).unwrap().content;
+        program.add_file(&format!("{}.vm", name), vm_code).unwrap();
+    }
+    program
+}
+
+#[test]
+fn run_test() {
+    let main = "class Main {
+        static int result, calls;
+        function void main() {
+            var int i;
+            let i = 10;
+            while (i > 0) {
+                let result = result + Main.fib(i);
+                let i = i - 1;
+            }
+            return;
+        }
+        function int fib(int n) {
+            let calls = calls + 1;
+            if (n < 2) { return n; }
+            return Main.fib(n - 1) + Main.fib(n - 2);
+        }
+    }";
+    let program = load(&[("Main", main), ("Sys", SYS)]);
+    let mut emulator = Emulator::new(&program);
+    assert_eq!(emulator.run(&mut (), 1_000_000).unwrap(), Stop::Halted);
+    // the static variables of Main come first
+    assert_eq!(emulator.ram()[16], 143);
+    assert_eq!(emulator.ram()[17], 452);
+    // Sys.init is still running
+    assert_eq!(emulator.ram()[0], 261);
+}
+
+#[test]
+fn stop_test() {
+    let program = load(&[("Main", "class Main { function void main() { while (true) {} return; } }"), ("Sys", SYS)]);
+    let mut emulator = Emulator::new(&program);
+    assert_eq!(emulator.run(&mut (), 1000).unwrap(), Stop::Limit);
+    assert_eq!(emulator.executed(), 1000);
+    assert_eq!(emulator.run(&mut (), 1500).unwrap(), Stop::Limit);
+    assert_eq!(emulator.executed(), 1500);
+
+    let sys = "class Sys { function void init() { return; } }";
+    let program = load(&[("Sys", sys)]);
+    let mut emulator = Emulator::new(&program);
+    assert_eq!(emulator.run(&mut (), 1000).unwrap(), Stop::Returned);
+    assert_eq!(emulator.executed(), 3);
+}
+
+#[test]
+fn emulator_error_test() {
+    let program = load(&[("Main", "class Main { function void main() { do Math.sqrt(4); return; } }"), ("Sys", SYS)]);
+    let error = Emulator::new(&program).run(&mut (), 1000).unwrap_err();
+    assert_eq!(error.to_string(), "Runtime Error in Main.main: Unknown function 'Math.sqrt'");
+
+    let program = load(&[("Main", "class Main { function void main() { return; } }")]);
+    let error = Emulator::new(&program).run(&mut (), 1000).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Runtime Error in the bootstrap code: The program has no function 'Sys.init', it needs an operating system"
+    );
+
+    let load_error = |vm_code: &str| Program::new().add_file("Main.vm", vm_code).unwrap_err();
+    assert_eq!(
+        load_error("function Main.main 0\npsh constant 1\n"),
+        EmulatorError::Syntax {
+            file: "Main.vm".to_string(),
+            line: 2,
+            message: "Unknown command 'psh constant 1'".to_string(),
+        }
+    );
+    assert_eq!(
+        load_error("function Main.main 0\ngoto END\nreturn\n").to_string(),
+        "Main.vm: Error in line 2: Unknown label 'END' in function 'Main.main'"
+    );
+    assert_eq!(
+        load_error("function Main.main 0\npop temp 8\n").to_string(),
+        "Main.vm: Error in line 2: Index 8 is out of range for segment 'temp'"
+    );
+    assert_eq!(
+        load_error("push constant 1\n").to_string(),
+        "Main.vm: Error in line 1: VM commands must be inside a function"
+    );
+}
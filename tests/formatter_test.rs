@@ -0,0 +1,111 @@
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+use jack_compiler::compiler::Compiler;
+use jack_compiler::formatter::error::FormatError;
+use jack_compiler::formatter::Formatter;
+
+#[test]
+fn same_vm_code_test() {
+    let files = common::jack_files("tests/aux_files");
+    assert!(!files.is_empty());
+    let compiler = Compiler::new();
+    for file in files {
+        let source = fs::read_to_string(&file).unwrap();
+        let formatted = Formatter::new().format(&source).unwrap();
+        assert_eq!(
+            compiler.compile_str(&file, &formatted).unwrap(),
+            compiler.compile_str(&file, &source).unwrap(),
+            "{}",
+            file
+        );
+        // formatting twice changes nothing
+        assert_eq!(Formatter::new().format(&formatted).unwrap(), formatted, "{}", file);
+    }
+}
+
+#[test]
+fn format_test() {
+    let source = "class  Main{\n\
+                  // head\n\
+                  field int a,b;static boolean c;\n\
+                  /** Doc.\n    * More.\n    */\n\
+                  function void main(){var Array arr;var int i; // idx\n\n\n\
+                  let arr[i-1]=-~(i*-2)+Foo.bar(1,\"s\",true)-x;\n\
+                  if(~(i=0)){do Output.printInt(-1);}else{let i=i+1; /* tail */}\n\
+                  while (i<3) { }\n\
+                  return -i;\n\
+                  // end of body\n\
+                  }\n\
+                  } // end\n";
+    let expected = "class Main {\n\
+                    \x20 // head\n\
+                    \x20 field int a, b;\n\
+                    \x20 static boolean c;\n\
+                    \n\
+                    \x20 /** Doc.\n\
+                    \x20  * More.\n\
+                    \x20  */\n\
+                    \x20 function void main() {\n\
+                    \x20   var Array arr;\n\
+                    \x20   var int i; // idx\n\
+                    \n\
+                    \x20   let arr[i - 1] = -~(i * -2) + Foo.bar(1, \"s\", true) - x;\n\
+                    \x20   if (~(i = 0)) {\n\
+                    \x20     do Output.printInt(-1);\n\
+                    \x20   } else {\n\
+                    \x20     let i = i + 1; /* tail */\n\
+                    \x20   }\n\
+                    \x20   while (i < 3) {\n\
+                    \x20   }\n\
+                    \x20   return -i;\n\
+                    \x20   // end of body\n\
+                    \x20 }\n\
+                    } // end\n";
+    assert_eq!(Formatter::new().indent_width(2).format(source).unwrap(), expected);
+}
+
+#[test]
+fn format_error_test() {
+    let error = Formatter::new().format("class Main { field int x }").unwrap_err();
+    assert!(matches!(error, FormatError::Parse(_)));
+    assert_eq!(
+        error.to_string(),
+        "Parse Error in line 1, column 26: Expected ';' but found '}': class Main { field int x }"
+    );
+    let error = Formatter::new().format("class Main { /* }").unwrap_err();
+    assert!(matches!(error, FormatError::Lex(_)));
+}
+
+#[test]
+fn fmt_check_test() {
+    let dir = std::env::temp_dir().join("jack_compiler_fmt_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("Main.jack");
+    fs::write(&file, "class Main{function void main(){return;}}").unwrap();
+    let fmt = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+            .arg("fmt")
+            .args(args)
+            .arg(&dir)
+            .output()
+            .unwrap()
+    };
+
+    let output = fmt(&["--check"]);
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), file.to_str().unwrap());
+    assert!(fmt(&[]).status.success());
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "class Main {\n    function void main() {\n        return;\n    }\n}\n"
+    );
+    assert!(fmt(&["--check"]).status.success());
+    // options of the build are rejected
+    assert!(!fmt(&["--cache", "jack-cache"]).status.success());
+    assert!(!fmt(&["--stdout"]).status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use jack_compiler::io::glob::Glob;
+use jack_compiler::compiler::Compiler;
+use jack_compiler::io::source::{DiskProvider, FileProvider, TarProvider};
+use jack_compiler::io::watch::Watcher;
+
+#[test]
+fn glob_test() {
+    let glob = Glob::new("*Game.jack");
+    assert!(glob.matches("SquareGame.jack"));
+    assert!(glob.matches("Square/SquareGame.jack"));
+    assert!(!glob.matches("Square/Square.jack"));
+
+    let glob = Glob::new("Square/*.jack");
+    assert!(glob.matches("Square/Main.jack"));
+    assert!(!glob.matches("Pong/Main.jack"));
+    assert!(!glob.matches("Square/lib/Main.jack"));
+
+    let glob = Glob::new("src/**/*.jack");
+    assert!(glob.matches("src/Main.jack"));
+    assert!(glob.matches("src/a/b/Main.jack"));
+    assert!(!glob.matches("test/Main.jack"));
+
+    let glob = Glob::new("Ma?n.jack");
+    assert!(glob.matches("Main.jack"));
+    assert!(!glob.matches("Maiin.jack"));
+}
+
+#[test]
+fn recursive_disk_provider_test() {
+    let files = DiskProvider::new("tests/aux_files")
+        .recursive(true)
+        .include("Square*/*.jack")
+        .exclude("Main.jack");
+    let names = files.jack_files().unwrap();
+    assert_eq!(
+        names,
+        vec![
+            "tests/aux_files/Square/Square.jack",
+            "tests/aux_files/Square/SquareGame.jack",
+            "tests/aux_files/SquareDance/Square.jack",
+            "tests/aux_files/SquareDance/SquareGame.jack",
+        ]
+    );
+    assert_eq!(
+        files.relative_path(&names[0]),
+        PathBuf::from("Square/Square.jack")
+    );
+
+    // without a recursive walk, only the top level of a directory is listed
+    assert!(DiskProvider::new("tests/aux_files").jack_files().unwrap().is_empty());
+}
+
+#[test]
+fn multiple_paths_test() {
+    let files = DiskProvider::with_paths(&[
+        "tests/aux_files/Square/SquareGame.jack",
+        "tests/aux_files/Seven",
+        "tests/aux_files/Square",
+    ]);
+    let names = files.jack_files().unwrap();
+    assert_eq!(
+        names,
+        vec![
+            "tests/aux_files/Seven/Main.jack",
+            "tests/aux_files/Square/Main.jack",
+            "tests/aux_files/Square/Square.jack",
+            "tests/aux_files/Square/SquareGame.jack",
+        ]
+    );
+    assert_eq!(files.relative_path(&names[0]), PathBuf::from("Main.jack"));
+}
+
+/// Returns a tar entry with the given type, e.g. `b'0'` for a file, as written by `tar`.
+fn tar_entry(name: &str, entry_type: u8, data: &[u8]) -> Vec<u8> {
+    let mut header = vec![0u8; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..107].copy_from_slice(b"0000644");
+    header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+    header[156] = entry_type;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|&byte| byte as u32).sum();
+    header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+    let mut entry = header;
+    entry.extend_from_slice(data);
+    entry.resize(entry.len().div_ceil(512) * 512, 0);
+    entry
+}
+
+#[test]
+fn tar_provider_test() {
+    let main = "class Main { function void main() { do Point.new(); return; } }";
+    let point = "class Point { constructor Point new() { return this; } }";
+    let long_name = format!("{}/Point.jack", "src".repeat(40));
+    let mut archive = Vec::new();
+    archive.extend(tar_entry("./src/", b'5', b""));
+    archive.extend(tar_entry("./src/Main.jack", b'0', main.as_bytes()));
+    archive.extend(tar_entry("./src/README", b'0', b"Not Jack"));
+    // GNU tar stores names longer than 100 bytes in an entry of its own
+    archive.extend(tar_entry("././@LongLink", b'L', long_name.as_bytes()));
+    archive.extend(tar_entry("src/srcsrc", b'0', point.as_bytes()));
+    archive.extend(vec![0u8; 1024]);
+
+    let files = TarProvider::from_bytes(&archive).unwrap();
+    assert_eq!(files.jack_files().unwrap(), vec!["src/Main.jack".to_string(), long_name.clone()]);
+    assert_eq!(files.read("src/Main.jack").unwrap(), main);
+    assert_eq!(files.relative_path(&long_name), PathBuf::from(&long_name));
+    let output = Compiler::new().compile_files(&files).unwrap();
+    assert!(output.class("Point").is_some());
+
+    archive[0] = b'x';
+    let error = TarProvider::from_bytes(&archive).err().unwrap();
+    assert_eq!(error.to_string(), "not a valid tar archive: invalid header checksum at byte 0");
+}
+
+#[test]
+fn watcher_test() {
+    let dir = std::env::temp_dir().join("jack_compiler_watcher_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("Main.jack").to_string_lossy().into_owned();
+    let point = dir.join("Point.jack").to_string_lossy().into_owned();
+    std::fs::write(&main, "class Main { }").unwrap();
+    std::fs::write(&point, "class Point { }").unwrap();
+
+    let mut watcher = Watcher::new();
+    let files = vec![main.clone(), point.clone()];
+    assert_eq!(watcher.changes(&files), files);
+    assert!(watcher.changes(&files).is_empty());
+
+    // set the time explicitly, the file system may not tell apart two writes in a row
+    let file = std::fs::File::options().write(true).open(&point).unwrap();
+    file.set_modified(std::time::SystemTime::UNIX_EPOCH).unwrap();
+    assert_eq!(watcher.changes(&files), vec![point.clone()]);
+    assert!(watcher.changes(&files).is_empty());
+
+    assert_eq!(watcher.changes(&files[..1]), vec![point.clone()]);
+    assert_eq!(watcher.changes(&files), vec![point]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
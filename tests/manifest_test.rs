@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use jack_compiler::compiler::error::CompileError;
+use jack_compiler::compiler::options::{Emit, Extension, OptLevel};
+use jack_compiler::compiler::output::ArtifactKind;
+use jack_compiler::compiler::Compiler;
+use jack_compiler::io::source::MemoryProvider;
+use jack_compiler::manifest::{Manifest, Os};
+
+#[test]
+fn manifest_test() {
+    let text = r#"
+# Pong, with the classes shared with Square
+[program]
+entry = "Main"
+sources = [
+    "src",   # the game
+    "extra",
+]
+libraries = ["../lib"]
+os = "os"
+
+[options]
+emit = ["vm", "xml"]
+out_dir = "build"
+recursive = true
+exclude = ["*Test.jack"]
+annotate = true
+opt_level = 1
+extensions = []
+"#;
+    let manifest = Manifest::parse(text, Path::new("pong")).unwrap();
+    assert_eq!(manifest.entry, "Main");
+    assert_eq!(
+        manifest.sources,
+        vec![PathBuf::from("pong/src"), PathBuf::from("pong/extra")]
+    );
+    assert_eq!(manifest.libraries, vec![PathBuf::from("pong/../lib")]);
+    assert_eq!(manifest.os, Os::Dir(PathBuf::from("pong/os")));
+    assert_eq!(
+        manifest.options.emit,
+        Emit {
+            tokens: false,
+            xml: true,
+            vm: true
+        }
+    );
+    assert_eq!(manifest.options.out_dir, Some(PathBuf::from("pong/build")));
+    assert!(manifest.options.annotate);
+    assert!(!manifest.options.debug_info);
+    assert_eq!(manifest.options.optimization, OptLevel::O1);
+    assert!(manifest.options.extensions.is_empty());
+    assert!(manifest.recursive);
+    assert_eq!(manifest.exclude, vec!["*Test.jack"]);
+
+    let manifest = Manifest::parse("[program]\nentry = \"Main\"\n", Path::new(".")).unwrap();
+    assert_eq!(manifest.sources, vec![PathBuf::from(".")]);
+    assert_eq!(manifest.os, Os::Native);
+    assert_eq!(manifest.options.emit, Emit::default());
+
+    let manifest = Manifest::parse("[program]\nentry = \"Main\"\nos = \"bundled\"\n", Path::new(".")).unwrap();
+    assert_eq!(manifest.os, Os::Bundled);
+
+    let manifest = Manifest::parse(
+        "[program]\nentry = \"Main\"\n[options]\nextensions = [\"precedence\"]\n",
+        Path::new("."),
+    )
+    .unwrap();
+    assert_eq!(manifest.options.optimization, OptLevel::O0);
+    assert!(manifest.options.extensions.contains(&Extension::OperatorPrecedence));
+}
+
+#[test]
+fn toml_subset_test() {
+    let text = r#"
+[ program ]
+entry = 'Main'          # a literal string
+sources = ['src\game', "tab\there\u00e9\U0001F600",]
+
+[options]
+exclude = [
+    [], # not a string
+]
+jobs = +1_0
+"#;
+    let error = Manifest::parse(text, Path::new("")).unwrap_err().to_string();
+    assert_eq!(error, "jack.toml: Error in line 7: Expected an array of strings");
+
+    let manifest = Manifest::parse(&text.replace("[], # not a string", "'*Test.jack', # \"'"), Path::new("")).unwrap();
+    assert_eq!(manifest.entry, "Main");
+    assert_eq!(
+        manifest.sources,
+        vec![PathBuf::from("src\\game"), PathBuf::from("tab\there\u{e9}\u{1F600}")]
+    );
+    assert_eq!(manifest.exclude, vec!["*Test.jack"]);
+    assert_eq!(manifest.options.jobs, 10);
+
+    let error = |value: &str| {
+        Manifest::parse(&format!("[program]\nentry = {}\n", value), Path::new("."))
+            .unwrap_err()
+            .message
+    };
+    assert_eq!(error("{ name = \"Main\" }"), "Inline tables are not supported");
+    assert_eq!(error("\"\"\"Main\"\"\""), "Multi-line strings are not supported");
+    assert_eq!(error("\"\\x\""), "Invalid escape sequence in string");
+    assert_eq!(error("\"\\u00\""), "Invalid unicode escape sequence in string");
+    assert_eq!(error("0x10"), "Invalid value '0x10', only decimal integers are supported");
+    assert_eq!(error("1.5"), "Invalid value '1.5'");
+    assert_eq!(error("1__0"), "Invalid value '1__0'");
+    assert_eq!(error("010"), "Invalid value '010'");
+    let error = |text: &str| Manifest::parse(text, Path::new(".")).unwrap_err().message;
+    assert_eq!(error("[program]\nprogram.entry = \"Main\"\n"), "Dotted keys are not supported");
+    assert_eq!(error("[program]\n\"entry\" = \"Main\"\n"), "Quoted keys are not supported");
+    assert_eq!(error("[[program]]\nentry = \"Main\"\n"), "Arrays of tables are not supported");
+}
+
+#[test]
+fn manifest_error_test() {
+    let error = |text: &str| Manifest::parse(text, Path::new(".")).unwrap_err().to_string();
+    assert_eq!(
+        error("[program]\nentry = \"Main\"\nsource = [\"src\"]\n"),
+        "jack.toml: Error in line 3: Unknown key 'source' in table 'program'"
+    );
+    assert_eq!(
+        error("[program]\nentry = \"Main\"\n[options]\nannotate = \"yes\"\n"),
+        "jack.toml: Error in line 4: Expected true or false"
+    );
+    assert_eq!(
+        error("[program]\nsources = [\"src\"]\n"),
+        "jack.toml: Error: Missing key 'entry' in table 'program'"
+    );
+    assert_eq!(
+        error("entry = \"Main\"\n"),
+        "jack.toml: Error in line 1: Key 'entry' must be in a table, e.g. '[program]'"
+    );
+    assert_eq!(
+        error("[program]\nentry = \"Main\nos = \"native\"\n"),
+        "jack.toml: Error in line 2: Unterminated string"
+    );
+    assert_eq!(
+        error("[program]\nentry = \"Main\"\n[options]\nopt_level = 2\n"),
+        "jack.toml: Error in line 4: Expected the optimization level 0 or 1"
+    );
+    assert_eq!(
+        error("[program]\nentry = \"Main\"\n[options]\nextensions = [\"macros\"]\n"),
+        "jack.toml: Error in line 4: Unknown extension 'macros' (expected precedence)"
+    );
+}
+
+#[test]
+fn compile_program_test() {
+    let mut files = MemoryProvider::new();
+    files.insert(
+        "Main.jack",
+        "class Main { function void main() { do Point.new(); return; } }",
+    );
+    let mut libraries = MemoryProvider::new();
+    libraries.insert("Point.jack", "class Point { constructor Point new() { return this; } }");
+
+    let compiler = Compiler::new();
+    let output = compiler.compile_program(&files, &libraries, "Main").unwrap();
+    assert_eq!(output.classes.len(), 1);
+    assert!(output.class("Point").is_none());
+
+    let error = compiler.compile_program(&files, &libraries, "Pong").unwrap_err();
+    assert!(matches!(error, CompileError::MissingEntry { .. }));
+    let error = compiler.compile_program(&files, &libraries, "Point").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Point.jack: Error in line 1, column 7: Class \"Point\" has no function \"main\""
+    );
+
+    libraries.insert("Main.jack", "class Main { }");
+    let error = compiler.compile_program(&files, &libraries, "Main").unwrap_err();
+    assert!(matches!(error, CompileError::DuplicateClass { .. }));
+}
+
+#[test]
+fn link_os_test() {
+    let os_dir = std::env::temp_dir().join("jack_manifest_test_os");
+    fs::create_dir_all(&os_dir).unwrap();
+    fs::write(os_dir.join("Math.vm"), "function Math.init 0\n").unwrap();
+    fs::write(os_dir.join("Main.vm"), "function Main.main 0\n").unwrap();
+    fs::write(os_dir.join("Memory.vm"), "function Memory.init 0\n").unwrap();
+
+    let mut files = MemoryProvider::new();
+    files.insert("Main.jack", "class Main { function void main() { return; } }");
+    let mut libraries = MemoryProvider::new();
+    libraries.insert("Memory.jack", "class Memory { function void init() { return; } }");
+    let mut output = Compiler::new().compile_program(&files, &libraries, "Main").unwrap();
+    assert_eq!(output.libraries, vec!["Memory"]);
+    let result = Os::Dir(os_dir.clone()).link(&mut output, Path::new("build"));
+    fs::remove_dir_all(&os_dir).unwrap();
+    result.unwrap();
+
+    // Main.vm and Memory.vm of the OS are replaced by the program's own Main class
+    // and the Memory class of the library
+    assert_eq!(output.classes.len(), 2);
+    assert!(output.class("Memory").is_none());
+    let math = output.class("Math").unwrap().artifact(ArtifactKind::Vm).unwrap();
+    assert_eq!(math.path, PathBuf::from("build/Math.vm"));
+    assert_eq!(math.content, "function Math.init 0\n");
+
+    assert!(Os::Native.link(&mut output, Path::new("build")).is_ok());
+    assert_eq!(output.classes.len(), 2);
+
+    // Math was linked already, and Memory is a library
+    Os::Bundled.link(&mut output, Path::new("build")).unwrap();
+    let names: Vec<&str> = output.classes.iter().map(|class| class.name.as_str()).collect();
+    assert_eq!(names, vec!["Main", "Math", "Array", "Keyboard", "Output", "Screen", "String", "Sys"]);
+    let sys = output.class("Sys").unwrap().artifact(ArtifactKind::Vm).unwrap();
+    assert_eq!(sys.path, PathBuf::from("build/Sys.vm"));
+    assert!(sys.content.starts_with("function Sys.init 0\n"));
+}
@@ -0,0 +1,97 @@
+use jack_compiler::compiler::Compiler;
+use jack_compiler::emulator::{Emulator, Program, Stop};
+use jack_compiler::os;
+
+/// Runs the class `Main` on the bundled operating system and returns the RAM.
+fn run(main: &str) -> Vec<i16> {
+    let mut program = Program::new();
+    let vm_code = Compiler::new().compile_str("Main.jack", main).unwrap();
+    program.add_file("Main.vm", &vm_code).unwrap();
+    for (name, vm_code) in os::compile().unwrap() {
+        program.add_file(&format!("{}.vm", name), &vm_code).unwrap();
+    }
+    let mut emulator = Emulator::new(&program);
+    assert_eq!(emulator.run(&mut (), 10_000_000).unwrap(), Stop::Halted);
+    emulator.ram().to_vec()
+}
+
+#[test]
+fn math_test() {
+    let main = "class Main {
+        static int a, b, c, d, e, f, g;
+        function void main() {
+            let a = -123 * 45;
+            let b = 300 * 300;
+            let c = -100 / 7;
+            let d = 32767 / -1;
+            let e = Math.sqrt(1000);
+            let f = Math.sqrt(32767);
+            let g = Math.max(Math.abs(-5), Math.min(3, 4));
+            return;
+        }
+    }";
+    let ram = run(main);
+    // the static variables of Main come first
+    assert_eq!(&ram[16..23], &[-5535, 90000_i32 as i16, -14, -32767, 31, 181, 5]);
+}
+
+#[test]
+fn string_memory_test() {
+    let main = "class Main {
+        static int value, length, first, last, same, heap;
+        function void main() {
+            var String s;
+            var Array x, y;
+            let s = \"-1234x\";
+            let value = s.intValue();
+            do s.setInt(-32767 - 1);
+            let length = s.length();
+            let first = s.charAt(0);
+            let last = s.charAt(5);
+            // a freed block is used again
+            let x = Array.new(10);
+            do x.dispose();
+            let y = Array.new(10);
+            let same = x = y;
+            let heap = (x > 2047) & (x < 16384);
+            return;
+        }
+    }";
+    let ram = run(main);
+    assert_eq!(&ram[16..22], &[-1234, 6, b'-' as i16, b'8' as i16, -1, -1]);
+}
+
+#[test]
+fn output_test() {
+    let main = "class Main {
+        function void main() {
+            do Output.moveCursor(1, 3);
+            do Output.printString(\"AB\");
+            do Screen.drawPixel(17, 100);
+            return;
+        }
+    }";
+    let ram = run(main);
+    let word = |address: usize| ram[address] as u16;
+    // the pixels of 'A' and 'B' share the word of columns 2 and 3 on every line of row 1,
+    // which starts 11 lines down; the first line of a character is empty
+    let row = 16384 + 11 * 32;
+    assert_eq!(word(row + 1), 0);
+    assert_eq!(word(row + 32 + 1), 0x0C00);
+    assert_eq!(word(row + 2 * 32 + 1), 0x1E00);
+    assert_eq!(word(row + 32 + 2), 0x3F);
+    assert_eq!(word(16384 + 100 * 32 + 1), 2);
+}
+
+#[test]
+fn error_test() {
+    let main = "class Main {
+        function void main() {
+            do Output.printInt(1 / 0);
+            return;
+        }
+    }";
+    let ram = run(main);
+    // ERR3 is printed in the top left corner, the first line of 'E' and 'R' is 0x7F and 0x3F
+    assert_eq!(ram[16384 + 32] as u16, 0x3F7F);
+}
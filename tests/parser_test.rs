@@ -2,7 +2,11 @@ use std::fs;
 
 use jack_compiler::io;
 use jack_compiler::tokenizer::Tokenizer;
+use jack_compiler::parser::parse_tree::subroutine::SubroutineType;
+use jack_compiler::parser::parse_tree::var::VarKind;
 use jack_compiler::parser::Parser;
+use jack_compiler::tokenizer::tokens::Keyword;
+use jack_compiler::xml_writer::XmlWriter;
 
 fn remove_whitespace(s: String) -> String {
     let mut chars = Vec::new();
@@ -26,10 +30,10 @@ fn remove_whitespace(s: String) -> String {
 fn compare(path_to_jack_file: &str, path_to_target_xml: &str) -> (String, String) {
     let tokenizer = Tokenizer::new();
     let parser = Parser::new();
-    let lines = io::read_file(path_to_jack_file);
-    let tokens = tokenizer.tokenize(lines);
-    let parse_tree = parser.parse(tokens, path_to_jack_file);
-    let xml = format!("{}", parse_tree.class_node);
+    let source = io::read_file(path_to_jack_file).unwrap();
+    let tokens = tokenizer.tokenize(&source).unwrap();
+    let parse_tree = parser.parse(tokens, path_to_jack_file).unwrap();
+    let xml = XmlWriter::new().write(&parse_tree);
     let target_xml = fs::read_to_string(path_to_target_xml)
         .expect("Reading target file failed.");
     let target_xml = remove_whitespace(target_xml);
@@ -98,3 +102,135 @@ fn array_test_test() {
     );
     assert_eq!(xml, target_xml);
 }
+
+fn parse_str(source: &str) -> String {
+    let tokens = Tokenizer::new().tokenize(source).unwrap();
+    let parse_tree = Parser::new().parse(tokens, "Main.jack").unwrap();
+    XmlWriter::new().write(&parse_tree)
+}
+
+#[test]
+fn unary_term_followed_by_operator_test() {
+    let xml = parse_str("class Main { function int f(int x) { return -x + 1; } }");
+    assert!(xml.contains(
+        "<term>\n<symbol> - </symbol>\n<term>\n<identifier> x </identifier>\n</term>\n</term>\n<symbol> + </symbol>\n<term>\n<integerConstant> 1 </integerConstant>\n</term>"
+    ));
+}
+
+#[test]
+fn deeply_nested_expression_test() {
+    let depth = 200;
+    let source = format!(
+        "class Main {{ function int f() {{ return {}1{}; }} }}",
+        "(".repeat(depth),
+        ")".repeat(depth)
+    );
+    let xml = parse_str(&source);
+    assert_eq!(xml.matches("<expression>").count(), depth + 1);
+}
+
+#[test]
+fn typed_ast_test() {
+    use jack_compiler::parser::parse_tree::expression::{BinaryOp, Const, Expr, UnaryOp};
+    use jack_compiler::parser::parse_tree::statement::{LetTarget, Statement};
+
+    let source = "class Main { function void f(Array a) { let a[i] = -x + (y * 2); return; } }";
+    let tokens = Tokenizer::new().tokenize(source).unwrap();
+    let parse_tree = Parser::new().parse(tokens, "Main.jack").unwrap();
+    let statement = &parse_tree.class_node.subroutines[0].body.statements[0];
+    let let_statement = match statement {
+        Statement::Let(let_statement) => let_statement,
+        _ => panic!("expected let statement"),
+    };
+    assert!(matches!(
+        &let_statement.target,
+        LetTarget::Index { name, index } if name == "a" && matches!(index, Expr::Var(i) if i == "i")
+    ));
+    match &let_statement.value {
+        Expr::Binary { lhs, op: BinaryOp::Add, rhs } => {
+            assert!(matches!(
+                lhs.as_ref(),
+                Expr::Unary { op: UnaryOp::Neg, operand } if matches!(operand.as_ref(), Expr::Var(x) if x == "x")
+            ));
+            assert!(matches!(
+                rhs.as_ref(),
+                Expr::Paren(inner) if matches!(
+                    inner.as_ref(),
+                    Expr::Binary { op: BinaryOp::Mul, rhs, .. } if matches!(rhs.as_ref(), Expr::Const(Const::Integer(2)))
+                )
+            ));
+        }
+        value => panic!("expected binary expression, found {:?}", value),
+    }
+
+    let xml = XmlWriter::new().write(&parse_tree);
+    assert!(xml.contains(
+        "<parameterList>\n<identifier> Array </identifier>\n<identifier> a </identifier>\n</parameterList>"
+    ));
+}
+
+/// Runs the compiler on a Jack file with the given contents and returns its error output.
+fn compile_error(file_name: &str, source: &str, args: &[&str]) -> Option<String> {
+    let dir = std::env::temp_dir().join(format!("jack_parser_test_{}", file_name.replace(".jack", "")));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(file_name);
+    fs::write(&path, source).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_jack_compiler"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    if output.status.success() {
+        None
+    } else {
+        Some(String::from_utf8(output.stderr).unwrap())
+    }
+}
+
+#[test]
+fn class_per_file_test() {
+    let error = compile_error("Main.jack", "class Main { }\nclass Other { }\n", &[]).unwrap();
+    assert!(error.contains("line 2, column 1: Only one class is allowed per file"));
+    let error = compile_error("Trailing.jack", "class Trailing { } }", &[]).unwrap();
+    assert!(error.contains("line 1, column 20: Unexpected token after the end of the class"));
+}
+
+#[test]
+fn file_name_mismatch_test() {
+    let source = "class Other { }";
+    let error = compile_error("Mismatch.jack", source, &[]).unwrap();
+    assert!(error.contains("line 1, column 7: Class name \"Other\" must match file name \"Mismatch\""));
+    assert_eq!(compile_error("Mismatch.jack", source, &["--allow-file-name-mismatch"]), None);
+
+    let tokens = Tokenizer::new().tokenize(source).unwrap();
+    let parse_tree = Parser::new()
+        .allow_file_name_mismatch(true)
+        .parse(tokens, "Main.jack")
+        .unwrap();
+    assert_eq!(parse_tree.class_node.name, "Other");
+}
+
+#[test]
+fn parse_error_test() {
+    let tokens = Tokenizer::new().tokenize("class Main {\n  field int x\n}").unwrap();
+    let error = Parser::new().parse(tokens, "Main.jack").err().unwrap();
+    assert_eq!(error.position.line, 3);
+    assert_eq!(error.position.column, 1);
+    assert_eq!(error.line, "}");
+    assert_eq!(
+        error.to_string(),
+        "Parse Error in line 3, column 1: Expected ';' but found '}': }"
+    );
+
+    let error = Parser::new().parse(Vec::new(), "Main.jack").err().unwrap();
+    assert_eq!(error.to_string(), "Parse Error: Expected 'class' but found end of file");
+}
+
+#[test]
+fn keyword_kind_test() {
+    assert!(matches!(SubroutineType::get(&Keyword::Method), Some(SubroutineType::Method)));
+    assert!(SubroutineType::get(&Keyword::Var).is_none());
+    assert!(matches!(VarKind::get(&Keyword::Field), Some(VarKind::Field)));
+    assert!(VarKind::get(&Keyword::Function).is_none());
+}
@@ -0,0 +1,82 @@
+use jack_compiler::emulator::{Emulator, Program, Stop};
+use jack_compiler::profiler::{ProfileEntry, Profiler};
+
+/// `Main.f(n)` calls itself until `n` is 0, so `Main.f(3)` makes four calls.
+const RECURSION: &str = "
+function Sys.init 0
+call Main.main 0
+pop temp 0
+call Sys.halt 0
+function Sys.halt 0
+label LOOP
+goto LOOP
+function Main.main 0
+push constant 3
+call Main.f 1
+return
+function Main.f 0
+push argument 0
+push constant 0
+eq
+if-goto END
+push argument 0
+push constant 1
+sub
+call Main.f 1
+return
+label END   // 7 instructions when n is 0, 10 otherwise
+push constant 0
+return
+";
+
+fn profile(vm_code: &str) -> Profiler {
+    let mut program = Program::new();
+    program.add_file("Main.vm", vm_code).unwrap();
+    let mut profiler = Profiler::new(&program);
+    let mut emulator = Emulator::new(&program);
+    assert_eq!(emulator.run(&mut profiler, 1000).unwrap(), Stop::Halted);
+    assert_eq!(emulator.executed(), profiler.total());
+    profiler
+}
+
+#[test]
+fn flat_profile_test() {
+    let profiler = profile(RECURSION);
+    assert_eq!(profiler.total(), 45);
+    let entry = |name: &str, inclusive, exclusive, calls| ProfileEntry {
+        name: name.to_string(),
+        inclusive,
+        exclusive,
+        calls,
+    };
+    // recursive calls are counted once in the inclusive instructions
+    assert_eq!(
+        profiler.entries(),
+        vec![
+            entry("Main.f", 37, 37, 4),
+            entry("Main.main", 41, 4, 1),
+            entry("Sys.init", 45, 4, 1),
+        ]
+    );
+    assert_eq!(
+        profiler.flat_profile(),
+        " inclusive       %  exclusive       %     calls  subroutine\n        \
+                 37   82.2%         37   82.2%         4  Main.f\n        \
+                 41   91.1%          4    8.9%         1  Main.main\n        \
+                 45  100.0%          4    8.9%         1  Sys.init\n"
+    );
+}
+
+#[test]
+fn folded_stacks_test() {
+    let profiler = profile(RECURSION);
+    assert_eq!(
+        profiler.folded_stacks(),
+        "Sys.init 4\n\
+         Sys.init;Main.main 4\n\
+         Sys.init;Main.main;Main.f 10\n\
+         Sys.init;Main.main;Main.f;Main.f 10\n\
+         Sys.init;Main.main;Main.f;Main.f;Main.f 10\n\
+         Sys.init;Main.main;Main.f;Main.f;Main.f;Main.f 7\n"
+    );
+}
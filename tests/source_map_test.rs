@@ -0,0 +1,41 @@
+use jack_compiler::io;
+use jack_compiler::tokenizer::Tokenizer;
+use jack_compiler::parser::Parser;
+use jack_compiler::vm_writer::VMWriter;
+use jack_compiler::vm_writer::source_map::SourceMap;
+
+fn compile(path_to_jack_file: &str) -> (String, SourceMap) {
+    let tokenizer = Tokenizer::new();
+    let parser = Parser::new();
+    let vm_writer = VMWriter::new();
+    let source = io::read_file(path_to_jack_file).unwrap();
+    let tokens = tokenizer.tokenize(&source).unwrap();
+    let parse_tree = parser.parse(tokens, path_to_jack_file).unwrap();
+    vm_writer.write_with_source_map(&parse_tree).unwrap()
+}
+
+#[test]
+fn seven_test() {
+    let (vm_code, source_map) = compile("tests/aux_files/Seven/Main.jack");
+    assert_eq!(vm_code.lines().count(), source_map.entries.len());
+    let lines: Vec<usize> = source_map.entries.iter().map(|e| e.position.line).collect();
+    assert_eq!(lines, vec![12, 13, 13, 13, 13, 13, 13, 13, 14, 14]);
+    let entry = source_map.get(1).unwrap();
+    assert_eq!(entry.subroutine, "Main.main");
+    assert_eq!(entry.position.column, 7);
+    assert_eq!(source_map.first_instruction_of_line(14), Some(8));
+}
+
+#[test]
+fn pong_test() {
+    for class in ["Ball", "Bat", "Main", "PongGame"] {
+        let (vm_code, source_map) = compile(&format!("tests/aux_files/Pong/{}.jack", class));
+        assert_eq!(vm_code.lines().count(), source_map.entries.len());
+        for (instruction, entry) in vm_code.lines().zip(source_map.entries.iter()) {
+            assert!(entry.subroutine.starts_with(&format!("{}.", class)));
+            if let Some(name) = instruction.strip_prefix("function ") {
+                assert!(name.starts_with(&entry.subroutine));
+            }
+        }
+    }
+}
@@ -2,11 +2,14 @@ use std::fs;
 
 use jack_compiler::io;
 use jack_compiler::tokenizer::Tokenizer;
+use jack_compiler::tokenizer::error::LexErrorKind;
+use jack_compiler::tokenizer::stream::TokenStream;
+use jack_compiler::tokenizer::tokens::{Constant, Keyword, Symbol, Token};
 
 fn compare(path_to_jack_file: &str, path_to_target_xml: &str) -> (String, String) {
     let tokenizer = Tokenizer::new();
-    let lines = io::read_file(path_to_jack_file);
-    let tokens = tokenizer.tokenize(lines);
+    let source = io::read_file(path_to_jack_file).unwrap();
+    let tokens = tokenizer.tokenize(&source).unwrap();
     let xml = tokenizer.write_xml(tokens);
     let target_xml = fs::read_to_string(path_to_target_xml)
         .expect("Reading target file failed.");
@@ -75,3 +78,148 @@ fn expr_less_square_square_game_test() {
     );
     assert_eq!(xml, target_xml);
 }
+
+fn token_strings(source: &str) -> Vec<String> {
+    let tokenizer = Tokenizer::new();
+    tokenizer
+        .tokenize(source)
+        .unwrap()
+        .iter()
+        .map(|t| match &t.token {
+            Token::Symbol(s) => s.to_string(),
+            Token::Keyword(k) => k.to_string(),
+            Token::Identifier(s) => s.clone(),
+            Token::Constant(Constant::StringConstant(s)) => format!("\"{}\"", s),
+            Token::Constant(Constant::IntegerConstant(i)) => i.to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn comment_inside_string_test() {
+    assert_eq!(
+        token_strings("do Output.printString(\"http://x\"); // comment"),
+        vec!["do", "Output", ".", "printString", "(", "\"http://x\"", ")", ";"]
+    );
+}
+
+#[test]
+fn block_comment_mid_line_test() {
+    assert_eq!(
+        token_strings("let x = 1; /* note */ let y = 2;"),
+        vec!["let", "x", "=", "1", ";", "let", "y", "=", "2", ";"]
+    );
+    assert_eq!(
+        token_strings("let x /* a\n comment */ = 1;\n/** doc */ return;"),
+        vec!["let", "x", "=", "1", ";", "return", ";"]
+    );
+}
+
+#[test]
+fn line_and_column_test() {
+    let tokenizer = Tokenizer::new();
+    let tokens = tokenizer
+        .tokenize("class Main {\r\n\tfield int x;\r\n}\r\n")
+        .unwrap();
+    let positions: Vec<(usize, usize)> = tokens
+        .iter()
+        .map(|t| (t.position().line, t.position().column))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![(1, 1), (1, 7), (1, 12), (2, 2), (2, 8), (2, 12), (2, 13), (3, 1)]
+    );
+    assert_eq!(tokens[3].line.content, "field int x;");
+}
+
+fn lex_error(source: &str) -> (LexErrorKind, usize, usize) {
+    let tokenizer = Tokenizer::new();
+    let error = tokenizer.tokenize(source).unwrap_err();
+    (error.kind, error.position.line, error.position.column)
+}
+
+#[test]
+fn lex_errors_test() {
+    assert_eq!(
+        lex_error("do Output.printString(\"abc);"),
+        (LexErrorKind::UnterminatedString, 1, 23)
+    );
+    assert_eq!(
+        lex_error("let s = \"abc\nd\";"),
+        (LexErrorKind::NewlineInString, 1, 9)
+    );
+    assert_eq!(
+        lex_error("let a@b = 1;"),
+        (LexErrorKind::IllegalCharacter('@'), 1, 6)
+    );
+    assert_eq!(
+        lex_error("let x = 1;\n  let 1x = 2;"),
+        (LexErrorKind::IdentifierStartsWithDigit("1x".to_string()), 2, 7)
+    );
+    assert_eq!(
+        lex_error("let x = 32768;"),
+        (LexErrorKind::IntegerOutOfRange("32768".to_string()), 1, 9)
+    );
+    assert_eq!(
+        lex_error("let x = 1;\n/* comment"),
+        (LexErrorKind::UnterminatedComment, 2, 1)
+    );
+    assert!(Tokenizer::new().tokenize("let x = 32767;").is_ok());
+}
+
+#[test]
+fn lex_error_message_test() {
+    let error = Tokenizer::new().tokenize("class Main {\n   let x = $;\n}").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Lex Error in line 2, column 12: Illegal character '$': let x = $;"
+    );
+}
+
+#[test]
+fn typed_tokens_test() {
+    let tokens = Tokenizer::new().tokenize("if (x < true) { let y = ~y; }").unwrap();
+    assert_eq!(tokens[0].token, Token::Keyword(Keyword::If));
+    assert_eq!(tokens[3].token, Token::Symbol(Symbol::LessThan));
+    assert_eq!(tokens[4].token, Token::Keyword(Keyword::True));
+    assert_eq!(tokens[10].token, Token::Symbol(Symbol::Tilde));
+
+    assert_eq!("while".parse::<Keyword>(), Ok(Keyword::While));
+    assert!("While".parse::<Keyword>().is_err());
+    assert_eq!(Keyword::Constructor.to_string(), "constructor");
+    assert_eq!("&".parse::<Symbol>(), Ok(Symbol::Ampersand));
+    assert_eq!(Symbol::Ampersand.to_xml(), "&amp;");
+}
+
+#[test]
+fn token_stream_test() {
+    let mut stream = Tokenizer::new().stream("let a[i] = x; // done");
+    assert_eq!(
+        stream.peek().unwrap().as_ref().unwrap().token,
+        Token::Keyword(Keyword::Let)
+    );
+    assert_eq!(
+        stream.peek_nth(2).unwrap().as_ref().unwrap().token,
+        Token::Symbol(Symbol::LeftBracket)
+    );
+    assert!(stream.peek_nth(7).is_some());
+    assert!(stream.peek_nth(8).is_none());
+
+    let first = stream.next().unwrap().unwrap();
+    assert_eq!(first.token, Token::Keyword(Keyword::Let));
+    assert_eq!(
+        stream.peek().unwrap().as_ref().unwrap().token,
+        Token::Identifier("a".to_string())
+    );
+    assert_eq!(stream.count(), 7);
+}
+
+#[test]
+fn token_stream_stops_at_error_test() {
+    let mut stream = TokenStream::new("let $ = 1;");
+    assert!(stream.next().unwrap().is_ok());
+    let error = stream.next().unwrap().unwrap_err();
+    assert_eq!(error.kind, LexErrorKind::IllegalCharacter('$'));
+    assert!(stream.peek().is_none());
+    assert!(stream.next().is_none());
+}
@@ -0,0 +1,83 @@
+use jack_compiler::io;
+use jack_compiler::parser::parse_tree::expression::{BinaryOp, Const, Expr, SubroutineCall};
+use jack_compiler::parser::parse_tree::statement::Statement;
+use jack_compiler::parser::parse_tree::visit::{self, Visitor, VisitorMut};
+use jack_compiler::parser::parse_tree::ParseTree;
+use jack_compiler::parser::Parser;
+use jack_compiler::tokenizer::Tokenizer;
+use jack_compiler::vm_writer::VMWriter;
+
+fn parse(source: &str, file: &str) -> ParseTree {
+    let tokens = Tokenizer::new().tokenize(source).unwrap();
+    Parser::new().parse(tokens, file).unwrap()
+}
+
+/// Counts the calls of each subroutine.
+#[derive(Default)]
+struct CallCounter {
+    calls: Vec<String>,
+    statements: usize,
+}
+
+impl Visitor for CallCounter {
+    fn visit_statement(&mut self, statement: &Statement) {
+        self.statements += 1;
+        visit::walk_statement(self, statement);
+    }
+
+    fn visit_subroutine_call(&mut self, subroutine_call: &SubroutineCall) {
+        let name = match &subroutine_call.caller {
+            Some(caller) => format!("{}.{}", caller, subroutine_call.subroutine_name),
+            None => subroutine_call.subroutine_name.clone(),
+        };
+        self.calls.push(name);
+        visit::walk_subroutine_call(self, subroutine_call);
+    }
+}
+
+#[test]
+fn visitor_test() {
+    let path = "tests/aux_files/Square/Main.jack";
+    let parse_tree = parse(&io::read_file(path).unwrap(), path);
+    let mut counter = CallCounter::default();
+    counter.visit_class(&parse_tree.class_node);
+    assert_eq!(
+        counter.calls,
+        vec!["SquareGame.new", "game.run", "game.dispose"]
+    );
+    assert!(counter.statements > 3);
+}
+
+/// Folds additions of integer constants, e.g. `1 + 2` becomes `3`.
+struct ConstantFolder;
+
+impl VisitorMut for ConstantFolder {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit::walk_expr_mut(self, expr);
+        if let Expr::Binary {
+            lhs,
+            op: BinaryOp::Add,
+            rhs,
+        } = expr
+        {
+            if let (Expr::Const(Const::Integer(a)), Expr::Const(Const::Integer(b))) =
+                (lhs.as_ref(), rhs.as_ref())
+            {
+                *expr = Expr::Const(Const::Integer(a + b));
+            }
+        }
+    }
+}
+
+#[test]
+fn visitor_mut_test() {
+    let mut parse_tree = parse(
+        "class Main { function int f() { return 1 + 2 + 3; } }",
+        "Main.jack",
+    );
+    ConstantFolder.visit_class_mut(&mut parse_tree.class_node);
+    assert_eq!(
+        VMWriter::new().write(&parse_tree).unwrap(),
+        "function Main.f 0\npush constant 6\nreturn\n"
+    );
+}
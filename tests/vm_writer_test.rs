@@ -9,10 +9,10 @@ fn compare(path_to_jack_file: &str, path_to_target_vm: &str) -> (String, String)
     let tokenizer = Tokenizer::new();
     let parser = Parser::new();
     let vm_writer = VMWriter::new();
-    let lines = io::read_file(path_to_jack_file);
-    let tokens = tokenizer.tokenize(lines);
-    let parse_tree = parser.parse(tokens, path_to_jack_file);
-    let vm_code = vm_writer.write(&parse_tree);
+    let source = io::read_file(path_to_jack_file).unwrap();
+    let tokens = tokenizer.tokenize(&source).unwrap();
+    let parse_tree = parser.parse(tokens, path_to_jack_file).unwrap();
+    let vm_code = vm_writer.write(&parse_tree).unwrap();
     let target_vm = fs::read_to_string(path_to_target_vm)
         .expect("Reading target file failed.");
     (vm_code, target_vm)
@@ -143,3 +143,35 @@ fn complex_arrays_test() {
     );
     assert_eq!(vm_code, target_vm);
 }
+
+#[test]
+fn annotated_square_square_test() {
+    let tokenizer = Tokenizer::new();
+    let parser = Parser::new();
+    let vm_writer = VMWriter::new().annotate(true);
+    let source = io::read_file("tests/aux_files/Square/Square.jack").unwrap();
+    let tokens = tokenizer.tokenize(&source).unwrap();
+    let parse_tree = parser.parse(tokens, "tests/aux_files/Square/Square.jack").unwrap();
+    let vm_code = vm_writer.write(&parse_tree).unwrap();
+    assert!(vm_code.starts_with(
+        "// tests/aux_files/Square/Square.jack:15: constructor Square new(int Ax, int Ay, int Asize) {\n\
+         function Square.new 0\n"
+    ));
+    assert!(vm_code.contains("// tests/aux_files/Square/Square.jack:16: let x = Ax;\npush argument 0\npop this 0\n"));
+
+    // without the comments, the annotated code is the same as the regular code
+    let mut stripped: Vec<&str> = vm_code.lines().filter(|l| !l.starts_with("//")).collect();
+    stripped.push("");
+    let target_vm = fs::read_to_string("tests/aux_files/Square/Square.vm")
+        .expect("Reading target file failed.");
+    assert_eq!(stripped.join("\n"), target_vm);
+
+    // the comments name the Jack file, even if the class is named differently
+    let tokens = tokenizer.tokenize("class Main {\n  function void main() { return; }\n}").unwrap();
+    let parse_tree = Parser::new()
+        .allow_file_name_mismatch(true)
+        .parse(tokens, "src/Other.jack")
+        .unwrap();
+    let vm_code = vm_writer.write(&parse_tree).unwrap();
+    assert!(vm_code.starts_with("// src/Other.jack:2: function void main() { return; }\nfunction Main.main 0\n"));
+}